@@ -1,7 +1,10 @@
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use serde::Deserialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// Loads assistant persona metadata from `ai/personas.json`.
 pub fn load_personas(base_dir: impl AsRef<Path>) -> Result<Vec<Persona>> {
@@ -21,6 +24,124 @@ pub fn load_bugs(base_dir: impl AsRef<Path>) -> Result<Vec<Bug>> {
     read_json(path)
 }
 
+/// Loads the GraphQL joke catalogue from `ai/jokes.json`.
+pub fn load_jokes(base_dir: impl AsRef<Path>) -> Result<Vec<Joke>> {
+    let path = resolve(base_dir, "jokes.json");
+    read_json(path)
+}
+
+/// Writes the task part of the weekly Markdown report: unfinished and solved task tables.
+pub fn write_task_summary(board: &TaskBoard, writer: &mut dyn Write) -> Result<()> {
+    writeln!(writer, "## Tasks (board generated {})", board.generated_at)?;
+    writeln!(writer)?;
+    writeln!(writer, "### Unfinished ({})", board.unfinished.len())?;
+    writeln!(writer)?;
+    writeln!(writer, "| ID | Title | Owner | Blocked on |")?;
+    writeln!(writer, "|---|---|---|---|")?;
+    for task in &board.unfinished {
+        writeln!(
+            writer,
+            "| {} | {} | {} | {} |",
+            cell(&task.id),
+            cell(&task.title),
+            cell(task.owner.as_deref().unwrap_or("-")),
+            task.blocked_on.len()
+        )?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "### Solved ({})", board.solved.len())?;
+    writeln!(writer)?;
+    writeln!(writer, "| ID | Title | Resolution |")?;
+    writeln!(writer, "|---|---|---|")?;
+    for task in &board.solved {
+        writeln!(
+            writer,
+            "| {} | {} | {} |",
+            cell(&task.id),
+            cell(&task.title),
+            cell(task.resolution.as_deref().unwrap_or("-"))
+        )?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes the bug part of the weekly Markdown report, grouped by label.
+pub fn write_bug_summary(bugs: &[Bug], writer: &mut dyn Write) -> Result<()> {
+    let mut by_label: BTreeMap<&str, Vec<&Bug>> = BTreeMap::new();
+    for bug in bugs {
+        if bug.labels.is_empty() {
+            by_label.entry("unlabeled").or_default().push(bug);
+        }
+        for label in &bug.labels {
+            by_label.entry(label).or_default().push(bug);
+        }
+    }
+
+    writeln!(writer, "## Bugs ({})", bugs.len())?;
+    writeln!(writer)?;
+    for (label, bugs) in by_label {
+        writeln!(writer, "### {label}")?;
+        writeln!(writer)?;
+        for bug in bugs {
+            writeln!(
+                writer,
+                "- **{}** [{}] {} ({})",
+                bug.id,
+                bug.status,
+                bug.title,
+                bug.owner.as_deref().unwrap_or("unassigned")
+            )?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Whether any of the persona's strengths shows up in the task's title or description.
+pub fn matches_task(persona: &Persona, task: &Task) -> bool {
+    strength_score(persona, task) > 0.0
+}
+
+/// Ranks personas by the share of their strengths that match the task, best first.
+///
+/// A strength matches when it appears (case-insensitively) in the task title or description.
+pub fn rank_personas_for_task<'a>(personas: &'a [Persona], task: &Task) -> Vec<(&'a Persona, f64)> {
+    let mut ranked: Vec<_> = personas
+        .iter()
+        .map(|persona| (persona, strength_score(persona, task)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+fn strength_score(persona: &Persona, task: &Task) -> f64 {
+    if persona.strengths.is_empty() {
+        return 0.0;
+    }
+    let haystack = format!("{} {}", task.title, task.description).to_lowercase();
+    let matched = persona
+        .strengths
+        .iter()
+        .filter(|strength| haystack.contains(&strength.to_lowercase()))
+        .count();
+    matched as f64 / persona.strengths.len() as f64
+}
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Writes the task board back to `ai/tasks.json`.
+pub fn save_tasks(base_dir: impl AsRef<Path>, board: &TaskBoard) -> Result<()> {
+    write_json(resolve(base_dir, "tasks.json"), board)
+}
+
+/// Writes the bug ledger back to `ai/bugs.json`.
+pub fn save_bugs(base_dir: impl AsRef<Path>, bugs: &[Bug]) -> Result<()> {
+    write_json(resolve(base_dir, "bugs.json"), bugs)
+}
+
 fn resolve(base_dir: impl AsRef<Path>, file: &str) -> PathBuf {
     base_dir.as_ref().join("ai").join(file)
 }
@@ -33,7 +154,13 @@ where
     Ok(serde_json::from_str(&data)?)
 }
 
-#[derive(Debug, Deserialize)]
+fn write_json<T: Serialize + ?Sized>(path: PathBuf, value: &T) -> Result<()> {
+    let mut data = serde_json::to_string_pretty(value)?;
+    data.push('\n');
+    std::fs::write(&path, data).with_context(|| format!("writing {}", path.display()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Persona {
     pub id: String,
     pub name: String,
@@ -42,16 +169,19 @@ pub struct Persona {
     pub strengths: Vec<String>,
     #[serde(default)]
     pub notes: String,
+    /// Fields not modelled above, kept so saving does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TaskBoard {
     pub generated_at: String,
     pub unfinished: Vec<Task>,
     pub solved: Vec<Task>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Task {
     pub id: String,
     pub title: String,
@@ -60,11 +190,32 @@ pub struct Task {
     pub blocked_on: Vec<String>,
     #[serde(default)]
     pub owner: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub resolution: Option<String>,
+    /// Fields not modelled above, kept so saving does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Joke {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+impl Joke {
+    pub fn new(id: impl Into<String>, package: Option<&str>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            package: package.map(|pkg| pkg.to_string()),
+            text: text.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Bug {
     pub id: String,
     pub title: String,
@@ -76,4 +227,53 @@ pub struct Bug {
     pub created_at: Option<String>,
     #[serde(default)]
     pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    /// Fields not modelled above, kept so saving does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn persona(id: &str, strengths: &[&str]) -> Persona {
+        Persona {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            strengths: strengths.iter().map(|s| s.to_string()).collect(),
+            notes: String::new(),
+            extra: Map::new(),
+        }
+    }
+
+    #[test]
+    fn personas_are_ranked_by_matching_strengths() {
+        let personas = [
+            persona("designer", &["TUI layout", "colour themes"]),
+            persona("builder", &["Cross toolchain", "binutils"]),
+            persona("packager", &["binutils", "release notes"]),
+        ];
+        let task = Task {
+            id: "t1".to_string(),
+            title: "Fix Binutils pass 1".to_string(),
+            description: "The cross toolchain build stops after configure.".to_string(),
+            blocked_on: Vec::new(),
+            owner: None,
+            resolution: None,
+            extra: Map::new(),
+        };
+
+        let ranked: Vec<_> = rank_personas_for_task(&personas, &task)
+            .into_iter()
+            .map(|(persona, score)| (persona.id.as_str(), score))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![("builder", 1.0), ("packager", 0.5), ("designer", 0.0)]
+        );
+        assert!(!matches_task(&personas[0], &task));
+    }
 }
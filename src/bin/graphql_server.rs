@@ -2,13 +2,20 @@
 
 use std::env;
 use std::sync::Arc;
+use std::time::Instant;
 
+use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::{App, HttpRequest, HttpResponse, HttpServer, middleware::Compress, web};
 use anyhow::{Context, Result};
+use juniper::http::GraphQLBatchRequest;
 use juniper_actix::{graphiql_handler, graphql_handler};
+use tokio_stream::StreamExt;
 
 use package_management::db;
-use package_management::graphql::{self, GraphQLContext, Schema};
+use package_management::graphql::schema::progress_events;
+use package_management::graphql::{
+    self, BookCatalogCache, DownloadProgressHub, GraphQLContext, Schema,
+};
 
 const DEFAULT_BIND_ADDR: &str = "127.0.0.1:8080";
 
@@ -16,19 +23,22 @@ const DEFAULT_BIND_ADDR: &str = "127.0.0.1:8080";
 async fn main() -> std::io::Result<()> {
     if let Err(err) = run().await {
         eprintln!("GraphQL server failed: {err:#}");
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            err.to_string(),
-        ));
+        return Err(std::io::Error::other(err.to_string()));
     }
 
     Ok(())
 }
 
 async fn run() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
     let pool = db::establish_pool().context("initialising SQLite pool")?;
     let schema = Arc::new(graphql::create_schema());
     let jokes = Arc::new(graphql::context::JokeCatalog::default());
+    let progress = DownloadProgressHub::default();
+    let book_catalogs = BookCatalogCache::default();
     let bind_addr = env::var("LPKG_GRAPHQL_ADDR").unwrap_or_else(|_| DEFAULT_BIND_ADDR.to_string());
     let workers = worker_count();
 
@@ -38,18 +48,26 @@ async fn run() -> Result<()> {
         let app_schema = Arc::clone(&schema);
         let pool = pool.clone();
         let jokes = Arc::clone(&jokes);
+        let progress = progress.clone();
+        let book_catalogs = book_catalogs.clone();
 
         App::new()
             .app_data(web::Data::from(app_schema))
             .app_data(web::Data::new(pool))
             .app_data(web::Data::from(jokes))
+            .app_data(web::Data::new(progress))
+            .app_data(web::Data::new(book_catalogs))
             .wrap(Compress::default())
             .service(
                 web::resource("/graphql")
-                    .route(web::post().to(graphql_endpoint))
-                    .route(web::get().to(graphql_endpoint)),
+                    .route(web::post().to(graphql_post_endpoint))
+                    .route(web::get().to(graphql_get_endpoint)),
             )
             .service(web::resource("/playground").route(web::get().to(graphiql_endpoint)))
+            .service(
+                web::resource("/events/downloads/{session_id}")
+                    .route(web::get().to(download_events_endpoint)),
+            )
     })
     .workers(workers)
     .bind(&bind_addr)
@@ -59,15 +77,118 @@ async fn run() -> Result<()> {
     .context("running GraphQL server")
 }
 
-async fn graphql_endpoint(
-    schema: web::Data<Arc<Schema>>,
+async fn graphql_get_endpoint(
+    schema: web::Data<Schema>,
     pool: web::Data<db::Pool>,
-    jokes: web::Data<Arc<graphql::context::JokeCatalog>>,
+    jokes: web::Data<graphql::context::JokeCatalog>,
+    progress: web::Data<DownloadProgressHub>,
+    book_catalogs: web::Data<BookCatalogCache>,
     req: HttpRequest,
     payload: web::Payload,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let context = GraphQLContext::with_catalog(pool.get_ref().clone(), Arc::clone(jokes.get_ref()));
-    graphql_handler(schema.get_ref().as_ref(), &context, req, payload).await
+    let started = Instant::now();
+    let context = request_context(&req, &pool, &jokes, &progress, &book_catalogs);
+    let operation_name = url::form_urlencoded::parse(req.query_string().as_bytes())
+        .find(|(key, _)| key == "operationName")
+        .map(|(_, value)| value.into_owned());
+
+    let mut response = graphql_handler(schema.get_ref(), &context, req, payload).await?;
+    finish_request(&context, operation_name.as_deref(), started, &mut response);
+    Ok(response)
+}
+
+async fn graphql_post_endpoint(
+    schema: web::Data<Schema>,
+    pool: web::Data<db::Pool>,
+    jokes: web::Data<graphql::context::JokeCatalog>,
+    progress: web::Data<DownloadProgressHub>,
+    book_catalogs: web::Data<BookCatalogCache>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let started = Instant::now();
+    let context = request_context(&req, &pool, &jokes, &progress, &book_catalogs);
+
+    let request: GraphQLBatchRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            let mut response = HttpResponse::BadRequest().body(err.to_string());
+            finish_request(&context, None, started, &mut response);
+            return response;
+        }
+    };
+    let operation_name = match &request {
+        GraphQLBatchRequest::Single(single) => single.operation_name.clone(),
+        GraphQLBatchRequest::Batch(_) => Some("<batch>".to_string()),
+    };
+
+    let result = request.execute(schema.get_ref(), &context).await;
+    let mut response = if result.is_ok() {
+        HttpResponse::Ok().json(&result)
+    } else {
+        HttpResponse::BadRequest().json(&result)
+    };
+    finish_request(&context, operation_name.as_deref(), started, &mut response);
+    response
+}
+
+fn request_context(
+    req: &HttpRequest,
+    pool: &web::Data<db::Pool>,
+    jokes: &web::Data<graphql::context::JokeCatalog>,
+    progress: &web::Data<DownloadProgressHub>,
+    book_catalogs: &web::Data<BookCatalogCache>,
+) -> GraphQLContext {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    GraphQLContext::with_catalog(pool.get_ref().clone(), jokes.clone().into_inner())
+        .with_auth_token(token)
+        .with_progress_hub(progress.get_ref().clone())
+        .with_book_catalogs(book_catalogs.get_ref().clone())
+}
+
+/// Log the request at INFO level and tag the response with `X-Request-Id`.
+fn finish_request(
+    context: &GraphQLContext,
+    operation_name: Option<&str>,
+    started: Instant,
+    response: &mut HttpResponse,
+) {
+    let request_id = context.request_id().to_string();
+    tracing::info!(
+        request_id = %request_id,
+        operation_name = operation_name.unwrap_or("<anonymous>"),
+        duration_ms = started.elapsed().as_millis() as u64,
+        status = response.status().as_u16(),
+        "graphql request"
+    );
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static("x-request-id"), value);
+    }
+}
+
+/// Server-Sent Events feed of `downloadProgress` events for one session.
+async fn download_events_endpoint(
+    session_id: web::Path<String>,
+    progress: web::Data<DownloadProgressHub>,
+) -> HttpResponse {
+    let stream = progress_events(progress.get_ref(), &session_id).filter_map(|event| {
+        let payload = serde_json::to_string(&event).ok()?;
+        Some(Ok::<_, actix_web::Error>(web::Bytes::from(format!(
+            "data: {payload}\n\n"
+        ))))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream)
 }
 
 async fn graphiql_endpoint() -> Result<HttpResponse, actix_web::Error> {
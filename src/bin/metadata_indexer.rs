@@ -1,18 +1,29 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
+use console::Style;
 use jsonschema::JSONSchema;
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
 use regex::Regex;
 use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
 use serde_json::{Value, json};
 use sha2::{Digest, Sha256};
 use walkdir::WalkDir;
 
-use package_management::pkgs::generator;
+use package_management::html;
+use package_management::ingest::parse_numeric;
+use package_management::pkgs::generator::{self, GenerateOutcome};
+use package_management::wget_list::{ManifestKind, manifest_url};
 
 #[derive(Parser)]
 #[command(
@@ -24,6 +35,11 @@ struct Cli {
     #[arg(long, default_value = ".")]
     base_dir: PathBuf,
 
+    /// Use a mirror for one book's pages and manifests, e.g. `lfs=https://mirror.example/lfs`
+    /// (repeatable)
+    #[arg(long = "base-url-override", value_name = "BOOK=URL", value_parser = parse_base_url_override)]
+    base_url_overrides: Vec<(String, String)>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -46,19 +62,25 @@ enum Command {
         /// Force re-download even if cache files exist
         #[arg(long)]
         force: bool,
+        /// Write a JSON summary of the refreshed manifests to this path
+        #[arg(long)]
+        summary_output: Option<PathBuf>,
     },
     /// Fetch and draft metadata for a specific package page
     Harvest {
         /// Book identifier (lfs, mlfs, blfs, glfs)
         #[arg(long)]
         book: String,
-        /// Page path (relative to base) or full URL
+        /// Page path (relative to base) or full URL; repeat to harvest several pages
+        #[arg(long = "page", required_unless_present = "pages_file")]
+        pages: Vec<String>,
+        /// Text file listing one page path or URL per line (blank lines and `#` comments skipped)
         #[arg(long)]
-        page: String,
+        pages_file: Option<PathBuf>,
         /// Override base URL for the selected book
         #[arg(long)]
         base_url: Option<String>,
-        /// Optional explicit output file path
+        /// Output file path for a single page, or output directory when harvesting several
         #[arg(long)]
         output: Option<PathBuf>,
         /// Do not write to disk, just print JSON to stdout
@@ -77,10 +99,88 @@ enum Command {
         #[arg(long)]
         overwrite: bool,
     },
+    /// Print the module source that `generate` would write, without touching the filesystem
+    Preview {
+        /// Path to the harvested metadata JSON file
+        #[arg(long)]
+        metadata: PathBuf,
+    },
+    /// Compare two index.json files and report added, removed and changed packages
+    Diff {
+        /// Previous index.json
+        old: PathBuf,
+        /// Current index.json
+        new: PathBuf,
+        /// Also write the JSON diff document to this path
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Print a machine-readable JSON document instead of the colored summary
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report SBU totals per stage and metadata completeness counts
+    Stats {
+        /// Emit CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Sum the SBU values of one book into a total build-time estimate
+    EstimateBuild {
+        /// Book identifier (lfs, mlfs, blfs, glfs)
+        #[arg(long)]
+        book: String,
+        /// Only count packages of this stage (e.g. cross-toolchain)
+        #[arg(long)]
+        stage: Option<String>,
+        /// Wall-clock minutes per SBU on the build machine
+        #[arg(long, default_value_t = 10.0)]
+        minutes_per_sbu: f64,
+    },
+    /// Emit a Markdown table of build and runtime dependencies for one book
+    DepTable {
+        /// Book identifier (lfs, mlfs, blfs, glfs)
+        #[arg(long)]
+        book: String,
+        /// Write the table to this file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check that every indexed package's anchor URL answers HEAD with HTTP 200
+    ValidateAnchors {
+        /// Per-request timeout in milliseconds
+        #[arg(long, default_value_t = 10_000)]
+        timeout_ms: u64,
+        /// Number of concurrent requests
+        #[arg(long, default_value_t = 8)]
+        parallel: usize,
+    },
+    /// Watch ai/metadata/packages and re-run validate + index after every change
+    Watch {
+        /// Quiet period in milliseconds to wait after the last change before re-indexing
+        #[arg(long, default_value_t = 500)]
+        debounce_ms: u64,
+    },
+}
+
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Mirrors from `--base-url-override`, keyed by lower-case book name.
+type BaseUrlOverrides = HashMap<String, String>;
+
+fn parse_base_url_override(raw: &str) -> Result<(String, String), String> {
+    let (book, url) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected BOOK=URL, got '{raw}'"))?;
+    let url = url.trim().trim_end_matches('/');
+    if book.trim().is_empty() || url.is_empty() {
+        return Err(format!("expected BOOK=URL, got '{raw}'"));
+    }
+    Ok((book.trim().to_lowercase(), url.to_string()))
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let overrides: BaseUrlOverrides = cli.base_url_overrides.into_iter().collect();
     let base_dir = cli.base_dir.canonicalize().unwrap_or(cli.base_dir);
     let metadata_dir = base_dir.join("ai").join("metadata");
     let schema_path = metadata_dir.join("schema.json");
@@ -89,29 +189,7 @@ fn main() -> Result<()> {
     let (_schema_value, schema) = load_schema(&schema_path)?;
     let packages = scan_packages(&packages_dir)?;
 
-    let mut had_errors = false;
-    for package in &packages {
-        let validation = schema.validate(&package.value);
-        if let Err(errors) = validation {
-            had_errors = true;
-            eprintln!(
-                "Schema validation failed for {}:",
-                package.relative_path.display()
-            );
-            for err in errors {
-                eprintln!("  - {}", err);
-            }
-        }
-
-        if let Some(err) = &package.summary_error {
-            had_errors = true;
-            eprintln!(
-                "Summary extraction failed for {}: {}",
-                package.relative_path.display(),
-                err
-            );
-        }
-    }
+    let had_errors = report_validation_errors(&schema, &packages);
 
     match cli.command {
         Command::Validate => {
@@ -124,100 +202,124 @@ fn main() -> Result<()> {
                 anyhow::bail!("metadata validation failed; index not updated");
             }
 
-            let summaries: Vec<_> = packages
-                .iter()
-                .filter_map(|pkg| pkg.summary.clone())
-                .collect();
-
-            let schema_version = summaries
-                .first()
-                .map(|s| s.schema_version.as_str())
-                .unwrap_or("v0.0.0");
-
-            let generated_at = Utc::now().to_rfc3339();
-            let packages_json: Vec<Value> = summaries
-                .iter()
-                .map(|s| {
-                    json!({
-                        "id": s.id.clone(),
-                        "name": s.name.clone(),
-                        "version": s.version.clone(),
-                        "stage": s.stage.clone(),
-                        "book": s.book.clone(),
-                        "variant": s.variant.clone(),
-                        "status": s.status.clone(),
-                        "path": s.relative_path.clone(),
-                        "tags": s.tags.clone(),
-                    })
-                })
-                .collect();
-
-            let index = json!({
-                "generated_at": generated_at,
-                "schema_version": schema_version,
-                "packages": packages_json,
-            });
-
-            let index_path = metadata_dir.join("index.json");
-            let serialized = if compact {
-                serde_json::to_string(&index)?
-            } else {
-                serde_json::to_string_pretty(&index)?
-            };
-            fs::write(&index_path, serialized)
-                .with_context(|| format!("writing {}", index_path.display()))?;
+            let index_path = write_index(&metadata_dir, &packages, compact)?;
             println!("Updated {}", index_path.display());
         }
+        Command::Watch { debounce_ms } => {
+            watch_packages(
+                &metadata_dir,
+                &packages_dir,
+                &schema,
+                Duration::from_millis(debounce_ms),
+            )?;
+        }
         Command::Harvest {
             book,
-            page,
+            pages,
+            pages_file,
             base_url,
             output,
             dry_run,
         } => {
             let book_lower = book.to_lowercase();
-            let harvest = harvest_metadata(&metadata_dir, &book_lower, &page, base_url.as_deref())?;
-
-            if dry_run {
-                println!("{}", serde_json::to_string_pretty(&harvest.value)?);
-            } else {
-                let output_path = output.unwrap_or_else(|| {
-                    metadata_dir
-                        .join("packages")
-                        .join(&book_lower)
-                        .join(format!("{}.json", harvest.slug))
+            let mut pages = pages;
+            if let Some(path) = &pages_file {
+                pages.extend(read_pages_file(path)?);
+            }
+            if pages.is_empty() {
+                anyhow::bail!("no pages to harvest");
+            }
+            // A single page keeps the historical meaning of --output as the target file;
+            // batches treat it as the directory every <slug>.json is written to.
+            let batch = pages.len() > 1;
+
+            let mut harvested = 0usize;
+            let mut failed = Vec::new();
+            for page in &pages {
+                let result = harvest_metadata(
+                    &metadata_dir,
+                    &overrides,
+                    &book_lower,
+                    page,
+                    base_url.as_deref(),
+                )
+                .and_then(|harvest| {
+                    if dry_run {
+                        println!("{}", serde_json::to_string_pretty(&harvest.value)?);
+                        return Ok(());
+                    }
+                    let output_path = match &output {
+                        Some(path) if !batch => path.clone(),
+                        Some(dir) => dir.join(format!("{}.json", harvest.slug)),
+                        None => metadata_dir
+                            .join("packages")
+                            .join(&book_lower)
+                            .join(format!("{}.json", harvest.slug)),
+                    };
+                    if let Some(parent) = output_path.parent() {
+                        fs::create_dir_all(parent)
+                            .with_context(|| format!("creating directory {}", parent.display()))?;
+                    }
+                    fs::write(&output_path, serde_json::to_string_pretty(&harvest.value)?)
+                        .with_context(|| format!("writing {}", output_path.display()))?;
+                    println!(
+                        "Harvested metadata for {} -> {}",
+                        harvest.package_id,
+                        output_path.display()
+                    );
+                    Ok(())
                 });
-                if let Some(parent) = output_path.parent() {
-                    fs::create_dir_all(parent)
-                        .with_context(|| format!("creating directory {}", parent.display()))?;
+
+                match result {
+                    Ok(()) => harvested += 1,
+                    Err(err) if !batch => return Err(err),
+                    Err(err) => {
+                        eprintln!("warning: failed to harvest {page}: {err:#}");
+                        failed.push(page.clone());
+                    }
                 }
-                fs::write(&output_path, serde_json::to_string_pretty(&harvest.value)?)
-                    .with_context(|| format!("writing {}", output_path.display()))?;
-                println!(
-                    "Harvested metadata for {} -> {}",
-                    harvest.package_id,
-                    output_path.display()
-                );
+            }
+
+            if !dry_run && harvested > 0 {
                 println!(
                     "Run `metadata_indexer --base-dir {} index` to refresh the index.",
                     base_dir.display()
                 );
             }
+            if !failed.is_empty() {
+                eprintln!("Failed pages ({} of {}):", failed.len(), pages.len());
+                for page in &failed {
+                    eprintln!("  - {page}");
+                }
+                anyhow::bail!("{} page(s) failed to harvest", failed.len());
+            }
         }
-        Command::Refresh { books, force } => {
-            let unique: HashSet<_> = books.into_iter().map(|b| b.to_lowercase()).collect();
-            let mut refreshed = 0usize;
+        Command::Refresh {
+            books,
+            force,
+            summary_output,
+        } => {
+            let unique: BTreeSet<_> = books.into_iter().map(|b| b.to_lowercase()).collect();
+            let mut refreshed = Vec::new();
             for book in unique {
                 for kind in [ManifestKind::WgetList, ManifestKind::Md5Sums] {
-                    match refresh_manifest(&metadata_dir, &book, kind, force) {
+                    match refresh_manifest(&metadata_dir, &overrides, &book, kind, force) {
                         Ok(path) => {
-                            refreshed += 1;
+                            let size_bytes = fs::metadata(&path)
+                                .with_context(|| format!("inspecting {}", path.display()))?
+                                .len();
                             println!(
                                 "Refreshed {} manifest for {} -> {}",
                                 kind.description(),
                                 book,
                                 path.display()
                             );
+                            refreshed.push(RefreshedManifest {
+                                book: book.clone(),
+                                kind: kind.description(),
+                                path,
+                                size_bytes,
+                            });
                         }
                         Err(err) => {
                             eprintln!(
@@ -231,9 +333,18 @@ fn main() -> Result<()> {
                 }
             }
 
-            if refreshed == 0 {
+            if refreshed.is_empty() {
                 println!("No manifests refreshed (check warnings above).");
             }
+            if let Some(path) = summary_output {
+                let summary = RefreshSummary {
+                    refreshed_at: Utc::now().to_rfc3339(),
+                    manifests: refreshed,
+                };
+                fs::write(&path, serde_json::to_string_pretty(&summary)?)
+                    .with_context(|| format!("writing refresh summary {}", path.display()))?;
+                println!("Wrote refresh summary to {}", path.display());
+            }
         }
         Command::Generate {
             metadata,
@@ -242,7 +353,12 @@ fn main() -> Result<()> {
         } => {
             if overwrite {
                 match generator::module_directory(&metadata, &output) {
-                    Ok(dir) if dir.exists() => {
+                    // Modules generated from identical metadata are kept and reported as skipped.
+                    Ok(dir)
+                        if dir.exists()
+                            && !generator::module_is_up_to_date(&metadata, &dir.join("mod.rs"))
+                                .unwrap_or(false) =>
+                    {
                         fs::remove_dir_all(&dir).with_context(|| {
                             format!("removing existing module {}", dir.display())
                         })?;
@@ -257,14 +373,697 @@ fn main() -> Result<()> {
                 }
             }
 
-            let module_path = generator::generate_module(&metadata, &output)?;
-            println!("Generated module at {}", module_path.display());
+            match generator::generate_module(&metadata, &output)? {
+                GenerateOutcome::Generated(path) => {
+                    println!("Generated module at {}", path.display());
+                }
+                GenerateOutcome::Unchanged(path) => {
+                    println!("Module at {} is up to date; skipped", path.display());
+                }
+            }
+        }
+        Command::Preview { metadata } => {
+            print!("{}", generator::preview_module(&metadata)?);
+        }
+        Command::Diff {
+            old,
+            new,
+            output,
+            json,
+        } => {
+            let diff = diff_indexes(&load_index_entries(&old)?, &load_index_entries(&new)?);
+            let document = serde_json::to_string_pretty(&diff)?;
+
+            if let Some(path) = &output {
+                fs::write(path, &document)
+                    .with_context(|| format!("writing {}", path.display()))?;
+            }
+            if json {
+                println!("{document}");
+            } else {
+                print_index_diff(&diff);
+            }
+        }
+        Command::Stats { csv } => {
+            let stats = collect_stats(&packages);
+            if csv {
+                print!("{}", stats.to_csv());
+            } else {
+                stats.print_table();
+            }
+        }
+        Command::EstimateBuild {
+            book,
+            stage,
+            minutes_per_sbu,
+        } => {
+            let estimate = estimate_build(&packages, &book.to_lowercase(), stage.as_deref());
+            estimate.print(minutes_per_sbu);
+        }
+        Command::DepTable { book, output } => {
+            let table = dependency_table(&packages, &book.to_lowercase());
+            match output {
+                Some(path) => {
+                    fs::write(&path, &table)
+                        .with_context(|| format!("writing {}", path.display()))?;
+                    println!("Wrote dependency table to {}", path.display());
+                }
+                None => print!("{table}"),
+            }
+        }
+        Command::ValidateAnchors {
+            timeout_ms,
+            parallel,
+        } => {
+            let anchors = load_indexed_anchors(&metadata_dir)?;
+            let failures = check_anchors(&anchors, Duration::from_millis(timeout_ms), parallel)?;
+
+            if failures.is_empty() {
+                println!("All {} anchor URLs returned HTTP 200", anchors.len());
+            } else {
+                println!("package_id | url | status_code | error");
+                for failure in &failures {
+                    println!(
+                        "{} | {} | {} | {}",
+                        failure.package_id,
+                        failure.url,
+                        failure
+                            .status
+                            .map(|code| code.to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                        failure.error.as_deref().unwrap_or("-")
+                    );
+                }
+                anyhow::bail!(
+                    "{} of {} anchor URLs are unreachable",
+                    failures.len(),
+                    anchors.len()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshSummary {
+    refreshed_at: String,
+    manifests: Vec<RefreshedManifest>,
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshedManifest {
+    book: String,
+    kind: &'static str,
+    path: PathBuf,
+    size_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct IndexEntry {
+    id: String,
+    version: String,
+    status: String,
+}
+
+#[derive(Debug, Serialize)]
+struct IndexChange {
+    id: String,
+    before: IndexEntry,
+    after: IndexEntry,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct IndexDiff {
+    added: Vec<IndexEntry>,
+    removed: Vec<IndexEntry>,
+    changed: Vec<IndexChange>,
+}
+
+fn load_index_entries(path: &Path) -> Result<BTreeMap<String, IndexEntry>> {
+    let data =
+        fs::read_to_string(path).with_context(|| format!("reading index {}", path.display()))?;
+    let value: Value =
+        serde_json::from_str(&data).with_context(|| format!("parsing index {}", path.display()))?;
+    let packages = value
+        .get("packages")
+        .and_then(Value::as_array)
+        .with_context(|| format!("{} has no packages array", path.display()))?;
+
+    let field = |pkg: &Value, key: &str| {
+        pkg.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let mut entries = BTreeMap::new();
+    for pkg in packages {
+        let entry = IndexEntry {
+            id: field(pkg, "id"),
+            version: field(pkg, "version"),
+            status: field(pkg, "status"),
+        };
+        if entry.id.is_empty() {
+            return Err(anyhow!("{} contains a package without id", path.display()));
+        }
+        entries.insert(entry.id.clone(), entry);
+    }
+    Ok(entries)
+}
+
+fn diff_indexes(
+    old: &BTreeMap<String, IndexEntry>,
+    new: &BTreeMap<String, IndexEntry>,
+) -> IndexDiff {
+    let mut diff = IndexDiff::default();
+    for (id, before) in old {
+        match new.get(id) {
+            None => diff.removed.push(before.clone()),
+            Some(after) if after != before => diff.changed.push(IndexChange {
+                id: id.clone(),
+                before: before.clone(),
+                after: after.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.added = new
+        .iter()
+        .filter(|(id, _)| !old.contains_key(*id))
+        .map(|(_, entry)| entry.clone())
+        .collect();
+    diff
+}
+
+fn print_index_diff(diff: &IndexDiff) {
+    let green = Style::new().green();
+    let red = Style::new().red();
+    let yellow = Style::new().yellow();
+
+    for entry in &diff.added {
+        println!(
+            "{}",
+            green.apply_to(format!(
+                "+ {} {} [{}]",
+                entry.id, entry.version, entry.status
+            ))
+        );
+    }
+    for entry in &diff.removed {
+        println!(
+            "{}",
+            red.apply_to(format!(
+                "- {} {} [{}]",
+                entry.id, entry.version, entry.status
+            ))
+        );
+    }
+    for change in &diff.changed {
+        println!(
+            "{}",
+            yellow.apply_to(format!(
+                "~ {}: {} [{}] -> {} [{}]",
+                change.id,
+                change.before.version,
+                change.before.status,
+                change.after.version,
+                change.after.status
+            ))
+        );
+    }
+    println!(
+        "{} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+}
+
+#[derive(Debug, Default)]
+struct MetadataStats {
+    sbu_by_stage: BTreeMap<String, f64>,
+    packages_by_stage: BTreeMap<String, usize>,
+    missing_source_urls: usize,
+    missing_checksums: usize,
+    non_draft: usize,
+}
+
+fn collect_stats(packages: &[PackageRecord]) -> MetadataStats {
+    let mut stats = MetadataStats::default();
+    for record in packages {
+        let value = &record.value;
+        let stage = value
+            .pointer("/package/stage")
+            .and_then(Value::as_str)
+            .unwrap_or("unspecified")
+            .to_string();
+        let sbu = value
+            .pointer("/artifacts/sbu")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        *stats.sbu_by_stage.entry(stage.clone()).or_default() += sbu;
+        *stats.packages_by_stage.entry(stage).or_default() += 1;
+
+        let is_empty = |pointer: &str| {
+            value
+                .pointer(pointer)
+                .and_then(Value::as_array)
+                .is_none_or(|items| items.is_empty())
+        };
+        if is_empty("/source/urls") {
+            stats.missing_source_urls += 1;
+        }
+        if is_empty("/source/checksums") {
+            stats.missing_checksums += 1;
+        }
+        if value.pointer("/status/state").and_then(Value::as_str) != Some("draft") {
+            stats.non_draft += 1;
+        }
+    }
+    stats
+}
+
+impl MetadataStats {
+    fn total_sbu(&self) -> f64 {
+        self.sbu_by_stage.values().sum()
+    }
+
+    fn print_table(&self) {
+        let width = self
+            .sbu_by_stage
+            .keys()
+            .map(String::len)
+            .max()
+            .unwrap_or(0)
+            .max("stage".len());
+        println!("{:<width$}  {:>8}  {:>9}", "stage", "packages", "total SBU");
+        for (stage, sbu) in &self.sbu_by_stage {
+            println!(
+                "{:<width$}  {:>8}  {:>9.1}",
+                stage, self.packages_by_stage[stage], sbu
+            );
+        }
+        println!(
+            "{:<width$}  {:>8}  {:>9.1}",
+            "total",
+            self.packages_by_stage.values().sum::<usize>(),
+            self.total_sbu()
+        );
+        println!();
+        println!("missing source URLs: {}", self.missing_source_urls);
+        println!("missing checksums:   {}", self.missing_checksums);
+        println!("non-draft status:    {}", self.non_draft);
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("stage,packages,total_sbu\n");
+        for (stage, sbu) in &self.sbu_by_stage {
+            out.push_str(&format!(
+                "{},{},{}\n",
+                stage, self.packages_by_stage[stage], sbu
+            ));
+        }
+        out.push_str(&format!(
+            "missing_source_urls,{},\n",
+            self.missing_source_urls
+        ));
+        out.push_str(&format!("missing_checksums,{},\n", self.missing_checksums));
+        out.push_str(&format!("non_draft,{},\n", self.non_draft));
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+struct BuildEstimate {
+    packages: usize,
+    total_sbu: f64,
+    unknown_sbu: usize,
+}
+
+/// Total `artifacts.sbu` of the packages in `book`, optionally restricted to `stage`.
+fn estimate_build(packages: &[PackageRecord], book: &str, stage: Option<&str>) -> BuildEstimate {
+    let mut estimate = BuildEstimate::default();
+    for record in packages {
+        let value = &record.value;
+        if value.pointer("/package/book").and_then(Value::as_str) != Some(book) {
+            continue;
+        }
+        if stage.is_some() && value.pointer("/package/stage").and_then(Value::as_str) != stage {
+            continue;
+        }
+        estimate.packages += 1;
+        match value.pointer("/artifacts/sbu").and_then(Value::as_f64) {
+            Some(sbu) => estimate.total_sbu += sbu,
+            None => estimate.unknown_sbu += 1,
+        }
+    }
+    estimate
+}
+
+impl BuildEstimate {
+    fn print(&self, minutes_per_sbu: f64) {
+        let minutes = (self.total_sbu * minutes_per_sbu).round() as u64;
+        println!(
+            "Estimated build time: {:.1} SBU (~{}h {:02}m at {} min/SBU) across {} packages",
+            self.total_sbu,
+            minutes / 60,
+            minutes % 60,
+            minutes_per_sbu,
+            self.packages
+        );
+        if self.unknown_sbu > 0 {
+            println!("{} packages with unknown SBU", self.unknown_sbu);
+        }
+    }
+}
+
+struct AnchorFailure {
+    package_id: String,
+    url: String,
+    status: Option<u16>,
+    error: Option<String>,
+}
+
+/// Stages in book order, used to sort the dependency table.
+const STAGE_ORDER: &[&str] = &[
+    "cross-toolchain",
+    "temporary-tools",
+    "system",
+    "system-configuration",
+    "system-finalization",
+    "desktop",
+    "server",
+    "multilib",
+    "kernel",
+    "boot",
+];
+
+/// Render a Markdown table of `dependencies.build` / `dependencies.runtime` for every package in
+/// `book`, ordered by stage and then by section number.
+fn dependency_table(packages: &[PackageRecord], book: &str) -> String {
+    let str_at = |value: &Value, pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let mut rows = Vec::new();
+    for record in packages {
+        let value = &record.value;
+        if str_at(value, "/package/book").as_deref() != Some(book) {
+            continue;
+        }
+
+        let stage_rank = str_at(value, "/package/stage")
+            .and_then(|stage| STAGE_ORDER.iter().position(|known| *known == stage))
+            .unwrap_or(STAGE_ORDER.len());
+        let section: Vec<u32> = str_at(value, "/package/section")
+            .map(|section| section.split('.').filter_map(|n| n.parse().ok()).collect())
+            .unwrap_or_default();
+
+        let name = str_at(value, "/package/name").unwrap_or_default();
+        let package = match str_at(value, "/package/variant") {
+            Some(variant) => format!("{name} ({variant})"),
+            None => name,
+        };
+        let build: Vec<String> = value
+            .pointer("/dependencies/build")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|dep| {
+                let name = dep.get("name")?.as_str()?;
+                let optional = dep.get("optional").and_then(Value::as_bool) == Some(true);
+                Some(if optional {
+                    format!("{name} (optional)")
+                } else {
+                    name.to_string()
+                })
+            })
+            .collect();
+        let runtime: Vec<String> = value
+            .pointer("/dependencies/runtime")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|dep| dep.as_str().map(str::to_string))
+            .collect();
+
+        rows.push(((stage_rank, section), package, build, runtime));
+    }
+    rows.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    let cell = |items: &[String]| {
+        if items.is_empty() {
+            "\u{2014}".to_string()
+        } else {
+            items.join(", ").replace('|', "\\|")
+        }
+    };
+    let mut out = String::from("| Package | Build Deps | Runtime Deps |\n|---|---|---|\n");
+    for (_, package, build, runtime) in &rows {
+        out.push_str(&format!(
+            "| {} | {} | {} |\n",
+            package.replace('|', "\\|"),
+            cell(build),
+            cell(runtime)
+        ));
+    }
+    out
+}
+
+/// Collect `(package_id, anchors.section)` for every package listed in index.json.
+fn load_indexed_anchors(metadata_dir: &Path) -> Result<Vec<(String, String)>> {
+    let index_path = metadata_dir.join("index.json");
+    let data = fs::read_to_string(&index_path)
+        .with_context(|| format!("reading index {}", index_path.display()))?;
+    let index: Value = serde_json::from_str(&data)
+        .with_context(|| format!("parsing index {}", index_path.display()))?;
+
+    let mut anchors = Vec::new();
+    for entry in index
+        .get("packages")
+        .and_then(Value::as_array)
+        .context("index.json has no packages array")?
+    {
+        let (Some(id), Some(path)) = (
+            entry.get("id").and_then(Value::as_str),
+            entry.get("path").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let package_path = metadata_dir.join(path);
+        let package: Value = serde_json::from_str(
+            &fs::read_to_string(&package_path)
+                .with_context(|| format!("reading {}", package_path.display()))?,
+        )
+        .with_context(|| format!("parsing {}", package_path.display()))?;
+        if let Some(url) = package
+            .pointer("/package/anchors/section")
+            .and_then(Value::as_str)
+        {
+            anchors.push((id.to_string(), url.to_string()));
+        }
+    }
+    Ok(anchors)
+}
+
+/// HEAD every anchor URL on a pool of `parallel` workers and return the non-200 responses.
+fn check_anchors(
+    anchors: &[(String, String)],
+    timeout: Duration,
+    parallel: usize,
+) -> Result<Vec<AnchorFailure>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(parallel.max(1))
+        .build()
+        .context("building anchor validation thread pool")?;
+    let agent = ureq::AgentBuilder::new()
+        .redirects(5)
+        .timeout(timeout)
+        .build();
+
+    Ok(pool.install(|| {
+        anchors
+            .par_iter()
+            .filter_map(|(package_id, url)| {
+                let (status, error) = match agent.head(url).call() {
+                    Ok(response) if response.status() == 200 => return None,
+                    Ok(response) => (Some(response.status()), None),
+                    Err(ureq::Error::Status(code, _)) => (Some(code), None),
+                    Err(err) => (None, Some(err.to_string())),
+                };
+                Some(AnchorFailure {
+                    package_id: package_id.clone(),
+                    url: url.clone(),
+                    status,
+                    error,
+                })
+            })
+            .collect()
+    }))
+}
+
+/// Print schema and summary errors for every package; returns whether any were found.
+fn report_validation_errors(schema: &JSONSchema, packages: &[PackageRecord]) -> bool {
+    let mut had_errors = false;
+    for package in packages {
+        let validation = schema.validate(&package.value);
+        if let Err(errors) = validation {
+            had_errors = true;
+            eprintln!(
+                "Schema validation failed for {}:",
+                package.relative_path.display()
+            );
+            for err in errors {
+                eprintln!("  - {}", err);
+            }
+        }
+
+        if let Some(err) = &package.summary_error {
+            had_errors = true;
+            eprintln!(
+                "Summary extraction failed for {}: {}",
+                package.relative_path.display(),
+                err
+            );
+        }
+    }
+
+    had_errors
+}
+
+/// Write `index.json` for the given packages and return its path.
+fn write_index(metadata_dir: &Path, packages: &[PackageRecord], compact: bool) -> Result<PathBuf> {
+    let summaries: Vec<_> = packages
+        .iter()
+        .filter_map(|pkg| pkg.summary.clone())
+        .collect();
+
+    let schema_version = summaries
+        .first()
+        .map(|s| s.schema_version.as_str())
+        .unwrap_or("v0.0.0");
+
+    let generated_at = Utc::now().to_rfc3339();
+    let packages_json: Vec<Value> = summaries
+        .iter()
+        .map(|s| {
+            json!({
+                "id": s.id.clone(),
+                "name": s.name.clone(),
+                "version": s.version.clone(),
+                "stage": s.stage.clone(),
+                "book": s.book.clone(),
+                "variant": s.variant.clone(),
+                "status": s.status.clone(),
+                "path": s.relative_path.clone(),
+                "tags": s.tags.clone(),
+            })
+        })
+        .collect();
+
+    let index = json!({
+        "generated_at": generated_at,
+        "schema_version": schema_version,
+        "packages": packages_json,
+    });
+
+    let index_path = metadata_dir.join("index.json");
+    let serialized = if compact {
+        serde_json::to_string(&index)?
+    } else {
+        serde_json::to_string_pretty(&index)?
+    };
+    fs::write(&index_path, serialized)
+        .with_context(|| format!("writing {}", index_path.display()))?;
+    Ok(index_path)
+}
+
+/// Validate, rebuild the index and print what changed relative to the previous index.
+fn reindex(metadata_dir: &Path, packages_dir: &Path, schema: &JSONSchema) -> Result<()> {
+    let packages = scan_packages(packages_dir)?;
+    if report_validation_errors(schema, &packages) {
+        anyhow::bail!("metadata validation failed; index not updated");
+    }
+
+    let index_path = metadata_dir.join("index.json");
+    let previous = if index_path.exists() {
+        load_index_entries(&index_path)?
+    } else {
+        BTreeMap::new()
+    };
+    write_index(metadata_dir, &packages, false)?;
+    let current = load_index_entries(&index_path)?;
+    print_index_diff(&diff_indexes(&previous, &current));
+    Ok(())
+}
+
+/// Re-run [`reindex`] whenever a JSON file under `packages_dir` changes, until Ctrl-C.
+fn watch_packages(
+    metadata_dir: &Path,
+    packages_dir: &Path,
+    schema: &JSONSchema,
+    debounce: Duration,
+) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+        .context("installing Ctrl-C handler")?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("creating file watcher")?;
+    watcher
+        .watch(packages_dir, RecursiveMode::Recursive)
+        .with_context(|| format!("watching {}", packages_dir.display()))?;
+    println!(
+        "Watching {} for changes (Ctrl-C to stop)",
+        packages_dir.display()
+    );
+
+    let mut runs = 0usize;
+    let mut last_run: Option<DateTime<Utc>> = None;
+    // Time of the most recent relevant event not yet handled by a run.
+    let mut pending: Option<Instant> = None;
+    while running.load(Ordering::SeqCst) {
+        match rx.recv_timeout(WATCH_POLL_INTERVAL) {
+            Ok(Ok(event)) if is_metadata_change(&event) => pending = Some(Instant::now()),
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(err)) => eprintln!("warning: watch error: {err}"),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if pending.is_some_and(|at| at.elapsed() >= debounce) {
+            pending = None;
+            runs += 1;
+            let now = Utc::now();
+            println!("[{}] run #{runs}", now.to_rfc3339());
+            if let Err(err) = reindex(metadata_dir, packages_dir, schema) {
+                eprintln!("run #{runs} failed: {err:#}");
+            }
+            last_run = Some(now);
         }
     }
 
+    println!(
+        "Watch stopped after {runs} run(s); last run: {}",
+        last_run
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_else(|| "never".to_string())
+    );
     Ok(())
 }
 
+fn is_metadata_change(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension().is_some_and(|ext| ext == "json"))
+}
+
 fn load_schema(path: &Path) -> Result<(&'static Value, JSONSchema)> {
     let data = fs::read_to_string(path)
         .with_context(|| format!("reading schema file {}", path.display()))?;
@@ -406,6 +1205,17 @@ fn extract_summary(value: &Value, relative_path: &Path) -> Result<PackageSummary
     })
 }
 
+fn read_pages_file(path: &Path) -> Result<Vec<String>> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("reading pages file {}", path.display()))?;
+    Ok(data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
 struct HarvestResult {
     value: Value,
     slug: String,
@@ -414,26 +1224,32 @@ struct HarvestResult {
 
 fn harvest_metadata(
     metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
     book: &str,
     page: &str,
     override_base: Option<&str>,
 ) -> Result<HarvestResult> {
-    let page_url = resolve_page_url(book, page, override_base)?;
+    let page_url = resolve_page_url(overrides, book, page, override_base)?;
     let html = fetch_text(&page_url).with_context(|| format!("fetching {page_url}"))?;
 
     let document = Html::parse_document(&html);
-    let harvest = build_metadata_value(metadata_dir, book, &page_url, &document, &html)?;
+    let harvest = build_metadata_value(metadata_dir, overrides, book, &page_url, &document, &html)?;
     Ok(harvest)
 }
 
-fn resolve_page_url(book: &str, page: &str, override_base: Option<&str>) -> Result<String> {
+fn resolve_page_url(
+    overrides: &BaseUrlOverrides,
+    book: &str,
+    page: &str,
+    override_base: Option<&str>,
+) -> Result<String> {
     if page.starts_with("http://") || page.starts_with("https://") {
         return Ok(page.to_string());
     }
 
     let base = override_base
         .map(|s| s.to_string())
-        .or_else(|| default_base_url(book).map(|s| s.to_string()))
+        .or_else(|| default_base_url(overrides, book))
         .context("no base URL available for book")?;
 
     let base = base.trim_end_matches('/');
@@ -448,18 +1264,43 @@ fn resolve_page_url(book: &str, page: &str, override_base: Option<&str>) -> Resu
     Ok(format!("{}/{}", base, page_path))
 }
 
-fn default_base_url(book: &str) -> Option<&'static str> {
-    match book {
-        "lfs" => Some("https://www.linuxfromscratch.org/lfs/view/12.1"),
-        "mlfs" => Some("https://linuxfromscratch.org/~thomas/multilib-m32"),
-        "blfs" => Some("https://www.linuxfromscratch.org/blfs/view/systemd"),
-        "glfs" => Some("https://www.linuxfromscratch.org/glfs/view/glfs"),
-        _ => None,
+/// Book base URL, honouring `--base-url-override`.
+fn default_base_url(overrides: &BaseUrlOverrides, book: &str) -> Option<String> {
+    if let Some(base) = overrides.get(book) {
+        return Some(base.to_string());
+    }
+    let base = match book {
+        "lfs" => "https://www.linuxfromscratch.org/lfs/view/12.1",
+        "mlfs" => "https://linuxfromscratch.org/~thomas/multilib-m32",
+        "blfs" => "https://www.linuxfromscratch.org/blfs/view/systemd",
+        "glfs" => "https://www.linuxfromscratch.org/glfs/view/glfs",
+        _ => return None,
+    };
+    Some(base.to_string())
+}
+
+/// Manifest URL for `book`. With `--base-url-override` the upstream file name is kept and
+/// resolved against the mirror.
+fn book_manifest_url(
+    overrides: &BaseUrlOverrides,
+    book: &str,
+    kind: ManifestKind,
+) -> Option<String> {
+    let upstream = manifest_url(book, kind);
+    match overrides.get(book) {
+        Some(base) => {
+            let file = upstream
+                .and_then(|url| url.rsplit('/').next())
+                .unwrap_or(kind.description());
+            Some(format!("{base}/{file}"))
+        }
+        None => upstream.map(str::to_string),
     }
 }
 
 fn build_metadata_value(
     metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
     book: &str,
     page_url: &str,
     document: &Html,
@@ -534,7 +1375,7 @@ fn build_metadata_value(
     });
 
     if source_urls.is_empty() {
-        match fallback_urls_from_wget(metadata_dir, book, &slug_base, &version) {
+        match fallback_urls_from_wget(metadata_dir, overrides, book, &slug_base, &version) {
             Ok(fallback) => {
                 if !fallback.is_empty() {
                     eprintln!(
@@ -572,7 +1413,8 @@ fn build_metadata_value(
     }
 
     let (sbu, disk) = extract_artifacts(document);
-    let build_steps = extract_build_steps(document);
+    let (build_steps, env_variables) = extract_build_steps(document);
+    let install_prefix = infer_install_prefix(&build_steps);
 
     let mut issues = Vec::new();
     if anchor_url.is_none() {
@@ -595,16 +1437,17 @@ fn build_metadata_value(
         })
         .collect();
 
-    let checksum_entries = match resolve_checksums(metadata_dir, book, archive_name.as_deref()) {
-        Ok(values) => values,
-        Err(err) => {
-            eprintln!(
-                "warning: failed to resolve checksums for {} {}: {}",
-                slug_base, version, err
-            );
-            Vec::new()
-        }
-    };
+    let checksum_entries =
+        match resolve_checksums(metadata_dir, overrides, book, archive_name.as_deref()) {
+            Ok(values) => values,
+            Err(err) => {
+                eprintln!(
+                    "warning: failed to resolve checksums for {} {}: {}",
+                    slug_base, version, err
+                );
+                Vec::new()
+            }
+        };
 
     let build_json: Vec<Value> = build_steps
         .iter()
@@ -660,14 +1503,14 @@ fn build_metadata_value(
         "artifacts": {
             "sbu": sbu,
             "disk": disk,
-            "install_prefix": Option::<String>::None,
+            "install_prefix": install_prefix,
         },
         "dependencies": {
             "build": Vec::<Value>::new(),
             "runtime": Vec::<Value>::new(),
         },
         "environment": {
-            "variables": Vec::<Value>::new(),
+            "variables": env_variables,
             "users": Vec::<Value>::new(),
         },
         "build": build_json,
@@ -747,15 +1590,11 @@ fn split_name_variant(title: &str) -> (String, String, Option<String>) {
 
     let bytes = base.as_bytes();
     for idx in (0..bytes.len()).rev() {
-        if bytes[idx] == b'-' {
-            if let Some(next) = bytes.get(idx + 1) {
-                if next.is_ascii_digit() {
-                    let name = base[..idx].trim();
-                    let version = base[idx + 1..].trim();
-                    if !name.is_empty() && !version.is_empty() {
-                        return (name.to_string(), version.to_string(), variant);
-                    }
-                }
+        if bytes[idx] == b'-' && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit) {
+            let name = base[..idx].trim();
+            let version = base[idx + 1..].trim();
+            if !name.is_empty() && !version.is_empty() {
+                return (name.to_string(), version.to_string(), variant);
             }
         }
     }
@@ -779,28 +1618,6 @@ struct SourceUrlEntry {
     kind: &'static str,
 }
 
-#[derive(Clone, Copy)]
-enum ManifestKind {
-    WgetList,
-    Md5Sums,
-}
-
-impl ManifestKind {
-    fn filename(&self) -> &'static str {
-        match self {
-            ManifestKind::WgetList => "wget-list.txt",
-            ManifestKind::Md5Sums => "md5sums.txt",
-        }
-    }
-
-    fn description(&self) -> &'static str {
-        match self {
-            ManifestKind::WgetList => "wget-list",
-            ManifestKind::Md5Sums => "md5sums",
-        }
-    }
-}
-
 fn collect_tarball_urls(page_url: &str, document: &Html) -> Vec<SourceUrlEntry> {
     let base = url::Url::parse(page_url).ok();
     let link_selector = Selector::parse("a").unwrap();
@@ -808,22 +1625,24 @@ fn collect_tarball_urls(page_url: &str, document: &Html) -> Vec<SourceUrlEntry>
     let mut results = Vec::new();
 
     for link in document.select(&link_selector) {
-        if let Some(href) = link.value().attr("href") {
-            if let Some(kind) = classify_artifact_url(href) {
-                let resolved = match (&base, url::Url::parse(href)) {
-                    (_, Ok(url)) => url,
-                    (Some(base_url), Err(_)) => match base_url.join(href) {
-                        Ok(url) => url,
-                        Err(_) => continue,
-                    },
-                    _ => continue,
-                };
-                if seen.insert(resolved.clone()) {
-                    results.push(SourceUrlEntry {
-                        url: resolved,
-                        kind,
-                    });
-                }
+        if let Some((href, kind)) = link
+            .value()
+            .attr("href")
+            .and_then(|href| classify_artifact_url(href).map(|kind| (href, kind)))
+        {
+            let resolved = match (&base, url::Url::parse(href)) {
+                (_, Ok(url)) => url,
+                (Some(base_url), Err(_)) => match base_url.join(href) {
+                    Ok(url) => url,
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            if seen.insert(resolved.clone()) {
+                results.push(SourceUrlEntry {
+                    url: resolved,
+                    kind,
+                });
             }
         }
     }
@@ -852,11 +1671,12 @@ fn classify_artifact_url(href: &str) -> Option<&'static str> {
 
 fn fallback_urls_from_wget(
     metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
     book: &str,
     slug: &str,
     version: &str,
 ) -> Result<Vec<SourceUrlEntry>> {
-    let manifest = load_jhalfs_manifest(metadata_dir, book, ManifestKind::WgetList)?;
+    let manifest = load_jhalfs_manifest(metadata_dir, overrides, book, ManifestKind::WgetList)?;
     let needle = format!("{}-{}", slug.replace('_', "-"), version);
     eprintln!("debug: searching wget-list for '{}'", needle);
     let mut entries = Vec::new();
@@ -884,6 +1704,7 @@ fn fallback_urls_from_wget(
 
 fn resolve_checksums(
     metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
     book: &str,
     archive_name: Option<&str>,
 ) -> Result<Vec<Value>> {
@@ -892,7 +1713,7 @@ fn resolve_checksums(
         return Ok(checksums);
     };
 
-    let manifest = load_jhalfs_manifest(metadata_dir, book, ManifestKind::Md5Sums)?;
+    let manifest = load_jhalfs_manifest(metadata_dir, overrides, book, ManifestKind::Md5Sums)?;
     for line in manifest.lines() {
         let trimmed = line.trim();
         if trimmed.is_empty() {
@@ -913,14 +1734,20 @@ fn resolve_checksums(
     Ok(checksums)
 }
 
-fn load_jhalfs_manifest(metadata_dir: &Path, book: &str, kind: ManifestKind) -> Result<String> {
-    let cache_path = refresh_manifest(metadata_dir, book, kind, false)?;
+fn load_jhalfs_manifest(
+    metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
+    book: &str,
+    kind: ManifestKind,
+) -> Result<String> {
+    let cache_path = refresh_manifest(metadata_dir, overrides, book, kind, false)?;
     fs::read_to_string(&cache_path)
         .with_context(|| format!("reading cached manifest {}", cache_path.display()))
 }
 
 fn refresh_manifest(
     metadata_dir: &Path,
+    overrides: &BaseUrlOverrides,
     book: &str,
     kind: ManifestKind,
     force: bool,
@@ -934,10 +1761,10 @@ fn refresh_manifest(
         return Ok(cache_path);
     }
 
-    let url = manifest_url(book, &kind)
+    let url = book_manifest_url(overrides, book, kind)
         .with_context(|| format!("no manifest URL configured for book '{}'", book))?;
 
-    let body = fetch_text(url).with_context(|| format!("fetching {url}"))?;
+    let body = fetch_text(&url).with_context(|| format!("fetching {url}"))?;
 
     fs::write(&cache_path, &body)
         .with_context(|| format!("caching manifest {}", cache_path.display()))?;
@@ -956,47 +1783,16 @@ fn fetch_text(url: &str) -> Result<String> {
         .with_context(|| format!("reading response body from {url}"))
 }
 
-fn manifest_url(book: &str, kind: &ManifestKind) -> Option<&'static str> {
-    match (book, kind) {
-        ("mlfs", ManifestKind::WgetList) => {
-            Some("https://www.linuxfromscratch.org/~thomas/multilib-m32/wget-list-sysv")
-        }
-        ("mlfs", ManifestKind::Md5Sums) => {
-            Some("https://www.linuxfromscratch.org/~thomas/multilib-m32/md5sums")
-        }
-        ("lfs", ManifestKind::WgetList) => {
-            Some("https://www.linuxfromscratch.org/lfs/view/12.1/wget-list")
-        }
-        ("lfs", ManifestKind::Md5Sums) => {
-            Some("https://www.linuxfromscratch.org/lfs/view/12.1/md5sums")
-        }
-        ("blfs", ManifestKind::WgetList) => {
-            Some("https://anduin.linuxfromscratch.org/BLFS/view/systemd/wget-list")
-        }
-        ("blfs", ManifestKind::Md5Sums) => {
-            Some("https://anduin.linuxfromscratch.org/BLFS/view/systemd/md5sums")
-        }
-        ("glfs", ManifestKind::WgetList) => {
-            Some("https://www.linuxfromscratch.org/glfs/view/glfs/wget-list")
-        }
-        ("glfs", ManifestKind::Md5Sums) => {
-            Some("https://www.linuxfromscratch.org/glfs/view/glfs/md5sums")
-        }
-        _ => None,
-    }
-}
-
 fn locate_child_anchor(heading: &ElementRef) -> Option<String> {
     let mut current = heading.first_child();
     while let Some(node) = current {
-        if let Some(element) = ElementRef::wrap(node) {
-            if let Some(id) = element
+        if let Some(id) = ElementRef::wrap(node).and_then(|element| {
+            element
                 .value()
                 .attr("id")
                 .or_else(|| element.value().attr("name"))
-            {
-                return Some(id.to_string());
-            }
+        }) {
+            return Some(id.to_string());
         }
         current = node.next_sibling();
     }
@@ -1011,7 +1807,7 @@ fn infer_archive_from_commands(document: &Html) -> Option<String> {
             if let Some(start) = line.find("tar -xf") {
                 let args = line[start + 7..].trim();
                 let parts: Vec<&str> = args.split_whitespace().collect();
-                if let Some(archive) = parts.get(0) {
+                if let Some(archive) = parts.first() {
                     let cleaned = archive.trim_matches(['"', '\'', ','].as_ref());
                     if cleaned.ends_with(".tar")
                         || cleaned.contains(".tar.")
@@ -1046,13 +1842,9 @@ fn extract_artifacts(document: &Html) -> (Option<f64>, Option<i64>) {
 
         if let (Some(title), Some(body)) = (title, body) {
             if title.contains("Approximate build time") {
-                if let Some(value) = parse_numeric(&body) {
-                    sbu = Some(value);
-                }
+                sbu = parse_numeric(&body).or(sbu);
             } else if title.contains("Required disk space") {
-                if let Some(value) = parse_numeric(&body) {
-                    disk = Some(value as i64);
-                }
+                disk = parse_numeric(&body).map(|value| value as i64).or(disk);
             }
         }
     }
@@ -1060,13 +1852,6 @@ fn extract_artifacts(document: &Html) -> (Option<f64>, Option<i64>) {
     (sbu, disk)
 }
 
-fn parse_numeric(input: &str) -> Option<f64> {
-    let re = Regex::new(r"([0-9]+(?:\\.[0-9]+)?)").ok()?;
-    re.captures(input)
-        .and_then(|caps| caps.get(1))
-        .and_then(|m| m.as_str().parse().ok())
-}
-
 struct BuildStep {
     phase: &'static str,
     commands: Vec<String>,
@@ -1075,17 +1860,29 @@ struct BuildStep {
     notes: Option<String>,
 }
 
-fn extract_build_steps(document: &Html) -> Vec<BuildStep> {
-    let pre_selector = Selector::parse("pre.userinput").unwrap();
+/// Build steps from the `userinput` blocks, plus `export NAME=value` lines pulled out as
+/// `{ "name", "value" }` environment variables.
+fn extract_build_steps(document: &Html) -> (Vec<BuildStep>, Vec<Value>) {
+    let pre_blocks = html::select_pre_blocks(document, Some("userinput")).unwrap_or_default();
     let mut steps = Vec::new();
+    let mut variables: Vec<Value> = Vec::new();
+    let mut seen = HashSet::new();
 
-    for pre in document.select(&pre_selector) {
+    for pre in pre_blocks {
         let code = pre.text().collect::<Vec<_>>().join("\n");
-        let commands: Vec<String> = code
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty())
-            .collect();
+        let mut commands: Vec<String> = Vec::new();
+        for line in code.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match parse_export(line) {
+                Some(exports) => {
+                    for (name, value) in exports {
+                        if seen.insert(name.clone()) {
+                            variables.push(json!({ "name": name, "value": value }));
+                        }
+                    }
+                }
+                None => commands.push(line.to_string()),
+            }
+        }
 
         if commands.is_empty() {
             continue;
@@ -1101,7 +1898,58 @@ fn extract_build_steps(document: &Html) -> Vec<BuildStep> {
         });
     }
 
-    steps
+    (steps, variables)
+}
+
+/// Install prefix from `--prefix=...` configure arguments or `make install DESTDIR=...`.
+/// Warns on stderr when a page uses more than one and returns the first.
+fn infer_install_prefix(steps: &[BuildStep]) -> Option<String> {
+    let mut prefixes: Vec<String> = Vec::new();
+    for command in steps.iter().flat_map(|step| &step.commands) {
+        let words: Vec<&str> = command
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
+            .collect();
+        let is_install = words.windows(2).any(|pair| pair == ["make", "install"]);
+        let found = words.iter().find_map(|word| {
+            word.strip_prefix("--prefix=")
+                .or_else(|| is_install.then(|| word.strip_prefix("DESTDIR=")).flatten())
+        });
+        if let Some(prefix) = found
+            .filter(|prefix| !prefix.is_empty() && !prefixes.iter().any(|known| known == prefix))
+        {
+            prefixes.push(prefix.to_string());
+        }
+    }
+
+    if prefixes.len() > 1 {
+        eprintln!(
+            "warning: conflicting install prefixes ({}); using {}",
+            prefixes.join(", "),
+            prefixes[0]
+        );
+    }
+    prefixes.into_iter().next()
+}
+
+/// Assignments of an `export FOO=bar BAZ="qux"` line. `None` if the line is anything else,
+/// including a bare `export FOO` or a name the schema does not accept.
+fn parse_export(line: &str) -> Option<Vec<(String, String)>> {
+    let words = shell_words::split(line.strip_prefix("export ")?).ok()?;
+    if words.is_empty() {
+        return None;
+    }
+    words
+        .into_iter()
+        .map(|word| {
+            let (name, value) = word.split_once('=')?;
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '_');
+            valid.then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
 }
 
 fn classify_phase(commands: &[String]) -> &'static str {
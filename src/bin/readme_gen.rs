@@ -1,4 +1,8 @@
 use std::fs;
+use std::path::Path;
+
+use package_management::db;
+use package_management::pkgs::by_name;
 
 fn main() -> anyhow::Result<()> {
     let readme = Readme::build();
@@ -56,6 +60,54 @@ impl MarkdownDoc {
         self
     }
 
+    fn numbered_list<I, S>(mut self, items: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for (index, item) in items.into_iter().enumerate() {
+            self.buffer.push_str(&format!("{}. ", index + 1));
+            self.buffer.push_str(item.as_ref());
+            self.buffer.push('\n');
+        }
+        self.buffer.push('\n');
+        self
+    }
+
+    fn table(mut self, headers: &[&str], rows: &[Vec<String>]) -> Self {
+        let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count().max(3)).collect();
+        for row in rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let render_row = |cells: Vec<&str>| {
+            let mut line = String::from("|");
+            for (cell, width) in cells.iter().zip(&widths) {
+                let pad = width - cell.chars().count();
+                line.push_str(&format!(" {}{} |", cell, " ".repeat(pad)));
+            }
+            line.push('\n');
+            line
+        };
+
+        self.buffer.push_str(&render_row(headers.to_vec()));
+        self.buffer.push('|');
+        for width in &widths {
+            self.buffer.push_str(&format!(" {} |", "-".repeat(*width)));
+        }
+        self.buffer.push('\n');
+        for row in rows {
+            let cells = (0..widths.len())
+                .map(|i| row.get(i).map(String::as_str).unwrap_or(""))
+                .collect();
+            self.buffer.push_str(&render_row(cells));
+        }
+        self.buffer.push('\n');
+        self
+    }
+
     fn code_block(mut self, language: &str, code: &str) -> Self {
         self.buffer.push_str("```");
         self.buffer.push_str(language);
@@ -70,6 +122,51 @@ impl MarkdownDoc {
     }
 }
 
+fn inline_code(text: &str) -> String {
+    format!("`{}`", text)
+}
+
+fn bold(text: &str) -> String {
+    format!("**{}**", text)
+}
+
+fn italic(text: &str) -> String {
+    format!("*{}*", text)
+}
+
+/// Package definitions for the overview table: the local database when one exists, otherwise
+/// the definitions shipped under `src/pkgs/by_name`.
+fn package_overview_rows() -> Vec<Vec<String>> {
+    let from_db = Path::new(&db::database_url())
+        .exists()
+        .then(|| db::establish_pool().and_then(|pool| db::load_package_definitions_via_pool(&pool)))
+        .and_then(Result::ok)
+        .filter(|definitions| !definitions.is_empty());
+    let definitions = from_db.unwrap_or_else(|| {
+        vec![
+            by_name::binutils_pass_1::definition(),
+            by_name::gcc_pass_1::definition(),
+            by_name::linux::definition(),
+            by_name::glibc::definition(),
+        ]
+    });
+
+    definitions
+        .into_iter()
+        .map(|definition| {
+            vec![
+                definition.name,
+                definition.version,
+                definition.stage.unwrap_or_else(|| "-".to_string()),
+                definition
+                    .source
+                    .map(|url| inline_code(&url))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect()
+}
+
 struct Readme;
 
 impl Readme {
@@ -99,16 +196,36 @@ impl Readme {
             .horizontal_rule()
             .heading(2, "🧰 Usage")
             .paragraph("Basic command structure:")
-            .code_block("bash", "lpkg [command] [package]")
+            .code_block("bash", "lpkg workflow <command> [options]")
             .paragraph("Common commands:")
             .bullet_list([
-                "`install` – Install a package",
-                "`remove` – Remove a package",
-                "`update` – Update the package list",
-                "`upgrade` – Upgrade all installed packages",
+                format!("{} – Check the host's build tools against the book", inline_code("env-check")),
+                format!("{} – Scaffold and store every package from the MLFS catalogue", inline_code("import-mlfs")),
+                format!("{} – Download and verify a book's source archives", inline_code("download <dir>")),
+                format!("{} – Write a stored package as a bash build script", inline_code("export-script <name>")),
             ])
-            .paragraph("For detailed usage:")
-            .code_block("bash", "lpkg --help")
+            .paragraph(&format!("A typical session, {}:", italic("in order")))
+            .numbered_list([
+                format!("{} to make sure the host can build the toolchain.", inline_code("lpkg workflow env-check")),
+                format!("{} to generate the package modules and database entries.", inline_code("lpkg workflow import-mlfs")),
+                format!("{} to fetch the sources.", inline_code("lpkg workflow download --book mlfs $LFS/sources")),
+                format!("{} to get a build script for one package.", inline_code("lpkg workflow export-script binutils --output binutils.sh")),
+            ])
+            .paragraph(&format!("For {}:", bold("detailed usage")))
+            .code_block("bash", "lpkg workflow --help")
+            .paragraph("Global settings live in `~/.config/lpkg/config.toml`. Every key can be overridden by an `LPKG_*` environment variable (`LPKG_DATABASE_URL`, `LPKG_BASE_DIR`, `LPKG_MIRROR_URL`, `LPKG_MIRRORS_FILE`, `LPKG_MAX_DOWNLOAD_THREADS`, `LPKG_CACHE_DIR`, `LPKG_LOG_LEVEL`, `LPKG_VERSION_CHECK_URL`, `LPKG_POOL_SIZE`), and command-line flags override both. `lpkg workflow print-config` shows the resolved values:")
+            .code_block("toml", "database_url = \"/var/lib/lpkg/lpkg.db\"\ndefault_base_dir = \"src/pkgs/by_name\"\nmax_download_threads = 4\nlog_level = \"info\"\nversion_check_url = \"https://www.linuxfromscratch.org/lfs/view/stable/chapter02/hostreqs.html\"\ndb_pool_size = 4")
+            .paragraph("`db_pool_size` (or `--db-pool-size`) sets the number of database connections. SQLite allows only one writer at a time, so values above 1 only help read-heavy workloads.")
+            .paragraph("`mirrors_file` points at a TOML list of fallback mirrors. When a download fails, `lpkg workflow download` retries the file from each enabled mirror in turn:")
+            .code_block("toml", "[[mirrors]]\nurl = \"https://ftp.fau.de/lfs/lfs-packages/12.4\"\npriority = 10\nenabled = true")
+            .paragraph("Logs go to stderr. `--log-level` (default `warn`) sets the verbosity and `--log-format json` emits one JSON object per line for log aggregators such as Loki:")
+            .code_block("bash", "lpkg --log-format json --log-level info workflow import-mlfs --dry-run")
+            .paragraph("When the MLFS book cannot be reached, lpkg falls back to the package catalogue compiled into the binary. Point `LPKG_MLFS_CACHE` at a JSON file to use a newer catalogue without rebuilding:")
+            .code_block("bash", "LPKG_MLFS_CACHE=/srv/ci/mlfs-catalog.json lpkg workflow import-mlfs --dry-run")
+            .horizontal_rule()
+            .heading(2, "📦 Packages")
+            .paragraph("Package definitions known to this checkout:")
+            .table(&["Name", "Version", "Stage", "Source"], &package_overview_rows())
             .horizontal_rule()
             .heading(2, "🔧 Development with Flakes")
             .paragraph("Clone the repository:")
@@ -148,19 +265,37 @@ RUSTFLAGS="-Cprofile-use=target/pgo-data/lpkg.profdata -Cllvm-args=-pgo-warn-mis
                 "Start the server with `cargo run --features graphql --bin graphql_server` (set `LPKG_GRAPHQL_ADDR` to override `127.0.0.1:8080`).",
                 "Query endpoint: `http://127.0.0.1:8080/graphql`",
                 "Interactive playground: `http://127.0.0.1:8080/playground`",
+                "Download progress (Server-Sent Events): `http://127.0.0.1:8080/events/downloads/<session-id>`, using the id returned by the `startDownload` mutation.",
+                "`bookPackages(book: \"blfs\", chapter: 9, limit: 20)` scrapes the live book (`lfs`, `mlfs`, `blfs` or `glfs`) on first use and serves later queries from memory.",
+                "Jokes are read from `ai/jokes.json`; after editing it, call the `reloadJokes` mutation (bearer token from `LPKG_GRAPHQL_TOKEN`) to pick up the changes without a restart.",
             ])
             .paragraph("Example query:")
-            .code_block("graphql", r"{
-  packages(limit: 5) {
+            .code_block("graphql", r#"{
+  packages(first: 5) {
+    totalCount
+    edges {
+      cursor
+      node {
+        name
+        version
+        enableLto
+      }
+    }
+    pageInfo {
+      hasNextPage
+      endCursor
+    }
+  }
+  search(input: { query: "gcc", stage: "cross-toolchain", lto: true, limit: 10 }) {
     name
     version
-    enableLto
+    variant
   }
   randomJoke {
     package
     text
   }
-}")
+}"#)
             .heading(3, "AI metadata tooling")
             .paragraph("The AI metadata store under `ai/metadata/` comes with a helper CLI to validate package records against the JSON schema and regenerate `index.json` after adding new entries:")
             .code_block("bash", r"cargo run --bin metadata_indexer -- --base-dir . validate
@@ -174,13 +309,41 @@ cargo run --bin metadata_indexer -- --base-dir . index")
   --dry-run")
             .paragraph("Keep the jhalfs manifests current with:")
             .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . refresh")
-            .paragraph("Passing `--books mlfs,blfs` restricts the refresh to specific books, and `--force` bypasses the local cache.")
+            .paragraph("Passing `--books mlfs,blfs` restricts the refresh to specific books, and `--force` bypasses the local cache. `--summary-output refresh.json` records each refreshed manifest (book, kind, path, size) with an RFC 3339 timestamp for CI freshness checks.")
             .paragraph("To materialise a Rust module from harvested metadata:")
             .code_block("bash", r"cargo run --bin metadata_indexer -- \
   --base-dir . generate \
   --metadata ai/metadata/packages/mlfs/binutils-pass-1.json \
   --output target/generated/by_name")
             .paragraph("Add `--overwrite` to regenerate an existing module directory.")
+            .paragraph("To see what changed between two index snapshots:")
+            .code_block("bash", r"cargo run --bin metadata_indexer -- \
+  --base-dir . diff old-index.json ai/metadata/index.json")
+            .paragraph("Added packages are shown in green, removed ones in red, and version/status changes in yellow. Pass `--json` for a machine-readable `{ \"added\", \"removed\", \"changed\" }` document, or `--output <file>` to save it.")
+            .paragraph("Summarise build effort and metadata gaps (SBU totals per stage, missing source URLs/checksums, non-draft records):")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . stats")
+            .paragraph("Add `--csv` to produce spreadsheet-friendly output.")
+            .paragraph("Estimate how long a whole book takes to build from the recorded SBU values (optionally limited to one stage; 1 SBU defaults to 10 minutes):")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . estimate-build --book mlfs --stage cross-toolchain --minutes-per-sbu 12")
+            .paragraph("Catch link rot in the recorded book anchors (every `package.anchors.section` URL must answer `HEAD` with HTTP 200):")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . validate-anchors --parallel 16 --timeout-ms 5000")
+            .paragraph("Harvest several pages in one go by repeating `--page`, or list them one per line in a file. Failed pages are reported at the end without aborting the batch, and `--output` names the directory that receives each `<slug>.json`:")
+            .code_block("bash", r"cargo run --bin metadata_indexer -- \
+  --base-dir . harvest \
+  --book mlfs \
+  --page chapter05/binutils-pass1 \
+  --page chapter05/gcc-pass1 \
+  --pages-file ci/pages.txt")
+            .paragraph("While editing metadata, `watch` re-validates and rebuilds `index.json` after every save under `ai/metadata/packages`, printing the added, removed and changed packages. Stop it with Ctrl-C:")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . watch --debounce-ms 750")
+            .paragraph("`dep-table` renders the build and runtime dependencies of one book as a Markdown table, ordered by stage and section, ready to paste into the docs:")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . dep-table --book mlfs --output docs/mlfs-dependencies.md")
+            .paragraph("To check what `generate` would produce without writing anything (handy in CI), print the module source with `preview`:")
+            .code_block("bash", "cargo run --bin metadata_indexer -- --base-dir . preview --metadata ai/metadata/packages/mlfs/binutils-pass-1.json")
+            .paragraph("Working against an internal mirror? `--base-url-override book=url` (repeatable) points page fetches and manifest downloads for that book at the mirror; manifests keep their upstream file names:")
+            .code_block("bash", r"cargo run --bin metadata_indexer -- --base-dir . \
+  --base-url-override lfs=https://mirror.example/lfs/view/12.1 \
+  refresh --books lfs --force")
             .heading(2, "📚 Documentation")
             .bullet_list([
                 "[Architecture Overview](docs/ARCHITECTURE.md) – high-level tour of the crate layout, binaries, and supporting modules.",
@@ -0,0 +1,125 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::mirrors::MirrorList;
+
+static CONFIG: OnceLock<LpkgConfig> = OnceLock::new();
+
+/// Global `lpkg` settings read from `~/.config/lpkg/config.toml`.
+///
+/// Every field can also be set through an `LPKG_*` environment variable, which takes precedence
+/// over the file; command-line flags in turn override both.
+///
+/// ```toml
+/// database_url = "/var/lib/lpkg/lpkg.db"
+/// default_base_dir = "src/pkgs/by_name"
+/// max_download_threads = 4
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LpkgConfig {
+    /// SQLite database URL (`LPKG_DATABASE_URL`).
+    pub database_url: Option<String>,
+    /// Default `by_name` directory for scaffolding commands (`LPKG_BASE_DIR`).
+    pub default_base_dir: Option<PathBuf>,
+    /// Preferred package mirror (`LPKG_MIRROR_URL`).
+    pub mirror_url: Option<String>,
+    /// TOML list of fallback mirrors used by the downloader (`LPKG_MIRRORS_FILE`).
+    pub mirrors_file: Option<PathBuf>,
+    /// Upper bound for concurrent downloads (`LPKG_MAX_DOWNLOAD_THREADS`).
+    pub max_download_threads: Option<usize>,
+    /// Directory for cached downloads and manifests (`LPKG_CACHE_DIR`).
+    pub cache_dir: Option<PathBuf>,
+    /// Default log level such as `info` or `debug` (`LPKG_LOG_LEVEL`).
+    pub log_level: Option<String>,
+    /// Default page for `EnvCheck` when no URL is given (`LPKG_VERSION_CHECK_URL`).
+    pub version_check_url: Option<String>,
+    /// Database connection pool size (`LPKG_POOL_SIZE`).
+    pub db_pool_size: Option<u32>,
+}
+
+impl LpkgConfig {
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lpkg").join("config.toml"))
+    }
+
+    /// Read the config file (if any) and apply `LPKG_*` environment overrides.
+    pub fn load() -> Result<Self> {
+        let mut config = match Self::config_path() {
+            Some(path) if path.exists() => {
+                let raw = fs::read_to_string(&path)
+                    .with_context(|| format!("reading {}", path.display()))?;
+                Self::from_toml(&raw).with_context(|| format!("parsing {}", path.display()))?
+            }
+            _ => Self::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(raw: &str) -> Result<Self> {
+        Ok(toml::from_str(raw)?)
+    }
+
+    /// Load the mirror list named by `mirrors_file`, if one is configured.
+    pub fn mirror_list(&self) -> Result<Option<MirrorList>> {
+        self.mirrors_file
+            .as_deref()
+            .map(MirrorList::from_toml)
+            .transpose()
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+
+        if let Some(value) = var("LPKG_DATABASE_URL") {
+            self.database_url = Some(value);
+        }
+        if let Some(value) = var("LPKG_BASE_DIR") {
+            self.default_base_dir = Some(value.into());
+        }
+        if let Some(value) = var("LPKG_MIRROR_URL") {
+            self.mirror_url = Some(value);
+        }
+        if let Some(value) = var("LPKG_MIRRORS_FILE") {
+            self.mirrors_file = Some(value.into());
+        }
+        if let Some(value) = var("LPKG_MAX_DOWNLOAD_THREADS") {
+            let threads = value
+                .parse()
+                .with_context(|| format!("invalid LPKG_MAX_DOWNLOAD_THREADS '{value}'"))?;
+            self.max_download_threads = Some(threads);
+        }
+        if let Some(value) = var("LPKG_CACHE_DIR") {
+            self.cache_dir = Some(value.into());
+        }
+        if let Some(value) = var("LPKG_LOG_LEVEL") {
+            self.log_level = Some(value);
+        }
+        if let Some(value) = var("LPKG_VERSION_CHECK_URL") {
+            self.version_check_url = Some(value);
+        }
+        if let Some(value) = var("LPKG_POOL_SIZE") {
+            let size = value
+                .parse()
+                .with_context(|| format!("invalid LPKG_POOL_SIZE '{value}'"))?;
+            self.db_pool_size = Some(size);
+        }
+        Ok(())
+    }
+}
+
+/// Install the process-wide configuration. Only the first call has an effect.
+pub fn init(config: LpkgConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// The configuration passed to [`init`], or defaults when none was installed.
+pub fn get() -> &'static LpkgConfig {
+    CONFIG.get_or_init(LpkgConfig::default)
+}
@@ -5,11 +5,11 @@ use std::cmp;
 use std::env;
 
 use anyhow::{Context, Result};
-use diesel::OptionalExtension;
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
 use diesel::sqlite::SqliteConnection;
 
+use crate::config;
 use crate::pkgs::package::PackageDefinition;
 
 use self::models::{NewPackage, Package};
@@ -19,16 +19,38 @@ pub type Pool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 pub type Connection = r2d2::PooledConnection<ConnectionManager<SqliteConnection>>;
 
 const DEFAULT_DB_URL: &str = "lpkg.db";
+const DEFAULT_POOL_SIZE: u32 = 4;
 
-/// Resolve the database URL from `LPKG_DATABASE_URL` or fall back to `lpkg.db` in the CWD.
+/// Characters treated as interchangeable word separators in search terms.
+const SEARCH_SEPARATORS: &[char] = &['·', '-', '_'];
+
+diesel::define_sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+/// Resolve the database URL from `LPKG_DATABASE_URL`, then `database_url` in the lpkg config,
+/// falling back to `lpkg.db` in the CWD.
 pub fn database_url() -> String {
-    env::var("LPKG_DATABASE_URL").unwrap_or_else(|_| DEFAULT_DB_URL.to_string())
+    env::var("LPKG_DATABASE_URL")
+        .ok()
+        .or_else(|| config::get().database_url.clone())
+        .unwrap_or_else(|| DEFAULT_DB_URL.to_string())
 }
 
-/// Build an r2d2 connection pool and ensure the schema exists.
+/// Build an r2d2 connection pool and ensure the schema exists. The size comes from the lpkg
+/// config (`--db-pool-size` / `LPKG_POOL_SIZE`), defaulting to 4.
 pub fn establish_pool() -> Result<Pool> {
+    let size = config::get()
+        .db_pool_size
+        .or_else(|| env::var("LPKG_POOL_SIZE").ok()?.parse().ok())
+        .unwrap_or(DEFAULT_POOL_SIZE);
+    establish_pool_with_size(size)
+}
+
+/// Like [`establish_pool`] with an explicit number of connections. SQLite serialises writers, so
+/// sizes above 1 only help read-heavy workloads.
+pub fn establish_pool_with_size(size: u32) -> Result<Pool> {
     let manager = ConnectionManager::<SqliteConnection>::new(database_url());
     let pool = Pool::builder()
+        .max_size(size.max(1))
         .build(manager)
         .context("creating Diesel connection pool")?;
 
@@ -58,12 +80,20 @@ fn initialize(conn: &mut SqliteConnection) -> Result<()> {
             enable_pgo BOOLEAN NOT NULL DEFAULT 1,
             cflags TEXT,
             ldflags TEXT,
-            profdata TEXT
+            profdata TEXT,
+            stage TEXT,
+            variant TEXT,
+            notes TEXT,
+            patch_commands TEXT
         )",
     )
     .execute(conn)
     .context("creating packages table")?;
 
+    for column in ["stage", "variant", "notes", "patch_commands"] {
+        ensure_column(conn, column)?;
+    }
+
     diesel::sql_query(
         "CREATE UNIQUE INDEX IF NOT EXISTS idx_packages_name_version ON packages(name, version)",
     )
@@ -73,6 +103,25 @@ fn initialize(conn: &mut SqliteConnection) -> Result<()> {
     Ok(())
 }
 
+#[derive(QueryableByName)]
+struct ColumnInfo {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+}
+
+/// Add a nullable TEXT column to databases created before the column existed.
+fn ensure_column(conn: &mut SqliteConnection, column: &str) -> Result<()> {
+    let columns = diesel::sql_query("PRAGMA table_info(packages)")
+        .load::<ColumnInfo>(conn)
+        .context("inspecting packages table")?;
+    if !columns.iter().any(|info| info.name == column) {
+        diesel::sql_query(format!("ALTER TABLE packages ADD COLUMN {column} TEXT"))
+            .execute(conn)
+            .with_context(|| format!("adding {column} column to packages table"))?;
+    }
+    Ok(())
+}
+
 /// Insert or update a package definition in the database.
 pub fn upsert_package(conn: &mut SqliteConnection, definition: &PackageDefinition) -> Result<()> {
     let record = NewPackage::try_from(definition)?;
@@ -108,6 +157,131 @@ pub fn load_packages_via_pool(pool: &Pool) -> Result<Vec<Package>> {
     load_packages(&mut conn)
 }
 
+/// Packages lacking a source URL or an MD5 checksum, ordered by name.
+pub fn list_packages_missing_checksums(conn: &mut SqliteConnection) -> Result<Vec<Package>> {
+    packages_dsl::packages
+        .filter(
+            packages_dsl::md5
+                .is_null()
+                .or(packages_dsl::source.is_null()),
+        )
+        .order((packages_dsl::name, packages_dsl::version))
+        .load::<Package>(conn)
+        .context("loading packages without checksums")
+}
+
+/// Pool-backed variant of [`list_packages_missing_checksums`].
+pub fn list_packages_missing_checksums_via_pool(pool: &Pool) -> Result<Vec<Package>> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    list_packages_missing_checksums(&mut conn)
+}
+
+/// Load up to `limit` packages ordered by `(name, version)`, starting strictly after the given
+/// `(name, version)` key when provided.
+pub fn load_packages_page(
+    conn: &mut SqliteConnection,
+    after: Option<(&str, &str)>,
+    limit: i64,
+) -> Result<Vec<Package>> {
+    let mut query = packages_dsl::packages
+        .order((packages_dsl::name, packages_dsl::version))
+        .into_boxed();
+
+    if let Some((name, version)) = after {
+        query = query.filter(
+            packages_dsl::name
+                .gt(name.to_string())
+                .or(packages_dsl::name
+                    .eq(name.to_string())
+                    .and(packages_dsl::version.gt(version.to_string()))),
+        );
+    }
+
+    query
+        .limit(limit)
+        .load::<Package>(conn)
+        .context("loading package page from database")
+}
+
+/// Pool-backed variant of [`load_packages_page`].
+pub fn load_packages_page_via_pool(
+    pool: &Pool,
+    after: Option<(&str, &str)>,
+    limit: i64,
+) -> Result<Vec<Package>> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    load_packages_page(&mut conn, after, limit)
+}
+
+/// Count all stored packages.
+pub fn count_packages(conn: &mut SqliteConnection) -> Result<i64> {
+    packages_dsl::packages
+        .count()
+        .get_result(conn)
+        .context("counting packages")
+}
+
+/// Pool-backed variant of [`count_packages`].
+pub fn count_packages_via_pool(pool: &Pool) -> Result<i64> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    count_packages(&mut conn)
+}
+
+/// Aggregate counts over the package table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageStats {
+    pub total: i64,
+    pub lto_enabled: i64,
+    pub pgo_enabled: i64,
+}
+
+impl PackageStats {
+    /// Share of packages with LTO enabled, in percent (0 when the table is empty).
+    pub fn lto_percent(&self) -> f64 {
+        percent(self.lto_enabled, self.total)
+    }
+
+    /// Share of packages with PGO enabled, in percent (0 when the table is empty).
+    pub fn pgo_percent(&self) -> f64 {
+        percent(self.pgo_enabled, self.total)
+    }
+}
+
+fn percent(part: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Count packages overall and with LTO/PGO enabled.
+pub fn package_stats(conn: &mut SqliteConnection) -> Result<PackageStats> {
+    let total = count_packages(conn)?;
+    let lto_enabled = packages_dsl::packages
+        .filter(packages_dsl::enable_lto.eq(true))
+        .count()
+        .get_result(conn)
+        .context("counting LTO-enabled packages")?;
+    let pgo_enabled = packages_dsl::packages
+        .filter(packages_dsl::enable_pgo.eq(true))
+        .count()
+        .get_result(conn)
+        .context("counting PGO-enabled packages")?;
+
+    Ok(PackageStats {
+        total,
+        lto_enabled,
+        pgo_enabled,
+    })
+}
+
+/// Pool-backed variant of [`package_stats`].
+pub fn package_stats_via_pool(pool: &Pool) -> Result<PackageStats> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    package_stats(&mut conn)
+}
+
 /// Load package definitions instead of raw Diesel models for convenience.
 pub fn load_package_definitions(conn: &mut SqliteConnection) -> Result<Vec<PackageDefinition>> {
     load_packages(conn)?
@@ -137,11 +311,35 @@ pub fn find_package(
         query = query.filter(packages_dsl::version.eq(version));
     }
 
-    query
-        .order(packages_dsl::version.desc())
-        .first::<Package>(conn)
-        .optional()
-        .context("querying package by name")
+    // Version strings don't sort numerically in SQL ("2.10" < "2.9"), so pick the newest here.
+    let packages = query
+        .load::<Package>(conn)
+        .context("querying package by name")?;
+    Ok(packages
+        .into_iter()
+        .max_by_key(|pkg| semver_sort_key(&pkg.version)))
+}
+
+/// Sort key comparing the numeric `major.minor.patch` prefix of `version`; missing or
+/// non-numeric components count as 0 and whatever follows breaks ties.
+fn semver_sort_key(version: &str) -> (u64, u64, u64, String) {
+    let mut numbers = [0u64; 3];
+    let mut rest = version.trim();
+    for slot in numbers.iter_mut() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            break;
+        }
+        *slot = rest[..digits].parse().unwrap_or(0);
+        rest = &rest[digits..];
+        match rest.strip_prefix('.') {
+            Some(tail) => rest = tail,
+            None => break,
+        }
+    }
+    (numbers[0], numbers[1], numbers[2], rest.to_string())
 }
 
 /// Convenience wrapper returning the package as a [`PackageDefinition`].
@@ -150,9 +348,9 @@ pub fn find_package_definition(
     name: &str,
     version: Option<&str>,
 ) -> Result<Option<PackageDefinition>> {
-    Ok(find_package(conn, name, version)?
+    find_package(conn, name, version)?
         .map(|pkg| pkg.into_definition())
-        .transpose()?)
+        .transpose()
 }
 
 /// Pool-backed variant of [`find_package_definition`].
@@ -165,6 +363,41 @@ pub fn find_package_definition_via_pool(
     find_package_definition(&mut conn, name, version)
 }
 
+/// Delete a package by name, limited to a single version when one is given. Returns the number
+/// of removed rows.
+pub fn delete_package(
+    conn: &mut SqliteConnection,
+    name: &str,
+    version: Option<&str>,
+) -> Result<usize> {
+    let mut query = diesel::delete(packages_dsl::packages)
+        .filter(packages_dsl::name.eq(name))
+        .into_boxed();
+
+    if let Some(version) = version {
+        query = query.filter(packages_dsl::version.eq(version));
+    }
+
+    query.execute(conn).context("deleting package record")
+}
+
+/// Pool-backed variant of [`delete_package`].
+pub fn delete_package_via_pool(pool: &Pool, name: &str, version: Option<&str>) -> Result<usize> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    delete_package(&mut conn, name, version)
+}
+
+/// Filters accepted by [`search_packages_with_options`]. Unset fields do not constrain results.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub query: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub lto: Option<bool>,
+    pub pgo: Option<bool>,
+    pub limit: Option<i64>,
+}
+
 /// Locate packages using a basic substring match on the name, ordered deterministically and
 /// optionally limited for responsiveness.
 pub fn search_packages(
@@ -172,25 +405,77 @@ pub fn search_packages(
     term: &str,
     limit: Option<i64>,
 ) -> Result<Vec<Package>> {
-    let trimmed = term.trim();
-    if trimmed.is_empty() {
+    if term.trim().is_empty() {
         return Ok(Vec::new());
     }
 
-    let normalized: String = trimmed.chars().take(128).collect();
-    let sanitized = normalized.replace('%', "\\%").replace('_', "\\_");
-    let pattern = format!("%{}%", sanitized);
+    let options = SearchOptions {
+        query: Some(term.to_string()),
+        limit,
+        ..SearchOptions::default()
+    };
+    search_packages_with_options(conn, &options)
+}
+
+/// Search packages by name substring combined with stage, variant and optimization filters.
+pub fn search_packages_with_options(
+    conn: &mut SqliteConnection,
+    options: &SearchOptions,
+) -> Result<Vec<Package>> {
     let mut query = packages_dsl::packages
-        .filter(packages_dsl::name.like(&pattern))
         .order((packages_dsl::name, packages_dsl::version))
         .into_boxed();
 
-    let effective_limit = limit.map(|value| cmp::max(1, value)).unwrap_or(50);
+    if let Some(term) = options
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+    {
+        query = query.filter(
+            lower(packages_dsl::name)
+                .like(search_pattern(term))
+                .escape('\\'),
+        );
+    }
+    if let Some(stage) = &options.stage {
+        query = query.filter(packages_dsl::stage.eq(stage.clone()));
+    }
+    if let Some(variant) = &options.variant {
+        query = query.filter(packages_dsl::variant.eq(variant.clone()));
+    }
+    if let Some(lto) = options.lto {
+        query = query.filter(packages_dsl::enable_lto.eq(lto));
+    }
+    if let Some(pgo) = options.pgo {
+        query = query.filter(packages_dsl::enable_pgo.eq(pgo));
+    }
+
+    let effective_limit = options.limit.map(|value| cmp::max(1, value)).unwrap_or(50);
     query = query.limit(cmp::min(effective_limit, 200));
 
-    query
-        .load::<Package>(conn)
-        .context("searching packages by name")
+    query.load::<Package>(conn).context("searching packages")
+}
+
+/// Case-insensitive `LIKE` pattern for `term` in which every run of whitespace, `·`, `-` or `_`
+/// matches any characters, so "gcc pass 1" also finds "gcc_pass_1".
+fn search_pattern(term: &str) -> String {
+    let normalized: String = term.chars().take(128).collect::<String>().to_lowercase();
+    let words: Vec<String> = normalized
+        .split(|c: char| c.is_whitespace() || SEARCH_SEPARATORS.contains(&c))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.replace('\\', "\\\\").replace('%', "\\%"))
+        .collect();
+    format!("%{}%", words.join("%"))
+}
+
+/// Pool-backed variant of [`search_packages_with_options`].
+pub fn search_packages_with_options_via_pool(
+    pool: &Pool,
+    options: &SearchOptions,
+) -> Result<Vec<Package>> {
+    let mut conn = pool.get().context("acquiring database connection")?;
+    search_packages_with_options(&mut conn, options)
 }
 
 /// Pool-backed variant of [`search_packages`].
@@ -202,3 +487,62 @@ pub fn search_packages_via_pool(
     let mut conn = pool.get().context("acquiring database connection")?;
     search_packages(&mut conn, term, limit)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::Connection;
+
+    fn memory_db(names: &[&str]) -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        initialize(&mut conn).unwrap();
+        for name in names {
+            upsert_package(&mut conn, &PackageDefinition::new(*name, "1.0")).unwrap();
+        }
+        conn
+    }
+
+    fn names(packages: Vec<Package>) -> Vec<String> {
+        packages.into_iter().map(|pkg| pkg.name).collect()
+    }
+
+    #[test]
+    fn search_ignores_case() {
+        let mut conn = memory_db(&["GCC", "glibc"]);
+        assert_eq!(
+            names(search_packages(&mut conn, "gcc", None).unwrap()),
+            ["GCC"]
+        );
+        assert_eq!(
+            names(search_packages(&mut conn, "GLIBC", None).unwrap()),
+            ["glibc"]
+        );
+    }
+
+    #[test]
+    fn search_normalizes_separators() {
+        let mut conn = memory_db(&["gcc_pass_1", "gcc-pass-2", "binutils"]);
+        assert_eq!(
+            names(search_packages(&mut conn, "gcc pass 1", None).unwrap()),
+            ["gcc_pass_1"]
+        );
+        assert_eq!(
+            names(search_packages(&mut conn, "GCC·pass", None).unwrap()),
+            ["gcc-pass-2", "gcc_pass_1"]
+        );
+    }
+
+    #[test]
+    fn missing_checksums_are_listed() {
+        let mut conn = memory_db(&["binutils", "gcc"]);
+        let mut complete = PackageDefinition::new("glibc", "2.42");
+        complete.source = Some("https://ftp.gnu.org/gnu/glibc/glibc-2.42.tar.xz".into());
+        complete.md5 = Some("23c6f5a27932b435cae94e087cb8b1f5".into());
+        upsert_package(&mut conn, &complete).unwrap();
+
+        assert_eq!(
+            names(list_packages_missing_checksums(&mut conn).unwrap()),
+            ["binutils", "gcc"]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use crate::pkgs::package::PackageDefinition;
 
 use super::schema::packages;
 
-#[derive(Debug, Queryable, Serialize, Deserialize)]
+#[derive(Debug, Clone, Queryable, Serialize, Deserialize)]
 pub struct Package {
     pub id: i32,
     pub name: String,
@@ -22,6 +22,10 @@ pub struct Package {
     pub cflags: Option<String>,
     pub ldflags: Option<String>,
     pub profdata: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub notes: Option<String>,
+    pub patch_commands: Option<String>,
 }
 
 impl Package {
@@ -32,6 +36,7 @@ impl Package {
             source: self.source,
             md5: self.md5,
             configure_args: parse_vec(self.configure_args)?,
+            patch_commands: parse_vec(self.patch_commands)?,
             build_commands: parse_vec(self.build_commands)?,
             install_commands: parse_vec(self.install_commands)?,
             dependencies: parse_vec(self.dependencies)?,
@@ -42,6 +47,9 @@ impl Package {
                 ldflags: parse_vec(self.ldflags)?,
                 profdata: self.profdata,
             },
+            stage: self.stage,
+            variant: self.variant,
+            notes: self.notes,
         })
     }
 }
@@ -62,6 +70,10 @@ pub struct NewPackage {
     pub cflags: Option<String>,
     pub ldflags: Option<String>,
     pub profdata: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub notes: Option<String>,
+    pub patch_commands: Option<String>,
 }
 
 impl TryFrom<&PackageDefinition> for NewPackage {
@@ -82,6 +94,10 @@ impl TryFrom<&PackageDefinition> for NewPackage {
             cflags: serialize_vec(&value.optimizations.cflags)?,
             ldflags: serialize_vec(&value.optimizations.ldflags)?,
             profdata: value.optimizations.profdata.clone(),
+            stage: value.stage.clone(),
+            variant: value.variant.clone(),
+            notes: value.notes.clone(),
+            patch_commands: serialize_vec(&value.patch_commands)?,
         })
     }
 }
@@ -15,5 +15,9 @@ diesel::table! {
         cflags -> Nullable<Text>,
         ldflags -> Nullable<Text>,
         profdata -> Nullable<Text>,
+        stage -> Nullable<Text>,
+        variant -> Nullable<Text>,
+        notes -> Nullable<Text>,
+        patch_commands -> Nullable<Text>,
     }
 }
@@ -0,0 +1,721 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "graphql")]
+use tokio::sync::broadcast;
+
+use crate::md5_utils::Checksum;
+use crate::mirrors::MirrorList;
+use crate::wget_list;
+
+/// File name of the persisted queue inside the download target directory.
+pub const QUEUE_FILE_NAME: &str = ".lpkg-queue.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Pending,
+    InProgress,
+    Completed,
+    /// Already present from an earlier run with a matching checksum.
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for EntryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            EntryStatus::Pending => "pending",
+            EntryStatus::InProgress => "in_progress",
+            EntryStatus::Completed => "completed",
+            EntryStatus::Skipped => "skipped",
+            EntryStatus::Failed => "failed",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A single file tracked by the download queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadEntry {
+    pub url: String,
+    pub filename: String,
+    pub expected_bytes: Option<u64>,
+    pub checksum: Option<Checksum>,
+    pub downloaded_bytes: u64,
+    pub status: EntryStatus,
+}
+
+impl DownloadEntry {
+    /// Create a pending entry, deriving the file name from the last URL path segment.
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into();
+        let filename = filename_from_url(&url);
+        Self {
+            url,
+            filename,
+            expected_bytes: None,
+            checksum: None,
+            downloaded_bytes: 0,
+            status: EntryStatus::Pending,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+}
+
+/// Progress snapshot reported while a queue entry is being downloaded.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub filename: String,
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>,
+    pub status: EntryStatus,
+}
+
+impl DownloadProgress {
+    fn from_entry(entry: &DownloadEntry) -> Self {
+        Self {
+            filename: entry.filename.clone(),
+            bytes_downloaded: entry.downloaded_bytes,
+            total_bytes: entry.expected_bytes,
+            status: entry.status,
+        }
+    }
+
+    /// Completion percentage, when the total size is known.
+    pub fn percent(&self) -> Option<f64> {
+        self.total_bytes
+            .filter(|total| *total > 0)
+            .map(|total| self.bytes_downloaded as f64 / total as f64 * 100.0)
+    }
+}
+
+/// Download queue persisted to `{target_dir}/.lpkg-queue.json` so cancelled runs can resume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadQueue {
+    pub entries: Vec<DownloadEntry>,
+    pub target_dir: PathBuf,
+}
+
+impl DownloadQueue {
+    pub fn new(target_dir: impl Into<PathBuf>, entries: Vec<DownloadEntry>) -> Self {
+        Self {
+            entries,
+            target_dir: target_dir.into(),
+        }
+    }
+
+    pub fn queue_path(target_dir: &Path) -> PathBuf {
+        target_dir.join(QUEUE_FILE_NAME)
+    }
+
+    /// Load a previously persisted queue, returning `None` when no queue file exists.
+    pub fn load(target_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::queue_path(target_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("reading download queue {}", path.display()))?;
+        let queue = serde_json::from_str(&data)
+            .with_context(|| format!("parsing download queue {}", path.display()))?;
+        Ok(Some(queue))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::queue_path(&self.target_dir);
+        let data = serde_json::to_string_pretty(self).context("serializing download queue")?;
+        fs::write(&path, data).with_context(|| format!("writing download queue {}", path.display()))
+    }
+
+    /// Delete the queue file from `target_dir`. Returns whether a file was removed.
+    pub fn remove(target_dir: &Path) -> Result<bool> {
+        let path = Self::queue_path(target_dir);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => {
+                Err(err).with_context(|| format!("removing download queue {}", path.display()))
+            }
+        }
+    }
+
+    /// Append entries whose URL is not already tracked, keeping existing progress intact.
+    pub fn merge(&mut self, entries: Vec<DownloadEntry>) {
+        for entry in entries {
+            if !self
+                .entries
+                .iter()
+                .any(|existing| existing.url == entry.url)
+            {
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &DownloadEntry> {
+        self.entries.iter().filter(|entry| !entry.status.is_done())
+    }
+
+    /// Number of entries currently in `status`.
+    pub fn count(&self, status: EntryStatus) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == status)
+            .count()
+    }
+}
+
+impl EntryStatus {
+    /// Completed or skipped: nothing left to fetch.
+    pub fn is_done(self) -> bool {
+        matches!(self, EntryStatus::Completed | EntryStatus::Skipped)
+    }
+}
+
+/// Options for [`download_files_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Fetch every file again, even those already on disk with a matching checksum.
+    pub force: bool,
+    /// Mirrors tried in round-robin order when an entry's own URL fails.
+    pub mirrors: Option<Arc<MirrorList>>,
+    /// Set from another thread to stop after the current chunk. The interrupted entry goes back
+    /// to [`EntryStatus::Pending`] so the next run resumes it.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl DownloadOptions {
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+}
+
+/// Download every entry into `target_dir`, resuming from a persisted queue when present.
+///
+/// The queue is written after each file completes; completed entries from a previous run are
+/// skipped and partially downloaded files are resumed with an HTTP range request.
+pub fn download_files(target_dir: &Path, entries: Vec<DownloadEntry>) -> Result<DownloadQueue> {
+    download_files_with_progress(target_dir, entries, |_| {})
+}
+
+/// Same as [`download_files`], invoking `on_progress` as bytes arrive and when entries finish.
+pub fn download_files_with_progress(
+    target_dir: &Path,
+    entries: Vec<DownloadEntry>,
+    on_progress: impl FnMut(DownloadProgress),
+) -> Result<DownloadQueue> {
+    download_files_with_options(target_dir, entries, DownloadOptions::default(), on_progress)
+}
+
+/// Same as [`download_files_with_progress`]. Files already on disk whose checksum matches are
+/// marked [`EntryStatus::Skipped`] instead of being fetched, unless `options.force` is set.
+/// A cancelled run returns `Ok` with the unfinished entries still pending.
+pub fn download_files_with_options(
+    target_dir: &Path,
+    entries: Vec<DownloadEntry>,
+    options: DownloadOptions,
+    mut on_progress: impl FnMut(DownloadProgress),
+) -> Result<DownloadQueue> {
+    fs::create_dir_all(target_dir)
+        .with_context(|| format!("creating download directory {}", target_dir.display()))?;
+
+    let mut queue = match DownloadQueue::load(target_dir)? {
+        Some(mut existing) => {
+            existing.target_dir = target_dir.to_path_buf();
+            existing.merge(entries);
+            existing
+        }
+        None => DownloadQueue::new(target_dir, entries),
+    };
+    if options.force {
+        for entry in &mut queue.entries {
+            entry.status = EntryStatus::Pending;
+            entry.downloaded_bytes = 0;
+        }
+    }
+    queue.save()?;
+
+    let agent = ureq::AgentBuilder::new().redirects(5).build();
+
+    for idx in 0..queue.entries.len() {
+        if options.is_cancelled() {
+            break;
+        }
+        if queue.entries[idx].status.is_done() {
+            continue;
+        }
+
+        let path = target_dir.join(&queue.entries[idx].filename);
+        if options.force {
+            // Start from scratch rather than resuming whatever is on disk.
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    return Err(err).with_context(|| format!("removing {}", path.display()));
+                }
+                _ => {}
+            }
+        } else if is_verified(&path, &queue.entries[idx])? {
+            let size = fs::metadata(&path).map(|meta| meta.len()).ok();
+            let entry = &mut queue.entries[idx];
+            entry.downloaded_bytes = size.unwrap_or(0);
+            entry.expected_bytes = size;
+            entry.status = EntryStatus::Skipped;
+            on_progress(DownloadProgress::from_entry(entry));
+            queue.save()?;
+            continue;
+        }
+
+        let result = match (
+            download_entry(
+                &agent,
+                target_dir,
+                &mut queue.entries[idx],
+                options.cancel.as_deref(),
+                &mut on_progress,
+            ),
+            &options.mirrors,
+        ) {
+            (Err(err), Some(mirrors)) if !options.is_cancelled() => retry_from_mirrors(
+                &agent,
+                target_dir,
+                &mut queue.entries[idx],
+                mirrors,
+                options.cancel.as_deref(),
+                &mut on_progress,
+                err,
+            ),
+            (result, _) => result,
+        };
+        let entry = &mut queue.entries[idx];
+        match result {
+            Ok(()) => {
+                entry.status = EntryStatus::Completed;
+                on_progress(DownloadProgress::from_entry(entry));
+            }
+            Err(_) if options.is_cancelled() => {
+                entry.status = EntryStatus::Pending;
+                on_progress(DownloadProgress::from_entry(entry));
+                queue.save()?;
+                break;
+            }
+            Err(err) => {
+                entry.status = EntryStatus::Failed;
+                on_progress(DownloadProgress::from_entry(entry));
+                queue.save()?;
+                return Err(err.context(format!("downloading {}", queue.entries[idx].url)));
+            }
+        }
+        queue.save()?;
+    }
+
+    Ok(queue)
+}
+
+/// File names handled by [`download_wget_list`], by outcome.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DownloadReport {
+    pub downloaded: Vec<String>,
+    /// Already on disk with a matching checksum.
+    pub skipped: Vec<String>,
+}
+
+impl From<&DownloadQueue> for DownloadReport {
+    fn from(queue: &DownloadQueue) -> Self {
+        let mut report = Self::default();
+        for entry in &queue.entries {
+            match entry.status {
+                EntryStatus::Completed => report.downloaded.push(entry.filename.clone()),
+                EntryStatus::Skipped => report.skipped.push(entry.filename.clone()),
+                _ => {}
+            }
+        }
+        report
+    }
+}
+
+/// Queue every file named in a raw wget-list and download it with [`download_files`].
+///
+/// With `mirror`, files are fetched from `{mirror}/{filename}` instead of their upstream URL.
+/// `checksum_map` attaches checksums by file name, so matching files already on disk are skipped
+/// and corrupt downloads are rejected.
+pub fn download_wget_list(
+    wget_list: &str,
+    target_dir: &Path,
+    mirror: Option<String>,
+    checksum_map: Option<&HashMap<String, Checksum>>,
+) -> Result<DownloadReport> {
+    let entries = wget_list::parse_wget_list(wget_list)
+        .into_iter()
+        .map(|item| {
+            let url = match &mirror {
+                Some(base) => format!("{}/{}", base.trim_end_matches('/'), item.filename),
+                None => item.url,
+            };
+            let entry = DownloadEntry::new(url);
+            match checksum_map.and_then(|map| map.get(&entry.filename)) {
+                Some(checksum) => entry.with_checksum(checksum.clone()),
+                None => entry,
+            }
+        })
+        .collect();
+    let queue = download_files(target_dir, entries)?;
+    Ok(DownloadReport::from(&queue))
+}
+
+/// Async form of [`download_wget_list`], run on tokio's blocking thread pool so callers on a
+/// runtime (the TUI, the GraphQL server) do not stall their worker threads.
+pub async fn download_files_async(
+    wget_list: &str,
+    target_dir: &Path,
+    mirror: Option<String>,
+    checksum_map: Option<&HashMap<String, Checksum>>,
+) -> Result<DownloadReport> {
+    let wget_list = wget_list.to_string();
+    let target_dir = target_dir.to_path_buf();
+    let checksum_map = checksum_map.cloned();
+    tokio::task::spawn_blocking(move || {
+        download_wget_list(&wget_list, &target_dir, mirror, checksum_map.as_ref())
+    })
+    .await
+    .context("download task panicked")?
+}
+
+/// Run [`download_files_with_progress`] on the blocking thread pool, publishing progress to
+/// `sender`. Send errors (no subscribers) are ignored.
+#[cfg(feature = "graphql")]
+pub async fn download_files_broadcast(
+    target_dir: PathBuf,
+    entries: Vec<DownloadEntry>,
+    sender: broadcast::Sender<DownloadProgress>,
+) -> Result<DownloadQueue> {
+    tokio::task::spawn_blocking(move || {
+        download_files_with_progress(&target_dir, entries, |progress| {
+            let _ = sender.send(progress);
+        })
+    })
+    .await
+    .context("download task panicked")?
+}
+
+fn download_entry(
+    agent: &ureq::Agent,
+    target_dir: &Path,
+    entry: &mut DownloadEntry,
+    cancel: Option<&AtomicBool>,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+) -> Result<()> {
+    let path = target_dir.join(&entry.filename);
+    let existing = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    entry.status = EntryStatus::InProgress;
+    entry.downloaded_bytes = existing;
+
+    let mut request = agent.get(&entry.url);
+    if existing > 0 {
+        request = request.set("Range", &format!("bytes={existing}-"));
+    }
+
+    let response = match request.call() {
+        Ok(response) => Some(response),
+        // The server has nothing past our offset: the partial file is already complete.
+        Err(ureq::Error::Status(416, _)) if existing > 0 => None,
+        Err(ureq::Error::Status(code, _)) => return Err(anyhow!("HTTP {code}")),
+        Err(other) => return Err(anyhow!(other)),
+    };
+
+    if let Some(response) = response {
+        let resumed = response.status() == 206;
+        let remaining = response
+            .header("Content-Length")
+            .and_then(|value| value.parse::<u64>().ok());
+
+        let mut file = if resumed {
+            OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("opening {}", path.display()))?
+        } else {
+            entry.downloaded_bytes = 0;
+            File::create(&path).with_context(|| format!("creating {}", path.display()))?
+        };
+
+        entry.expected_bytes = remaining.map(|bytes| bytes + entry.downloaded_bytes);
+        on_progress(DownloadProgress::from_entry(entry));
+
+        let mut reader = response.into_reader();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                return Err(anyhow!("cancelled"));
+            }
+            let read = reader
+                .read(&mut buffer)
+                .with_context(|| format!("reading {}", entry.url))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read])
+                .with_context(|| format!("writing {}", path.display()))?;
+            entry.downloaded_bytes += read as u64;
+            on_progress(DownloadProgress::from_entry(entry));
+        }
+    }
+
+    verify_download(&path, entry)
+}
+
+/// Check the finished file against the expected size and checksum. A checksum mismatch removes
+/// the file, since resuming would only re-request past the same bad bytes.
+fn verify_download(path: &Path, entry: &mut DownloadEntry) -> Result<()> {
+    if let Some(expected) = entry
+        .expected_bytes
+        .filter(|expected| entry.downloaded_bytes < *expected)
+    {
+        return Err(anyhow!(
+            "incomplete download ({} of {} bytes)",
+            entry.downloaded_bytes,
+            expected
+        ));
+    }
+
+    let mismatch = match &entry.checksum {
+        Some(checksum) => !checksum.matches_file(path)?,
+        None => false,
+    };
+    if mismatch {
+        fs::remove_file(path).with_context(|| format!("removing {}", path.display()))?;
+        entry.downloaded_bytes = 0;
+        return Err(anyhow!("checksum mismatch for {}", entry.filename));
+    }
+
+    Ok(())
+}
+
+/// Retry a failed entry from each enabled mirror at most once. The entry keeps its original URL
+/// so the persisted queue still matches it on the next run.
+fn retry_from_mirrors(
+    agent: &ureq::Agent,
+    target_dir: &Path,
+    entry: &mut DownloadEntry,
+    mirrors: &MirrorList,
+    cancel: Option<&AtomicBool>,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+    error: anyhow::Error,
+) -> Result<()> {
+    tracing::warn!("{} failed: {error:#}", entry.url);
+    let original = entry.url.clone();
+    let mut result = Err(error);
+    for _ in 0..mirrors.enabled().count() {
+        let Some(base) = mirrors.next_url() else {
+            break;
+        };
+        entry.url = format!("{}/{}", base.trim_end_matches('/'), entry.filename);
+        tracing::info!("retrying {} from {}", entry.filename, entry.url);
+        result = download_entry(agent, target_dir, entry, cancel, on_progress);
+        if result.is_ok() || cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            break;
+        }
+    }
+    entry.url = original;
+    result
+}
+
+/// Whether `path` exists and matches the entry's checksum. Entries without one never count.
+fn is_verified(path: &Path, entry: &DownloadEntry) -> Result<bool> {
+    match &entry.checksum {
+        Some(checksum) if path.is_file() => checksum.matches_file(path),
+        _ => Ok(false),
+    }
+}
+
+fn filename_from_url(url: &str) -> String {
+    url.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(url)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirrors::Mirror;
+    use std::env;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lpkg-downloader-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn queue_round_trips_through_disk() {
+        let dir = scratch_dir("round-trip");
+        assert!(DownloadQueue::load(&dir).unwrap().is_none());
+
+        let mut entry = DownloadEntry::new("https://example.invalid/pkgs/m4-1.4.20.tar.xz")
+            .with_checksum(Checksum::Md5("0123456789abcdef0123456789abcdef".into()));
+        entry.downloaded_bytes = 42;
+        entry.status = EntryStatus::InProgress;
+        DownloadQueue::new(&dir, vec![entry]).save().unwrap();
+
+        let loaded = DownloadQueue::load(&dir).unwrap().unwrap();
+        assert_eq!(loaded.entries.len(), 1);
+        assert_eq!(loaded.entries[0].filename, "m4-1.4.20.tar.xz");
+        assert_eq!(loaded.entries[0].downloaded_bytes, 42);
+        assert_eq!(loaded.entries[0].status, EntryStatus::InProgress);
+        assert!(DownloadQueue::remove(&dir).unwrap());
+        assert!(DownloadQueue::load(&dir).unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn merge_keeps_existing_progress() {
+        let mut done = DownloadEntry::new("https://example.invalid/a.tar.xz");
+        done.status = EntryStatus::Completed;
+        let mut queue = DownloadQueue::new("unused", vec![done]);
+        queue.merge(vec![
+            DownloadEntry::new("https://example.invalid/a.tar.xz"),
+            DownloadEntry::new("https://example.invalid/b.tar.xz"),
+        ]);
+
+        assert_eq!(queue.entries.len(), 2);
+        assert_eq!(queue.entries[0].status, EntryStatus::Completed);
+        assert_eq!(queue.pending().count(), 1);
+        assert_eq!(queue.count(EntryStatus::Completed), 1);
+    }
+
+    #[test]
+    fn checksum_mismatch_removes_file() {
+        let dir = scratch_dir("mismatch");
+        let path = dir.join("bad.tar.xz");
+        fs::write(&path, b"not the archive").unwrap();
+        let mut entry = DownloadEntry::new("https://example.invalid/bad.tar.xz")
+            .with_checksum(Checksum::Md5("0123456789abcdef0123456789abcdef".into()));
+        entry.downloaded_bytes = 15;
+
+        let err = verify_download(&path, &mut entry).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+        assert!(!path.exists());
+        assert_eq!(entry.downloaded_bytes, 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Serve `body` to a single HTTP request, returning the base URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        });
+        base
+    }
+
+    #[test]
+    fn failed_download_falls_back_to_mirror() {
+        let dir = scratch_dir("mirror-fallback");
+        // Nothing listens on a port whose listener was just dropped.
+        let dead = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mirror = serve_once(b"mirrored archive");
+        let options = DownloadOptions {
+            mirrors: Some(Arc::new(MirrorList::new(vec![Mirror {
+                url: mirror,
+                priority: 1,
+                enabled: true,
+            }]))),
+            ..Default::default()
+        };
+
+        let entry = DownloadEntry::new(format!("http://{dead}/sources/m4-1.4.20.tar.xz"));
+        let queue = download_files_with_options(&dir, vec![entry], options, |_| {}).unwrap();
+
+        assert_eq!(queue.entries[0].status, EntryStatus::Completed);
+        assert_eq!(
+            queue.entries[0].url,
+            format!("http://{dead}/sources/m4-1.4.20.tar.xz")
+        );
+        assert_eq!(
+            fs::read(dir.join("m4-1.4.20.tar.xz")).unwrap(),
+            b"mirrored archive"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn wget_list_downloads_from_mirror_with_checksums() {
+        let dir = scratch_dir("wget-list");
+        let mirror = serve_once(b"mirrored archive");
+        fs::write(dir.join("m4-1.4.20.tar.xz"), b"mirrored archive").unwrap();
+        let checksums = HashMap::from([(
+            "m4-1.4.20.tar.xz".to_string(),
+            Checksum::Md5(format!("{:x}", md5::compute(b"mirrored archive"))),
+        )]);
+        let wget_list = "https://ftp.gnu.org/gnu/m4/m4-1.4.20.tar.xz\n\
+                         https://ftp.gnu.org/gnu/make/make-4.4.1.tar.gz\n";
+
+        let report = download_wget_list(wget_list, &dir, Some(mirror), Some(&checksums)).unwrap();
+
+        assert_eq!(report.skipped, vec!["m4-1.4.20.tar.xz"]);
+        assert_eq!(report.downloaded, vec!["make-4.4.1.tar.gz"]);
+        assert_eq!(
+            fs::read(dir.join("make-4.4.1.tar.gz")).unwrap(),
+            b"mirrored archive"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn async_wget_list_download_reports_files() {
+        let dir = scratch_dir("wget-list-async");
+        let mirror = serve_once(b"mirrored archive");
+        let wget_list = "https://ftp.gnu.org/gnu/m4/m4-1.4.20.tar.xz\n";
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let report = runtime
+            .block_on(download_files_async(wget_list, &dir, Some(mirror), None))
+            .unwrap();
+
+        assert_eq!(report.downloaded, vec!["m4-1.4.20.tar.xz"]);
+        assert!(report.skipped.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
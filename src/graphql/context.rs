@@ -1,14 +1,84 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, RwLockReadGuard};
 
+use anyhow::{Context, anyhow};
 use rand::rng;
 use rand::seq::IteratorRandom;
+use tokio::sync::broadcast;
+use uuid::Uuid;
 
-use crate::db;
+pub use crate::ai::Joke;
+use crate::downloader::DownloadProgress;
+use crate::ingest::BookPackage;
+use crate::{ai, db};
+
+const TOKEN_ENV: &str = "LPKG_GRAPHQL_TOKEN";
+const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+/// Per-session broadcast channels fanning downloader progress out to subscribers.
+#[derive(Clone, Default)]
+pub struct DownloadProgressHub {
+    channels: Arc<Mutex<HashMap<String, broadcast::Sender<DownloadProgress>>>>,
+}
+
+impl DownloadProgressHub {
+    /// Sender for the given session, creating the channel on first use.
+    pub fn sender(&self, session_id: &str) -> broadcast::Sender<DownloadProgress> {
+        let mut channels = self.channels.lock().unwrap_or_else(|err| err.into_inner());
+        channels
+            .entry(session_id.to_string())
+            .or_insert_with(|| broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0)
+            .clone()
+    }
+
+    pub fn subscribe(&self, session_id: &str) -> broadcast::Receiver<DownloadProgress> {
+        self.sender(session_id).subscribe()
+    }
+
+    /// Drop the session channel so subscriber streams end once the download task finishes.
+    pub fn finish(&self, session_id: &str) {
+        let mut channels = self.channels.lock().unwrap_or_else(|err| err.into_inner());
+        channels.remove(session_id);
+    }
+}
+
+/// Lazily filled package list for one book.
+type CatalogSlot = Arc<OnceLock<Vec<BookPackage>>>;
+
+/// Book catalogues fetched on first use and shared across requests.
+#[derive(Clone, Default)]
+pub struct BookCatalogCache {
+    books: Arc<Mutex<HashMap<String, CatalogSlot>>>,
+}
+
+impl BookCatalogCache {
+    /// Cached packages for `book`, running `fetch` only while the slot is still empty.
+    pub fn get_or_fetch<F>(&self, book: &str, fetch: F) -> anyhow::Result<Vec<BookPackage>>
+    where
+        F: FnOnce() -> anyhow::Result<Vec<BookPackage>>,
+    {
+        let slot = {
+            let mut books = self.books.lock().unwrap_or_else(|err| err.into_inner());
+            Arc::clone(books.entry(book.to_string()).or_default())
+        };
+        if let Some(packages) = slot.get() {
+            return Ok(packages.clone());
+        }
+        let packages = fetch()?;
+        Ok(slot.get_or_init(|| packages).clone())
+    }
+}
 
 #[derive(Clone)]
 pub struct GraphQLContext {
     pub db_pool: db::Pool,
     jokes: Arc<JokeCatalog>,
+    auth_token: Option<String>,
+    progress: DownloadProgressHub,
+    book_catalogs: BookCatalogCache,
+    request_id: Uuid,
 }
 
 impl GraphQLContext {
@@ -16,6 +86,10 @@ impl GraphQLContext {
         Self {
             db_pool,
             jokes: Arc::new(JokeCatalog::default()),
+            auth_token: None,
+            progress: DownloadProgressHub::default(),
+            book_catalogs: BookCatalogCache::default(),
+            request_id: Uuid::new_v4(),
         }
     }
 
@@ -23,6 +97,10 @@ impl GraphQLContext {
         Self {
             db_pool,
             jokes: Arc::new(JokeCatalog::new(jokes)),
+            auth_token: None,
+            progress: DownloadProgressHub::default(),
+            book_catalogs: BookCatalogCache::default(),
+            request_id: Uuid::new_v4(),
         }
     }
 
@@ -30,78 +108,143 @@ impl GraphQLContext {
         Self {
             db_pool,
             jokes: catalog,
+            auth_token: None,
+            progress: DownloadProgressHub::default(),
+            book_catalogs: BookCatalogCache::default(),
+            request_id: Uuid::new_v4(),
         }
     }
 
-    pub fn joke_catalog(&self) -> Arc<JokeCatalog> {
-        Arc::clone(&self.jokes)
+    /// Attach the bearer token supplied with the current request.
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
     }
-}
 
-impl juniper::Context for GraphQLContext {}
+    /// Share a progress hub across requests so subscriptions see downloads started elsewhere.
+    pub fn with_progress_hub(mut self, hub: DownloadProgressHub) -> Self {
+        self.progress = hub;
+        self
+    }
 
-#[derive(Clone, Debug)]
-pub struct Joke {
-    pub id: String,
-    pub package: Option<String>,
-    pub text: String,
-}
+    /// Share fetched book catalogues across requests instead of re-scraping per query.
+    pub fn with_book_catalogs(mut self, cache: BookCatalogCache) -> Self {
+        self.book_catalogs = cache;
+        self
+    }
 
-impl Joke {
-    pub fn new(id: impl Into<String>, package: Option<&str>, text: impl Into<String>) -> Self {
-        Self {
-            id: id.into(),
-            package: package.map(|pkg| pkg.to_string()),
-            text: text.into(),
+    pub fn book_catalogs(&self) -> &BookCatalogCache {
+        &self.book_catalogs
+    }
+
+    pub fn progress_hub(&self) -> &DownloadProgressHub {
+        &self.progress
+    }
+
+    /// Identifier generated for the HTTP request this context was built for.
+    pub fn request_id(&self) -> Uuid {
+        self.request_id
+    }
+
+    /// Span tagging a resolver with the current request id.
+    pub fn span(&self, resolver: &'static str) -> tracing::Span {
+        tracing::info_span!("graphql_resolver", request_id = %self.request_id, resolver)
+    }
+
+    /// Check the request's bearer token against `LPKG_GRAPHQL_TOKEN`.
+    pub fn authorize(&self) -> anyhow::Result<()> {
+        let expected = env::var(TOKEN_ENV)
+            .map_err(|_| anyhow!("mutations are disabled: {TOKEN_ENV} is not set"))?;
+        match self.auth_token.as_deref() {
+            Some(token) if token == expected => Ok(()),
+            Some(_) => Err(anyhow!("invalid bearer token")),
+            None => Err(anyhow!("missing bearer token")),
         }
     }
+
+    pub fn joke_catalog(&self) -> Arc<JokeCatalog> {
+        Arc::clone(&self.jokes)
+    }
 }
 
+impl juniper::Context for GraphQLContext {}
+
+/// Jokes read from `ai/jokes.json`; `reload` swaps in the current file contents.
 #[derive(Clone)]
 pub struct JokeCatalog {
-    entries: Arc<Vec<Joke>>,
+    base_dir: PathBuf,
+    entries: Arc<RwLock<Vec<Joke>>>,
 }
 
 impl JokeCatalog {
     fn new(entries: Vec<Joke>) -> Self {
         Self {
-            entries: Arc::new(entries),
+            base_dir: PathBuf::from("."),
+            entries: Arc::new(RwLock::new(entries)),
         }
     }
 
+    /// Load `ai/jokes.json` below `base_dir`.
+    pub fn load(base_dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let base_dir = base_dir.as_ref().to_path_buf();
+        let entries = ai::load_jokes(&base_dir)
+            .with_context(|| format!("loading jokes from {}", base_dir.display()))?;
+        Ok(Self {
+            base_dir,
+            entries: Arc::new(RwLock::new(entries)),
+        })
+    }
+
+    /// Re-read the jokes file and return how many jokes it holds. On error the current
+    /// entries are kept.
+    pub fn reload(&self) -> anyhow::Result<usize> {
+        let entries = ai::load_jokes(&self.base_dir)
+            .with_context(|| format!("loading jokes from {}", self.base_dir.display()))?;
+        let count = entries.len();
+        *self.entries.write().unwrap_or_else(|err| err.into_inner()) = entries;
+        Ok(count)
+    }
+
+    fn entries(&self) -> RwLockReadGuard<'_, Vec<Joke>> {
+        self.entries.read().unwrap_or_else(|err| err.into_inner())
+    }
+
     pub fn random(&self, package: Option<&str>) -> Option<Joke> {
         let mut rng = rng();
 
-        if let Some(package) = package {
-            if let Some(chosen) = self
-                .entries
+        let entries = self.entries();
+        if let Some(package) = package
+            && let Some(chosen) = entries
                 .iter()
                 .filter(|joke| matches_package(joke, package))
                 .choose(&mut rng)
-            {
-                return Some(chosen.clone());
-            }
+        {
+            return Some(chosen.clone());
         }
 
-        self.entries.iter().choose(&mut rng).cloned()
+        entries.iter().choose(&mut rng).cloned()
     }
 
     pub fn all(&self, package: Option<&str>) -> Vec<Joke> {
+        let entries = self.entries();
         match package {
-            Some(package) => self
-                .entries
+            Some(package) => entries
                 .iter()
                 .filter(|joke| matches_package(joke, package))
                 .cloned()
                 .collect(),
-            None => self.entries.as_ref().clone(),
+            None => entries.clone(),
         }
     }
 }
 
+/// Loads `ai/jokes.json` from the working directory, starting empty if it cannot be read.
 impl Default for JokeCatalog {
     fn default() -> Self {
-        Self::new(default_jokes())
+        Self::load(".").unwrap_or_else(|err| {
+            tracing::warn!("{err:#}; starting with an empty joke catalogue");
+            Self::new(Vec::new())
+        })
     }
 }
 
@@ -111,28 +254,3 @@ fn matches_package(joke: &Joke, package: &str) -> bool {
         .map(|pkg| pkg.eq_ignore_ascii_case(package))
         .unwrap_or(false)
 }
-
-fn default_jokes() -> Vec<Joke> {
-    vec![
-        Joke::new(
-            "optimizer-overdrive",
-            Some("gcc"),
-            "The GCC optimizer walked into a bar, reordered everyone’s drinks, and they still tasted the same—just faster.",
-        ),
-        Joke::new(
-            "linker-chuckle",
-            Some("binutils"),
-            "Our linker refuses to go on vacation; it can’t handle unresolved references to the beach.",
-        ),
-        Joke::new(
-            "glibc-giggle",
-            Some("glibc"),
-            "The C library tried stand-up comedy but segfaulted halfway through the punchline.",
-        ),
-        Joke::new(
-            "pkg-general",
-            None,
-            "LPKG packages never get lost—they always follow the dependency graph back home.",
-        ),
-    ]
-}
@@ -1,14 +1,13 @@
 pub mod context;
 pub mod schema;
 
-pub use context::{GraphQLContext, Joke};
-pub use schema::QueryRoot;
+pub use context::{BookCatalogCache, DownloadProgressHub, GraphQLContext, Joke};
+pub use schema::{MutationRoot, QueryRoot, SubscriptionRoot};
 
-use juniper::{EmptyMutation, EmptySubscription, RootNode};
+use juniper::RootNode;
 
-pub type Schema =
-    RootNode<QueryRoot, EmptyMutation<GraphQLContext>, EmptySubscription<GraphQLContext>>;
+pub type Schema = RootNode<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 pub fn create_schema() -> Schema {
-    Schema::new(QueryRoot {}, EmptyMutation::new(), EmptySubscription::new())
+    Schema::new(QueryRoot {}, MutationRoot {}, SubscriptionRoot {})
 }
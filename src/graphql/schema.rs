@@ -1,9 +1,23 @@
-use anyhow::{Error as AnyhowError, Result as AnyhowResult};
-use juniper::{FieldResult, GraphQLObject, Value, graphql_object};
+use std::path::PathBuf;
+use std::pin::Pin;
 
+use anyhow::{Error as AnyhowError, Result as AnyhowResult, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use juniper::{
+    FieldError, FieldResult, GraphQLInputObject, GraphQLObject, Value, graphql_object,
+    graphql_subscription,
+};
+use serde::Serialize;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::downloader::{self, DownloadEntry, DownloadProgress};
+use crate::ingest::{self, BookKind, BookPackage, FetchOptions};
+use crate::pkgs::mlfs;
 use crate::{db, pkgs::package::PackageDefinition};
 
-use super::context::{GraphQLContext, Joke};
+use super::context::{DownloadProgressHub, GraphQLContext, Joke};
 
 #[derive(Clone, GraphQLObject)]
 #[graphql(description = "Package metadata exposed via the GraphQL API")]
@@ -21,6 +35,9 @@ pub struct PackageType {
     pub cflags: Vec<String>,
     pub ldflags: Vec<String>,
     pub profdata: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub notes: Option<String>,
 }
 
 impl From<PackageDefinition> for PackageType {
@@ -41,6 +58,120 @@ impl From<PackageDefinition> for PackageType {
             cflags: optimizations.cflags,
             ldflags: optimizations.ldflags,
             profdata: optimizations.profdata,
+            stage: pkg.stage,
+            variant: pkg.variant,
+            notes: pkg.notes,
+        }
+    }
+}
+
+#[derive(Clone, GraphQLObject)]
+#[graphql(description = "A package together with its pagination cursor")]
+pub struct PackageEdge {
+    pub node: PackageType,
+    pub cursor: String,
+}
+
+#[derive(Clone, GraphQLObject)]
+#[graphql(description = "Relay pagination metadata")]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+}
+
+#[derive(Clone, GraphQLObject)]
+#[graphql(description = "A page of packages ordered by name and version")]
+pub struct PackageConnection {
+    pub edges: Vec<PackageEdge>,
+    pub page_info: PageInfo,
+    pub total_count: i32,
+}
+
+fn encode_cursor(name: &str, version: &str) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{name}/{version}"))
+}
+
+fn decode_cursor(cursor: &str) -> AnyhowResult<(String, String)> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| anyhow!("invalid cursor"))?;
+    let raw = String::from_utf8(bytes).map_err(|_| anyhow!("invalid cursor"))?;
+    let (name, version) = raw
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("invalid cursor"))?;
+    Ok((name.to_string(), version.to_string()))
+}
+
+#[derive(Clone, GraphQLInputObject)]
+#[graphql(description = "Package metadata accepted by the upsertPackage mutation")]
+pub struct PackageInput {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    pub md5: Option<String>,
+    pub configure_args: Vec<String>,
+    pub build_commands: Vec<String>,
+    pub install_commands: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub enable_lto: bool,
+    pub enable_pgo: bool,
+    pub cflags: Vec<String>,
+    pub ldflags: Vec<String>,
+    pub profdata: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl From<PackageInput> for PackageDefinition {
+    fn from(input: PackageInput) -> Self {
+        let mut pkg = PackageDefinition::new(input.name, input.version);
+        pkg.source = input.source;
+        pkg.md5 = input.md5;
+        pkg.configure_args = input.configure_args;
+        pkg.build_commands = input.build_commands;
+        pkg.install_commands = input.install_commands;
+        pkg.dependencies = input.dependencies;
+        pkg.optimizations.enable_lto = input.enable_lto;
+        pkg.optimizations.enable_pgo = input.enable_pgo;
+        pkg.optimizations.cflags = input.cflags;
+        pkg.optimizations.ldflags = input.ldflags;
+        pkg.optimizations.profdata = input.profdata;
+        pkg.stage = input.stage;
+        pkg.variant = input.variant;
+        pkg.notes = input.notes;
+        pkg
+    }
+}
+
+#[derive(Clone, Default, GraphQLInputObject)]
+#[graphql(description = "Filters for the search query; omitted fields do not constrain results")]
+pub struct SearchInput {
+    #[graphql(description = "Case-insensitive substring matched against the package name")]
+    pub query: Option<String>,
+    #[graphql(description = "Book stage, e.g. `cross-toolchain` or `system`")]
+    pub stage: Option<String>,
+    #[graphql(description = "Package variant, e.g. `Pass 1`")]
+    pub variant: Option<String>,
+    #[graphql(description = "Only return packages with LTO enabled (true) or disabled (false)")]
+    pub lto: Option<bool>,
+    #[graphql(description = "Only return packages with PGO enabled (true) or disabled (false)")]
+    pub pgo: Option<bool>,
+    #[graphql(description = "Maximum number of results (1-200, defaults to 50)")]
+    pub limit: Option<i32>,
+}
+
+impl From<SearchInput> for db::SearchOptions {
+    fn from(input: SearchInput) -> Self {
+        Self {
+            query: input.query,
+            stage: input.stage,
+            variant: input.variant,
+            lto: input.lto,
+            pgo: input.pgo,
+            limit: input.limit.map(|value| i64::from(value.clamp(1, 200))),
         }
     }
 }
@@ -63,21 +194,124 @@ impl From<Joke> for JokeType {
     }
 }
 
+#[derive(Clone, GraphQLObject)]
+#[graphql(description = "A package section scraped from one of the LFS books")]
+pub struct BookPackageType {
+    pub book: String,
+    pub chapter: Option<i32>,
+    pub section: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+    pub href: Option<String>,
+    pub md5: Option<String>,
+    pub primary_source_url: Option<String>,
+    pub stage: Option<String>,
+    pub variant: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl From<BookPackage> for BookPackageType {
+    fn from(pkg: BookPackage) -> Self {
+        Self {
+            book: pkg.book.to_string(),
+            chapter: pkg.chapter.and_then(|chapter| i32::try_from(chapter).ok()),
+            section: pkg.section,
+            name: pkg.name,
+            version: pkg.version,
+            href: pkg.href,
+            md5: pkg.md5,
+            primary_source_url: pkg.primary_source_url,
+            stage: pkg.stage,
+            variant: pkg.variant,
+            notes: pkg.notes,
+        }
+    }
+}
+
+fn parse_book_kind(book: &str) -> AnyhowResult<BookKind> {
+    match book.to_ascii_lowercase().as_str() {
+        "lfs" => Ok(BookKind::Lfs),
+        "mlfs" => Ok(BookKind::Mlfs),
+        "blfs" => Ok(BookKind::Blfs),
+        "glfs" => Ok(BookKind::Glfs),
+        other => Err(anyhow!(
+            "unknown book `{other}` (expected lfs, mlfs, blfs or glfs)"
+        )),
+    }
+}
+
+/// Scrape `book` from its default upstream URL; MLFS falls back to the bundled catalogue.
+fn fetch_book_packages(book: BookKind) -> AnyhowResult<Vec<BookPackage>> {
+    match book {
+        BookKind::Mlfs => Ok(mlfs::load_or_fetch_catalog(None, true)?
+            .iter()
+            .map(mlfs::MlfsPackageRecord::to_book_package)
+            .collect()),
+        BookKind::Lfs => {
+            ingest::lfs::fetch_book(&FetchOptions::new(ingest::lfs::DEFAULT_BASE_URL, book))
+        }
+        BookKind::Blfs => {
+            ingest::blfs::fetch_book(&FetchOptions::new(ingest::blfs::DEFAULT_BASE_URL, book))
+        }
+        BookKind::Glfs => {
+            ingest::glfs::fetch_book(&FetchOptions::new(ingest::glfs::DEFAULT_BASE_URL, book))
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct QueryRoot;
 
 #[graphql_object(context = GraphQLContext)]
 impl QueryRoot {
-    fn packages(context: &GraphQLContext, limit: Option<i32>) -> FieldResult<Vec<PackageType>> {
-        let limit = limit.unwrap_or(50).clamp(1, 200) as usize;
-        let definitions =
-            db::load_package_definitions_via_pool(&context.db_pool).map_err(field_error)?;
+    fn packages(
+        context: &GraphQLContext,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<PackageConnection> {
+        let _span = context.span("packages").entered();
+        let first = i64::from(first.unwrap_or(50).clamp(1, 200));
+        let after = after
+            .as_deref()
+            .map(decode_cursor)
+            .transpose()
+            .map_err(field_error)?;
+
+        let mut rows = db::load_packages_page_via_pool(
+            &context.db_pool,
+            after
+                .as_ref()
+                .map(|(name, version)| (name.as_str(), version.as_str())),
+            first + 1,
+        )
+        .map_err(field_error)?;
+        let has_next_page = rows.len() as i64 > first;
+        rows.truncate(first as usize);
 
-        Ok(definitions
+        let total_count = db::count_packages_via_pool(&context.db_pool).map_err(field_error)?;
+
+        let edges = rows
             .into_iter()
-            .take(limit)
-            .map(PackageType::from)
-            .collect())
+            .map(|pkg| {
+                let cursor = encode_cursor(&pkg.name, &pkg.version);
+                pkg.into_definition().map(|definition| PackageEdge {
+                    node: PackageType::from(definition),
+                    cursor,
+                })
+            })
+            .collect::<AnyhowResult<Vec<_>>>()
+            .map_err(field_error)?;
+
+        Ok(PackageConnection {
+            page_info: PageInfo {
+                has_next_page,
+                has_previous_page: after.is_some(),
+                start_cursor: edges.first().map(|edge| edge.cursor.clone()),
+                end_cursor: edges.last().map(|edge| edge.cursor.clone()),
+            },
+            edges,
+            total_count: i32::try_from(total_count).unwrap_or(i32::MAX),
+        })
     }
 
     fn package(
@@ -85,6 +319,7 @@ impl QueryRoot {
         name: String,
         version: Option<String>,
     ) -> FieldResult<Option<PackageType>> {
+        let _span = context.span("package").entered();
         let definition =
             db::find_package_definition_via_pool(&context.db_pool, &name, version.as_deref())
                 .map_err(field_error)?;
@@ -92,14 +327,11 @@ impl QueryRoot {
         Ok(definition.map(PackageType::from))
     }
 
-    fn search(
-        context: &GraphQLContext,
-        query: String,
-        limit: Option<i32>,
-    ) -> FieldResult<Vec<PackageType>> {
-        let limit = limit.map(|value| i64::from(value.clamp(1, 200)));
-        let results =
-            db::search_packages_via_pool(&context.db_pool, &query, limit).map_err(field_error)?;
+    fn search(context: &GraphQLContext, input: SearchInput) -> FieldResult<Vec<PackageType>> {
+        let _span = context.span("search").entered();
+        let options = db::SearchOptions::from(input);
+        let results = db::search_packages_with_options_via_pool(&context.db_pool, &options)
+            .map_err(field_error)?;
 
         let packages = results
             .into_iter()
@@ -110,7 +342,37 @@ impl QueryRoot {
         Ok(packages)
     }
 
+    /// Packages scraped from the live book. Each book is fetched once and cached for the
+    /// lifetime of the server.
+    fn book_packages(
+        context: &GraphQLContext,
+        book: String,
+        chapter: Option<i32>,
+        limit: Option<i32>,
+    ) -> FieldResult<Vec<BookPackageType>> {
+        let _span = context.span("book_packages").entered();
+        let kind = parse_book_kind(&book).map_err(field_error)?;
+        let packages = context
+            .book_catalogs()
+            .get_or_fetch(&kind.to_string(), || fetch_book_packages(kind))
+            .map_err(field_error)?;
+
+        let limit = limit.map_or(usize::MAX, |value| value.max(0) as usize);
+        Ok(packages
+            .into_iter()
+            .filter(|pkg| {
+                chapter.is_none_or(|wanted| {
+                    pkg.chapter
+                        .is_some_and(|found| i64::from(found) == i64::from(wanted))
+                })
+            })
+            .take(limit)
+            .map(BookPackageType::from)
+            .collect())
+    }
+
     fn jokes(context: &GraphQLContext, package: Option<String>) -> FieldResult<Vec<JokeType>> {
+        let _span = context.span("jokes").entered();
         let catalog = context.joke_catalog();
         Ok(catalog
             .all(package.as_deref())
@@ -123,11 +385,159 @@ impl QueryRoot {
         context: &GraphQLContext,
         package: Option<String>,
     ) -> FieldResult<Option<JokeType>> {
+        let _span = context.span("random_joke").entered();
         let catalog = context.joke_catalog();
         Ok(catalog.random(package.as_deref()).map(JokeType::from))
     }
 }
 
+#[derive(Clone, Serialize)]
+pub struct DownloadProgressEvent {
+    pub filename: String,
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>,
+    pub percent: Option<f64>,
+    pub status: String,
+}
+
+impl From<DownloadProgress> for DownloadProgressEvent {
+    fn from(progress: DownloadProgress) -> Self {
+        Self {
+            percent: progress.percent(),
+            status: progress.status.to_string(),
+            filename: progress.filename,
+            bytes_downloaded: progress.bytes_downloaded,
+            total_bytes: progress.total_bytes,
+        }
+    }
+}
+
+/// Progress update for a file in a download session. Byte counts are floats because GraphQL
+/// has no 64-bit integer scalar.
+#[graphql_object(context = GraphQLContext)]
+impl DownloadProgressEvent {
+    fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    fn bytes_downloaded(&self) -> f64 {
+        self.bytes_downloaded as f64
+    }
+
+    fn total_bytes(&self) -> Option<f64> {
+        self.total_bytes.map(|bytes| bytes as f64)
+    }
+
+    fn percent(&self) -> Option<f64> {
+        self.percent
+    }
+
+    fn status(&self) -> &str {
+        &self.status
+    }
+}
+
+/// Stream of progress events for one download session.
+pub fn progress_events(
+    hub: &DownloadProgressHub,
+    session_id: &str,
+) -> impl Stream<Item = DownloadProgressEvent> + Send + 'static {
+    BroadcastStream::new(hub.subscribe(session_id))
+        .filter_map(|message| message.ok().map(DownloadProgressEvent::from))
+}
+
+type DownloadProgressStream =
+    Pin<Box<dyn Stream<Item = Result<DownloadProgressEvent, FieldError>> + Send>>;
+
+#[derive(Default)]
+pub struct SubscriptionRoot;
+
+#[graphql_subscription(context = GraphQLContext)]
+impl SubscriptionRoot {
+    async fn download_progress(
+        context: &GraphQLContext,
+        session_id: String,
+    ) -> DownloadProgressStream {
+        let _span = context.span("download_progress").entered();
+        Box::pin(progress_events(context.progress_hub(), &session_id).map(Ok))
+    }
+}
+
+#[derive(Default)]
+pub struct MutationRoot;
+
+#[graphql_object(context = GraphQLContext)]
+impl MutationRoot {
+    fn upsert_package(context: &GraphQLContext, input: PackageInput) -> FieldResult<PackageType> {
+        let _span = context.span("upsert_package").entered();
+        context.authorize().map_err(field_error)?;
+
+        let definition = PackageDefinition::from(input);
+        db::upsert_package_via_pool(&context.db_pool, &definition).map_err(field_error)?;
+
+        let persisted = db::find_package_definition_via_pool(
+            &context.db_pool,
+            &definition.name,
+            Some(&definition.version),
+        )
+        .map_err(field_error)?
+        .unwrap_or(definition);
+
+        Ok(PackageType::from(persisted))
+    }
+
+    fn delete_package(
+        context: &GraphQLContext,
+        name: String,
+        version: Option<String>,
+    ) -> FieldResult<bool> {
+        let _span = context.span("delete_package").entered();
+        context.authorize().map_err(field_error)?;
+
+        let deleted = db::delete_package_via_pool(&context.db_pool, &name, version.as_deref())
+            .map_err(field_error)?;
+        Ok(deleted > 0)
+    }
+
+    /// Re-read `ai/jokes.json` and return the number of jokes now being served.
+    fn reload_jokes(context: &GraphQLContext) -> FieldResult<i32> {
+        let _span = context.span("reload_jokes").entered();
+        context.authorize().map_err(field_error)?;
+
+        let count = context.joke_catalog().reload().map_err(field_error)?;
+        Ok(i32::try_from(count).unwrap_or(i32::MAX))
+    }
+
+    /// Start downloading `urls` into `target_dir` in the background and return the session id
+    /// to pass to the `downloadProgress` subscription.
+    fn start_download(
+        context: &GraphQLContext,
+        urls: Vec<String>,
+        target_dir: String,
+    ) -> FieldResult<String> {
+        let _span = context.span("start_download").entered();
+        context.authorize().map_err(field_error)?;
+
+        let session_id = format!("{:016x}", rand::random::<u64>());
+        let hub = context.progress_hub().clone();
+        let sender = hub.sender(&session_id);
+        let entries = urls.into_iter().map(DownloadEntry::new).collect();
+        let task_session = session_id.clone();
+
+        tokio::spawn(async move {
+            let result =
+                downloader::download_files_broadcast(PathBuf::from(target_dir), entries, sender)
+                    .await;
+            if let Err(err) = result {
+                eprintln!("[graphql] download session {task_session} failed: {err:#}");
+            }
+            hub.finish(&task_session);
+        });
+
+        Ok(session_id)
+    }
+}
+
 fn field_error(err: AnyhowError) -> juniper::FieldError {
     juniper::FieldError::new(err.to_string(), Value::null())
 }
@@ -1,19 +1,104 @@
-use anyhow::{Context, Result};
-use scraper::{Html, Selector};
+use anyhow::{Context, Result, anyhow};
+use scraper::{ElementRef, Html, Selector};
+
+/// Fetch `url` and return the rows of the first table matching `table_css`; see
+/// [`parse_table_from_html`].
+pub fn fetch_table_data(url: &str, table_css: &str) -> Result<Vec<Vec<String>>> {
+    let body = ureq::get(url)
+        .call()
+        .with_context(|| format!("requesting {url}"))?
+        .into_string()
+        .with_context(|| format!("reading body from {url}"))?;
+    parse_table_from_html(&body, table_css).with_context(|| format!("reading table from {url}"))
+}
+
+/// Collect each `<tr>` of the first element matching `table_css` as the whitespace-normalized
+/// text of its `<th>`/`<td>` cells.
+pub fn parse_table_from_html(html: &str, table_css: &str) -> Result<Vec<Vec<String>>> {
+    let document = Html::parse_document(html);
+    let table_selector = Selector::parse(table_css)
+        .map_err(|err| anyhow!("invalid selector '{table_css}': {err}"))?;
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("th, td").unwrap();
+
+    let table = document
+        .select(&table_selector)
+        .next()
+        .ok_or_else(|| anyhow!("no element matches '{table_css}'"))?;
+
+    Ok(table
+        .select(&row_selector)
+        .map(|row| {
+            row.select(&cell_selector)
+                .map(|cell| {
+                    let text: String = cell.text().collect();
+                    text.split_whitespace().collect::<Vec<_>>().join(" ")
+                })
+                .collect()
+        })
+        .collect())
+}
 
 pub fn fetch_pre_blocks(url: &str) -> Result<Vec<String>> {
+    fetch_pre_blocks_by_class(url, None)
+}
+
+/// Fetch `url` and return the inner HTML of its `<pre>` blocks, limited to `<pre class="...">`
+/// when `css_class` is given.
+pub fn fetch_pre_blocks_by_class(url: &str, css_class: Option<&str>) -> Result<Vec<String>> {
     let body = ureq::get(url)
         .call()
         .with_context(|| format!("requesting {url}"))?
         .into_string()
         .with_context(|| format!("reading body from {url}"))?;
     let document = Html::parse_document(&body);
-    let selector = Selector::parse("pre").unwrap();
 
-    let mut results = Vec::new();
-    for element in document.select(&selector) {
-        results.push(element.inner_html());
-    }
+    Ok(select_pre_blocks(&document, css_class)?
+        .into_iter()
+        .map(|element| element.inner_html())
+        .collect())
+}
 
-    Ok(results)
+/// Select the `<pre>` elements of an already parsed document, optionally filtered by class.
+pub fn select_pre_blocks<'a>(
+    document: &'a Html,
+    css_class: Option<&str>,
+) -> Result<Vec<ElementRef<'a>>> {
+    let css = match css_class {
+        Some(class) => format!("pre.{class}"),
+        None => "pre".to_string(),
+    };
+    let selector =
+        Selector::parse(&css).map_err(|err| anyhow!("invalid selector '{css}': {err}"))?;
+    Ok(document.select(&selector).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_first_matching_table() {
+        let html = r#"
+        <html><body>
+        <table class="other"><tr><td>ignored</td></tr></table>
+        <table class="deps">
+          <tr><th>Package</th><th>Kind</th></tr>
+          <tr><td><a href="x.html">libxml2</a>-2.14</td><td>required</td></tr>
+          <tr><td>ICU</td><td>
+            recommended</td></tr>
+        </table>
+        </body></html>
+        "#;
+        let rows = parse_table_from_html(html, "table.deps").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Package", "Kind"],
+                vec!["libxml2-2.14", "required"],
+                vec!["ICU", "recommended"],
+            ]
+        );
+        assert!(parse_table_from_html(html, "table.missing").is_err());
+    }
 }
@@ -2,9 +2,13 @@ use anyhow::{Context, Result};
 use regex::Regex;
 use scraper::{Html, Selector};
 
-use super::{BookPackage, FetchOptions};
+use super::{
+    BookPackage, FetchOptions, extract_dependencies_after, extract_source_url_near_heading,
+};
 use crate::ingest::lfs::split_name_version;
 
+pub const DEFAULT_BASE_URL: &str = "https://www.linuxfromscratch.org/blfs/view/systemd";
+
 pub fn fetch_book(options: &FetchOptions) -> Result<Vec<BookPackage>> {
     let base = options.base_url.trim_end_matches('/');
     let url = format!("{base}/book.html");
@@ -77,9 +81,11 @@ pub fn parse_book_html(
             version: Some(version),
             href,
             md5: None,
+            primary_source_url: extract_source_url_near_heading(&heading),
             stage: None,
             variant,
             notes: None,
+            dependencies: extract_dependencies_after(&heading),
         });
     }
 
@@ -89,14 +95,14 @@ pub fn parse_book_html(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ingest::BookKind;
+    use crate::ingest::{BookKind, DepKind};
 
     #[test]
     fn parse_blfs_sample() {
         let html = r#"
         <html><body>
-        <h1 class=\"sect1\" id=\"ch33-bzip2\">33.2. Bzip2-1.0.8</h1>
-        <h1 class=\"sect1\" id=\"ch33-about\">33.1. Introduction</h1>
+        <h1 class="sect1" id="ch33-bzip2">33.2. Bzip2-1.0.8</h1>
+        <h1 class="sect1" id="ch33-about">33.1. Introduction</h1>
         </body></html>
         "#;
         let opts = FetchOptions::new("https://example.invalid/blfs", BookKind::Blfs);
@@ -105,4 +111,38 @@ mod tests {
         assert_eq!(items[0].name, "Bzip2");
         assert_eq!(items[0].version.as_deref(), Some("1.0.8"));
     }
+
+    #[test]
+    fn dependencies_from_labelled_paragraphs() {
+        let html = r##"
+        <html><body><div class="sect1">
+        <h1 class="sect1" id="gtk3">25.4. GTK-3.24.43</h1>
+        <div class="package">
+          <h4>Required</h4>
+          <p class="required"><a href="#at-spi2">at-spi2-core-2.52.0</a> and
+            <a href="#pango">Pango-1.54.0</a></p>
+          <p>Optional: <a href="#colord">colord-1.4.7</a></p>
+        </div>
+        <div class="installation">
+          <h2 class="sect2">Installation of GTK</h2>
+          <p>See <a href="#notes">the notes</a>.</p>
+        </div>
+        </div></body></html>
+        "##;
+        let opts = FetchOptions::new("https://example.invalid/blfs", BookKind::Blfs);
+        let items = parse_book_html(&opts, "https://example.invalid/blfs/book.html", html).unwrap();
+        let deps: Vec<_> = items[0]
+            .dependencies
+            .iter()
+            .map(|dep| (dep.name.as_str(), dep.kind))
+            .collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("at-spi2-core", DepKind::Required),
+                ("Pango", DepKind::Required),
+                ("colord", DepKind::Optional),
+            ]
+        );
+    }
 }
@@ -2,9 +2,13 @@ use anyhow::{Context, Result};
 use regex::Regex;
 use scraper::{Html, Selector};
 
-use super::{BookPackage, FetchOptions};
+use super::{
+    BookPackage, FetchOptions, extract_dependencies_after, extract_source_url_near_heading,
+};
 use crate::ingest::lfs::split_name_version;
 
+pub const DEFAULT_BASE_URL: &str = "https://www.linuxfromscratch.org/glfs/view/glfs";
+
 pub fn fetch_book(options: &FetchOptions) -> Result<Vec<BookPackage>> {
     let base = options.base_url.trim_end_matches('/');
     let url = format!("{base}/book.html");
@@ -74,9 +78,11 @@ pub fn parse_book_html(
             version: Some(version),
             href,
             md5: None,
+            primary_source_url: extract_source_url_near_heading(&heading),
             stage: None,
             variant,
             notes: None,
+            dependencies: extract_dependencies_after(&heading),
         });
     }
 
@@ -92,7 +98,7 @@ mod tests {
     fn parse_glfs_sample() {
         let html = r#"
         <html><body>
-        <h1 class=\"sect1\" id=\"ch12-coreutils\">12.4. Coreutils-9.8</h1>
+        <h1 class="sect1" id="ch12-coreutils">12.4. Coreutils-9.8</h1>
         </body></html>
         "#;
         let opts = FetchOptions::new("https://example.invalid/glfs", BookKind::Glfs);
@@ -1,14 +1,42 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+
+use super::{BookPackage, FetchOptions, extract_source_url_near_heading};
+
+pub const DEFAULT_BASE_URL: &str = "https://www.linuxfromscratch.org/lfs/view/12.1";
 
-use super::{BookPackage, FetchOptions};
+/// How long `fetch_book` waits for the book server to accept the connection.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// How long `fetch_book` waits for each read of the response, so a server that accepts the
+/// connection but stalls mid-body cannot hang the fetch.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Download and parse `{base_url}/book.html`, bounded by [`CONNECT_TIMEOUT`] and
+/// [`READ_TIMEOUT`]. A `file://` base URL reads the page from disk instead, which keeps tests
+/// offline.
 pub fn fetch_book(options: &FetchOptions) -> Result<Vec<BookPackage>> {
     let base = options.base_url.trim_end_matches('/');
     let url = format!("{base}/book.html");
 
-    let body = ureq::get(&url)
+    if let Some(dir) = base.strip_prefix("file://") {
+        let path = Path::new(dir).join("book.html");
+        let body =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        return parse_book_html(options, &url, &body);
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build();
+    let body = agent
+        .get(&url)
         .call()
         .with_context(|| format!("fetching {url}"))?
         .into_string()
@@ -27,6 +55,8 @@ pub fn parse_book_html(
 
     let numbering_re =
         Regex::new(r"^(?P<chapter>\d+)\.(?P<section>\d+)\.\s+(?P<title>.+)$").unwrap();
+    let md5_selector = Selector::parse("tt, code").unwrap();
+    let md5_re = Regex::new(r"^[0-9a-f]{32}$").unwrap();
 
     let mut results = Vec::new();
 
@@ -60,6 +90,8 @@ pub fn parse_book_html(
         let stage = stage_for_chapter(chapter_num).map(|s| s.to_string());
         let identifier = format!("{chapter_num}.{section_num:02}");
 
+        let md5 = find_md5_after(&heading, &md5_selector, &md5_re);
+
         let href = heading.value().id().map(|id| {
             let mut base = book_url.to_string();
             if !base.contains('#') {
@@ -75,45 +107,63 @@ pub fn parse_book_html(
             name,
             version: Some(version),
             href,
-            md5: None,
+            md5,
+            primary_source_url: extract_source_url_near_heading(&heading),
             stage,
             variant,
             notes: None,
+            dependencies: Vec::new(),
         });
     }
 
     Ok(results)
 }
 
+/// First `<tt>`/`<code>` holding an MD5 sum in the siblings following `heading`, stopping at the
+/// next heading.
+fn find_md5_after(heading: &ElementRef, selector: &Selector, md5_re: &Regex) -> Option<String> {
+    heading
+        .next_siblings()
+        .filter_map(ElementRef::wrap)
+        .take_while(|sibling| !matches!(sibling.value().name(), "h1" | "h2"))
+        .flat_map(|sibling| {
+            // The sibling itself may be the <tt>/<code>, otherwise look inside it.
+            let own = selector.matches(&sibling).then_some(sibling).into_iter();
+            own.chain(sibling.select(selector))
+        })
+        .map(|element| element.text().collect::<String>().trim().to_lowercase())
+        .find(|text| md5_re.is_match(text))
+}
+
+/// Index of the last `-` followed by a digit, which separates a package name from its version.
+pub(crate) fn version_separator(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len())
+        .rev()
+        .find(|&idx| bytes[idx] == b'-' && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit))
+}
+
 pub(crate) fn split_name_version(title: &str) -> Option<(String, String, Option<String>)> {
-    // Find the last '-' whose next character is a digit (start of version)
-    let bytes = title.as_bytes();
-    for idx in (0..bytes.len()).rev() {
-        if bytes[idx] == b'-' {
-            if let Some(next) = bytes.get(idx + 1) {
-                if next.is_ascii_digit() {
-                    let name = title[..idx].trim();
-                    let mut remainder = title[idx + 1..].trim();
-                    if name.is_empty() || remainder.is_empty() {
-                        return None;
-                    }
-
-                    let mut variant = None;
-                    if let Some(pos) = remainder.find(" - ") {
-                        variant = Some(remainder[pos + 3..].trim().to_string());
-                        remainder = remainder[..pos].trim();
-                    } else if let Some(pos) = remainder.find(" (") {
-                        let note = remainder[pos + 1..].trim_end_matches(')').trim();
-                        variant = Some(note.to_string());
-                        remainder = remainder[..pos].trim();
-                    }
-
-                    return Some((name.to_string(), remainder.to_string(), variant));
-                }
-            }
-        }
+    // Perl module names such as `XML::Parser` keep their `::` here;
+    // `MlfsPackageRecord::module_alias` sanitizes it.
+    let idx = version_separator(title)?;
+    let name = title[..idx].trim();
+    let mut remainder = title[idx + 1..].trim();
+    if name.is_empty() || remainder.is_empty() {
+        return None;
     }
-    None
+
+    let mut variant = None;
+    if let Some(pos) = remainder.find(" - ") {
+        variant = Some(remainder[pos + 3..].trim().to_string());
+        remainder = remainder[..pos].trim();
+    } else if let Some(pos) = remainder.find(" (") {
+        let note = remainder[pos + 1..].trim_end_matches(')').trim();
+        variant = Some(note.to_string());
+        remainder = remainder[..pos].trim();
+    }
+
+    Some((name.to_string(), remainder.to_string(), variant))
 }
 
 fn stage_for_chapter(chapter: u32) -> Option<&'static str> {
@@ -137,10 +187,10 @@ mod tests {
     fn parse_sample_headings() {
         let html = r#"
         <html><body>
-        <h1 class=\"sect1\" id=\"ch05-binutils-pass1\">5.5. Binutils-2.45 - Pass 1</h1>
-        <h1 class=\"sect1\" id=\"ch05-gcc-pass1\">5.6. GCC-15.2.0 - Pass 1</h1>
-        <h1 class=\"sect1\" id=\"ch09-bootscripts\">9.3. LFS-Bootscripts-20250827</h1>
-        <h1 class=\"sect1\" id=\"ch08-xml-parser\">8.41. XML::Parser-2.47</h1>
+        <h1 class="sect1" id="ch05-binutils-pass1">5.5. Binutils-2.45 - Pass 1</h1>
+        <h1 class="sect1" id="ch05-gcc-pass1">5.6. GCC-15.2.0 - Pass 1</h1>
+        <h1 class="sect1" id="ch09-bootscripts">9.3. LFS-Bootscripts-20250827</h1>
+        <h1 class="sect1" id="ch08-xml-parser">8.41. XML::Parser-2.47</h1>
         </body></html>
         "#;
         let opts = FetchOptions::new("https://example.invalid/lfs", BookKind::Mlfs);
@@ -161,4 +211,52 @@ mod tests {
         assert_eq!(packages[2].variant, None);
         assert_eq!(packages[3].name, "XML::Parser");
     }
+
+    #[test]
+    fn md5_and_source_from_following_paragraphs() {
+        let html = r#"
+        <html><body>
+        <h1 class="sect1" id="ch05-binutils-pass1">5.5. Binutils-2.45 - Pass 1</h1>
+        <p>MD5 sum: <tt>DEE5B4267E0305A99A3C9D6131F45759</tt></p>
+        <p>Download: <a href="https://sourceware.org/pub/binutils/releases/binutils-2.45.tar.xz">binutils</a></p>
+        <h1 class="sect1" id="ch05-gcc-pass1">5.6. GCC-15.2.0 - Pass 1</h1>
+        <p>Download: <code>gcc-15.2.0.tar.xz</code></p>
+        </body></html>
+        "#;
+        let opts = FetchOptions::new("https://example.invalid/lfs", BookKind::Mlfs);
+        let packages =
+            parse_book_html(&opts, "https://example.invalid/lfs/book.html", html).unwrap();
+        assert_eq!(
+            packages[0].md5.as_deref(),
+            Some("dee5b4267e0305a99a3c9d6131f45759")
+        );
+        assert_eq!(packages[1].md5, None);
+        assert_eq!(
+            packages[0].primary_source_url.as_deref(),
+            Some("https://sourceware.org/pub/binutils/releases/binutils-2.45.tar.xz")
+        );
+        assert_eq!(packages[1].primary_source_url, None);
+    }
+
+    #[test]
+    fn split_perl_module_name() {
+        let (name, version, variant) = split_name_version("XML::Parser-2.47").unwrap();
+        assert_eq!(name, "XML::Parser");
+        assert_eq!(version, "2.47");
+        assert_eq!(variant, None);
+
+        let record = crate::pkgs::mlfs::MlfsPackageRecord {
+            name,
+            version,
+            chapter: Some(8),
+            section: Some("8.41".into()),
+            stage: None,
+            variant,
+            notes: None,
+            md5: None,
+            source: None,
+            dependencies: Vec::new(),
+        };
+        assert_eq!(record.module_alias(), "xml__parser");
+    }
 }
@@ -3,6 +3,11 @@ pub mod glfs;
 pub mod lfs;
 
 use std::fmt;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BookKind {
@@ -24,6 +29,32 @@ impl fmt::Display for BookKind {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DepKind {
+    Required,
+    Recommended,
+    Optional,
+}
+
+impl DepKind {
+    /// Parse a section label such as "Required" or "Optional:".
+    fn from_label(label: &str) -> Option<Self> {
+        match label.trim().trim_end_matches(':').to_lowercase().as_str() {
+            "required" => Some(DepKind::Required),
+            "recommended" => Some(DepKind::Recommended),
+            "optional" => Some(DepKind::Optional),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookDependency {
+    pub name: String,
+    pub kind: DepKind,
+}
+
 #[derive(Debug, Clone)]
 pub struct BookPackage {
     pub book: BookKind,
@@ -33,9 +64,13 @@ pub struct BookPackage {
     pub version: Option<String>,
     pub href: Option<String>,
     pub md5: Option<String>,
+    /// Upstream tarball linked right below the heading.
+    pub primary_source_url: Option<String>,
     pub stage: Option<String>,
     pub variant: Option<String>,
     pub notes: Option<String>,
+    /// Packages listed under "Required", "Recommended" and "Optional" (BLFS/GLFS pages).
+    pub dependencies: Vec<BookDependency>,
 }
 
 impl BookPackage {
@@ -65,3 +100,91 @@ impl<'a> FetchOptions<'a> {
         Self { base_url, book }
     }
 }
+
+const SOURCE_SUFFIXES: &[&str] = &[".tar.xz", ".tar.bz2", ".tar.gz"];
+
+/// Tarball link (`.tar.xz`, `.tar.bz2` or `.tar.gz`) in the three elements following `heading`.
+pub fn extract_source_url_near_heading(heading: &ElementRef) -> Option<String> {
+    let link_selector = Selector::parse("a[href]").unwrap();
+    heading
+        .next_siblings()
+        .filter_map(ElementRef::wrap)
+        .take(3)
+        .flat_map(|sibling| {
+            let own = link_selector
+                .matches(&sibling)
+                .then_some(sibling)
+                .into_iter();
+            own.chain(sibling.select(&link_selector))
+        })
+        .filter_map(|link| link.value().attr("href"))
+        .find(|href| SOURCE_SUFFIXES.iter().any(|suffix| href.ends_with(suffix)))
+        .map(str::to_string)
+}
+
+/// Linked package names in the "Required" / "Recommended" / "Optional" paragraphs between
+/// `heading` and the next `h1`/`h2`. A paragraph's kind comes from its `required`-style class,
+/// a leading "Required:" label, or the closest preceding label heading.
+pub fn extract_dependencies_after(heading: &ElementRef) -> Vec<BookDependency> {
+    let link_selector = Selector::parse("a").unwrap();
+    let mut dependencies: Vec<BookDependency> = Vec::new();
+    let mut current = None;
+
+    let elements = heading
+        .next_siblings()
+        .filter_map(ElementRef::wrap)
+        .take_while(|sibling| !matches!(sibling.value().name(), "h1" | "h2"))
+        .flat_map(|sibling| sibling.descendants().filter_map(ElementRef::wrap));
+
+    for element in elements {
+        let text = element.text().collect::<String>();
+        let tag = element.value().name();
+        if matches!(tag, "h2" | "h3" | "h4" | "h5" | "h6") {
+            // any other heading ends the dependency list
+            current = DepKind::from_label(&text);
+            continue;
+        }
+
+        let class_kind = element.value().classes().find_map(DepKind::from_label);
+        let kind = match tag {
+            "p" => class_kind
+                .or_else(|| {
+                    let (label, _) = text.trim_start().split_once(':')?;
+                    DepKind::from_label(label)
+                })
+                .or(current),
+            "div" => class_kind,
+            _ => None,
+        };
+        let Some(kind) = kind else {
+            continue;
+        };
+
+        for link in element.select(&link_selector) {
+            let label = link.text().collect::<String>();
+            let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+            if label.is_empty() {
+                continue;
+            }
+            let name = lfs::split_name_version(&label)
+                .map(|(name, _, _)| name)
+                .unwrap_or(label);
+            if !dependencies.iter().any(|dep| dep.name == name) {
+                dependencies.push(BookDependency { name, kind });
+            }
+        }
+    }
+
+    dependencies
+}
+
+static NUMERIC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([0-9]+(?:\.[0-9]+)?)").unwrap());
+
+/// First number in a book value such as "1.2 SBU" or "628 MB".
+pub fn parse_numeric(input: &str) -> Option<f64> {
+    NUMERIC_RE
+        .captures(input)
+        .and_then(|caps| caps.get(1))
+        .and_then(|m| m.as_str().parse().ok())
+}
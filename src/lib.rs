@@ -1,5 +1,7 @@
 pub mod ai;
+pub mod config;
 pub mod db;
+pub mod downloader;
 #[cfg(feature = "graphql")]
 pub mod graphql;
 pub mod html;
@@ -1,29 +1,68 @@
-use std::{collections::BTreeSet, env, fs, path::PathBuf};
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result, anyhow};
 use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
 
 use package_management::{
-    db, html, md5_utils,
+    ai,
+    config::{self, LpkgConfig},
+    db, downloader, html, md5_utils,
     pkgs::{
         by_name::bi::binutils::cross_toolchain::build_binutils_from_page,
         generator, mlfs,
+        package::{self, OptimizationPreset},
         scaffolder::{self, ScaffoldRequest},
     },
-    version_check, wget_list,
+    version_check,
+    wget_list::{self, ManifestKind},
 };
 
 #[cfg(feature = "tui")]
 use package_management::tui::disk_manager::DiskManager;
+#[cfg(feature = "tui")]
+use package_management::tui::keybindings::ActionDebounce;
+#[cfg(feature = "tui")]
+use package_management::tui::settings::Settings;
+
+const DEFAULT_BASE_DIR: &str = "src/pkgs/by_name";
 
 #[derive(Parser)]
 #[command(name = "lpkg", version, about = "LPKG – Lightweight Package Manager", long_about = None)]
 struct Cli {
+    /// Log output format written to stderr.
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+    /// Maximum log level (trace, debug, info, warn, error); defaults to the config, else warn.
+    #[arg(long, global = true)]
+    log_level: Option<tracing::Level>,
+    /// Database connection pool size (overrides LPKG_POOL_SIZE). SQLite allows one writer at a
+    /// time, so values above 1 only help read-heavy workloads.
+    #[arg(long, global = true)]
+    db_pool_size: Option<u32>,
     /// Command to run. Defaults to launching the TUI (when available).
     #[command(subcommand)]
     command: Option<Command>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    /// Human-readable lines.
+    Pretty,
+    /// One JSON object per line, for log aggregators.
+    Json,
+}
+
+// Same reasoning as `WorkflowCommand`: a one-off parse, never moved around.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Command {
     /// Run one of the automated workflows.
@@ -37,18 +76,33 @@ enum Command {
     Tui(TuiCommand),
 }
 
+// Parsed once per run, so the size of `ScaffoldPackage` does not matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum WorkflowCommand {
     /// Fetch <pre> blocks from the given URL and run version checks found inside them.
     EnvCheck {
         /// URL of the Linux From Scratch page containing ver_check/ver_kernel snippets.
-        url: String,
+        /// URL defaults to value from config file if not specified.
+        url: Option<String>,
+        /// Suggest package manager commands for tools that could not be found.
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+        /// Run the suggested install commands (implies --fix).
+        #[arg(long, default_value_t = false)]
+        auto_fix: bool,
     },
     /// Download the LFS wget-list and md5sums, optionally writing them to disk.
     FetchManifests {
         /// Output directory to store wget-list and md5sums files. Uses current dir if omitted.
         #[arg(long)]
         output: Option<PathBuf>,
+        /// Fail instead of warning when the wget-list contains duplicate filenames.
+        #[arg(long, default_value_t = false)]
+        strict: bool,
+        /// Books to fetch manifests for (comma-separated: lfs, mlfs, blfs, glfs).
+        #[arg(long, value_delimiter = ',', default_value = "lfs")]
+        books: Vec<String>,
     },
     /// Parse the Binutils Pass 1 page and build it using the extracted steps.
     BuildBinutils {
@@ -60,51 +114,92 @@ enum WorkflowCommand {
         /// Optional explicit cross-compilation target (defaults to $LFS_TGT env or x86_64-lfs-linux-gnu).
         #[arg(long)]
         target: Option<String>,
+        /// Wrap the compiler in ccache (default: when ccache is on PATH). Set CCACHE_DIR for the cache.
+        #[arg(long = "use-ccache", conflicts_with = "no_ccache")]
+        use_ccache: bool,
+        /// Never use ccache, even if it is installed.
+        #[arg(long = "no-ccache")]
+        no_ccache: bool,
     },
     /// Scaffold a new package module under `src/pkgs/by_name` with tuned optimizations.
     ScaffoldPackage {
         /// Logical package name (used for module layout and metadata).
-        #[arg(long)]
-        name: String,
+        #[arg(
+            long,
+            required_unless_present = "from_toml",
+            conflicts_with = "from_toml"
+        )]
+        name: Option<String>,
         /// Upstream version string.
-        #[arg(long)]
-        version: String,
+        #[arg(
+            long,
+            required_unless_present = "from_toml",
+            conflicts_with = "from_toml"
+        )]
+        version: Option<String>,
         /// Optional source archive URL.
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from_toml")]
         source: Option<String>,
         /// Optional MD5 checksum of the source archive.
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from_toml")]
         md5: Option<String>,
         /// Additional configure arguments (repeat flag).
-        #[arg(long = "configure-arg", value_name = "ARG")]
+        #[arg(
+            long = "configure-arg",
+            value_name = "ARG",
+            conflicts_with = "from_toml"
+        )]
         configure_arg: Vec<String>,
         /// Build commands (repeat flag).
-        #[arg(long = "build-cmd", value_name = "CMD")]
+        #[arg(long = "build-cmd", value_name = "CMD", conflicts_with = "from_toml")]
         build_cmd: Vec<String>,
         /// Install commands (repeat flag).
-        #[arg(long = "install-cmd", value_name = "CMD")]
+        #[arg(long = "install-cmd", value_name = "CMD", conflicts_with = "from_toml")]
         install_cmd: Vec<String>,
         /// Declared dependencies (repeat flag).
-        #[arg(long = "dependency", value_name = "PKG")]
+        #[arg(long = "dependency", value_name = "PKG", conflicts_with = "from_toml")]
         dependency: Vec<String>,
         /// Whether to enable LTO (defaults to true).
-        #[arg(long = "enable-lto", default_value_t = true)]
+        #[arg(
+            long = "enable-lto",
+            default_value_t = true,
+            conflicts_with = "from_toml"
+        )]
         enable_lto: bool,
         /// Whether to enable PGO instrumentation/use (defaults to true).
-        #[arg(long = "enable-pgo", default_value_t = true)]
+        #[arg(
+            long = "enable-pgo",
+            default_value_t = true,
+            conflicts_with = "from_toml"
+        )]
         enable_pgo: bool,
         /// Additional CFLAGS (repeat flag).
-        #[arg(long = "cflag", value_name = "FLAG")]
+        #[arg(long = "cflag", value_name = "FLAG", conflicts_with = "from_toml")]
         cflag: Vec<String>,
         /// Additional LDFLAGS (repeat flag).
-        #[arg(long = "ldflag", value_name = "FLAG")]
+        #[arg(long = "ldflag", value_name = "FLAG", conflicts_with = "from_toml")]
         ldflag: Vec<String>,
         /// Optional profile data file name for PGO replay (enables -fprofile-use).
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from_toml")]
         profdata: Option<String>,
-        /// Base directory for module generation (defaults to src/pkgs/by_name).
-        #[arg(long, default_value = "src/pkgs/by_name")]
-        base: PathBuf,
+        /// Optimization preset applied when no explicit --cflag/--ldflag flags are given.
+        #[arg(long = "optimization-preset", value_enum, conflicts_with = "from_toml")]
+        optimization_preset: Option<OptimizationPreset>,
+        /// Fail instead of warning when compiler flags conflict.
+        #[arg(long = "strict-flags", default_value_t = false)]
+        strict_flags: bool,
+        /// GNU target triple used to pick -march/-mtune flags (e.g. aarch64-unknown-linux-gnu).
+        #[arg(long = "target-triple")]
+        target_triple: Option<String>,
+        /// Write a CMake toolchain file alongside the generated module.
+        #[arg(long = "cmake-toolchain", default_value_t = false)]
+        cmake_toolchain: bool,
+        /// Base directory for module generation (config `default_base_dir`, else src/pkgs/by_name).
+        #[arg(long)]
+        base: Option<PathBuf>,
+        /// Load the package definition from a TOML file instead of individual flags.
+        #[arg(long = "from-toml", value_name = "FILE")]
+        from_toml: Option<PathBuf>,
     },
     /// Import all packages from the MLFS catalogue, scaffolding modules and persisting metadata.
     ImportMlfs {
@@ -114,15 +209,131 @@ enum WorkflowCommand {
         /// Only process the first N records (after deduplication).
         #[arg(long)]
         limit: Option<usize>,
-        /// Base directory for module generation (defaults to src/pkgs/by_name).
-        #[arg(long, default_value = "src/pkgs/by_name")]
-        base: PathBuf,
+        /// Base directory for module generation (config `default_base_dir`, else src/pkgs/by_name).
+        #[arg(long)]
+        base: Option<PathBuf>,
         /// Overwrite existing modules by deleting and regenerating them.
         #[arg(long, default_value_t = false)]
         overwrite: bool,
+        /// Number of packages to scaffold concurrently.
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
         /// Source URL for the MLFS book (defaults to the canonical mirror).
         #[arg(long = "source-url")]
         source_url: Option<String>,
+        /// Fail on network errors instead of falling back to the bundled MLFS catalogue.
+        #[arg(long = "no-fallback", default_value_t = false)]
+        no_fallback: bool,
+    },
+    /// Fetch the wget-list and print its URLs grouped by package.
+    ShowWgetGroups,
+    /// Fetch the wget-list and md5sums and write them as an aria2c input file.
+    GenerateAria2Input {
+        /// Output path for the aria2 input file. Prints to stdout if omitted.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Download every archive in a book's wget-list, checking each against its md5sums.
+    Download {
+        /// Directory receiving the archives (and the resumable `.lpkg-queue.json`).
+        target_dir: PathBuf,
+        /// Book whose manifests to use (lfs, mlfs, blfs, glfs).
+        #[arg(long, default_value = "lfs")]
+        book: String,
+        /// Re-download files even if they already exist with a matching MD5.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Verify downloaded archives in a directory against an md5sums manifest.
+    VerifyDownloads {
+        /// Directory containing the downloaded archives.
+        dir: PathBuf,
+        /// Local md5sums file. The LFS md5sums are fetched when omitted.
+        #[arg(long)]
+        md5sums: Option<PathBuf>,
+    },
+    /// List stored packages without a source URL or MD5; fails when any are found.
+    AuditPackages,
+    /// Generate an md5sums manifest for the archives in a directory.
+    GenerateMd5sums {
+        /// Directory containing the downloaded archives.
+        dir: PathBuf,
+        /// Output file (defaults to `md5sums` inside the directory).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a stored package definition as TOML.
+    DumpPackage {
+        /// Package name to look up in the database.
+        name: String,
+        /// Specific version to dump (defaults to the newest entry).
+        #[arg(long)]
+        version: Option<String>,
+    },
+    /// Show which fields changed between two stored versions of a package.
+    DiffPackage {
+        /// Package name to look up in the database.
+        name: String,
+        /// Version to diff from.
+        old_version: String,
+        /// Version to diff to.
+        new_version: String,
+    },
+    /// Export a stored package definition as a standalone bash build script.
+    ExportScript {
+        /// Package name to look up in the database.
+        name: String,
+        /// Specific version to export (defaults to the newest entry).
+        #[arg(long)]
+        version: Option<String>,
+        /// Output path for the script. Prints to stdout if omitted.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Remove the persisted download queue so the next download starts from scratch.
+    CleanQueue {
+        /// Directory containing the `.lpkg-queue.json` file.
+        target_dir: PathBuf,
+    },
+    /// Rank AI personas by how well their strengths fit a task from ai/tasks.json.
+    SuggestOwner {
+        /// Task id as listed in ai/tasks.json.
+        task_id: String,
+    },
+    /// Mark a bug in ai/bugs.json as closed.
+    CloseBug {
+        /// Bug id as listed in ai/bugs.json.
+        id: String,
+        /// Short note on how the bug was resolved.
+        #[arg(long)]
+        resolution: Option<String>,
+    },
+    /// Write a Markdown weekly report of the AI task board and bug ledger.
+    WeeklyReport {
+        /// Output path for the report. Prints to stdout if omitted.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Regenerate by_name/mod.rs and the package registry from the modules on disk.
+    RebuildIndex {
+        /// Base directory holding the modules (config `default_base_dir`, else src/pkgs/by_name).
+        #[arg(long)]
+        base: Option<PathBuf>,
+        /// Registry file to write (defaults to registry.rs next to the base directory).
+        #[arg(long)]
+        registry: Option<PathBuf>,
+    },
+    /// Print the fully resolved lpkg configuration (config file plus environment) as TOML.
+    PrintConfig,
+    /// Print a shell completion script for lpkg.
+    #[command(after_help = "Example: lpkg workflow generate-completion --shell bash >> ~/.bashrc")]
+    GenerateCompletion {
+        /// Shell to generate completions for (bash, zsh, fish, elvish, powershell).
+        #[arg(long, value_enum)]
+        shell: Shell,
+        /// Output path for the script. Prints to stdout if omitted.
+        #[arg(long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -135,6 +346,12 @@ enum TuiCommand {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let mut config = LpkgConfig::load().context("Loading lpkg config")?;
+    if let Some(size) = cli.db_pool_size {
+        config.db_pool_size = Some(size);
+    }
+    config::init(config);
+    init_logging(cli.log_format, cli.log_level);
 
     match cli.command {
         Some(Command::Workflow { workflow }) => run_workflow(workflow)?,
@@ -146,7 +363,8 @@ fn main() -> Result<()> {
                 println!(
                     "No command specified. Launching disk manager TUI. Use `lpkg help` for more options."
                 );
-                DiskManager::run_tui().map_err(|e| anyhow!(e.to_string()))?;
+                DiskManager::run_tui(&Settings::load().keybindings, &ActionDebounce::default())
+                    .map_err(|e| anyhow!(e.to_string()))?;
             }
 
             #[cfg(not(feature = "tui"))]
@@ -160,14 +378,118 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Install the global tracing subscriber; `--log-level` wins over the configured `log_level`.
+fn init_logging(format: LogFormat, level: Option<tracing::Level>) {
+    let level = level.unwrap_or_else(|| {
+        config::get()
+            .log_level
+            .as_deref()
+            .and_then(|value| match value.parse() {
+                Ok(level) => Some(level),
+                Err(_) => {
+                    eprintln!("warning: ignoring invalid log_level '{value}' in lpkg config");
+                    None
+                }
+            })
+            .unwrap_or(tracing::Level::WARN)
+    });
+
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(io::stderr);
+    match format {
+        LogFormat::Pretty => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+/// CLI `--base` wins over the configured `default_base_dir`, which wins over the built-in default.
+fn resolve_base_dir(base: Option<PathBuf>) -> PathBuf {
+    base.or_else(|| config::get().default_base_dir.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_BASE_DIR))
+}
+
+/// Fetch the wget-list and md5sums of `book` into `{target_dir}/{book}-wget-list` and
+/// `{target_dir}/{book}-md5sums`.
+fn fetch_book_manifests(book: &str, target_dir: &Path, strict: bool) -> Result<()> {
+    let wget_list =
+        wget_list::fetch_manifest(book, ManifestKind::WgetList).context("Fetching wget-list")?;
+    let md5sums =
+        wget_list::fetch_manifest(book, ManifestKind::Md5Sums).context("Fetching md5sums")?;
+
+    println!("Fetched {book} wget-list ({} bytes)", wget_list.len());
+    println!("Fetched {book} md5sums ({} bytes)", md5sums.len());
+
+    let duplicates = wget_list::find_duplicates(&wget_list::parse_wget_list(&wget_list));
+    if !duplicates.is_empty() {
+        for (filename, urls) in &duplicates {
+            eprintln!("warning: {filename} is listed {} times:", urls.len());
+            for url in urls {
+                eprintln!("  {url}");
+            }
+        }
+        if strict {
+            return Err(anyhow!(
+                "wget-list contains {} duplicated file(s)",
+                duplicates.len()
+            ));
+        }
+    }
+
+    let wget_path = target_dir.join(format!("{book}-wget-list"));
+    let md5_path = target_dir.join(format!("{book}-md5sums"));
+
+    fs::write(&wget_path, wget_list).with_context(|| format!("Writing {wget_path:?}"))?;
+    fs::write(&md5_path, md5sums).with_context(|| format!("Writing {md5_path:?}"))?;
+
+    println!("Saved artifacts to {:?} and {:?}", wget_path, md5_path);
+    Ok(())
+}
+
+/// Print (and with `run` execute) install commands for tools reported as missing by EnvCheck.
+fn suggest_env_fixes(missing: &BTreeSet<String>, run: bool) -> Result<()> {
+    let Some(manager) = version_check::PackageManager::detect() else {
+        eprintln!("No supported package manager (apt-get, pacman, dnf) found; install manually:");
+        for program in missing {
+            eprintln!("  {program}");
+        }
+        return Ok(());
+    };
+
+    println!("Suggested fixes ({manager:?}):");
+    for program in missing {
+        let Some(command) = manager.install_command(program) else {
+            println!("  {program}: no known package, install manually");
+            continue;
+        };
+        println!("  {program}: {}", command.join(" "));
+        if run {
+            let status = std::process::Command::new(&command[0])
+                .args(&command[1..])
+                .status()
+                .with_context(|| format!("Running `{}`", command.join(" ")))?;
+            if !status.success() {
+                eprintln!("  `{}` exited with {status}", command.join(" "));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
     match cmd {
-        WorkflowCommand::EnvCheck { url } => {
-            let pre_blocks = html::fetch_pre_blocks(&url)
+        WorkflowCommand::EnvCheck { url, fix, auto_fix } => {
+            let url = url
+                .or_else(|| config::get().version_check_url.clone())
+                .ok_or_else(|| {
+                    anyhow!("No URL given and no version_check_url set in the lpkg config")
+                })?;
+            let pre_blocks = html::fetch_pre_blocks_by_class(&url, None)
                 .with_context(|| format!("Fetching HTML `<pre>` blocks from {url}"))?;
 
             let mut ran_any = false;
             let mut failures = Vec::new();
+            let mut missing = BTreeSet::new();
 
             for (idx, block) in pre_blocks.iter().enumerate() {
                 if !(block.contains("ver_check") || block.contains("ver_kernel")) {
@@ -176,9 +498,16 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
 
                 ran_any = true;
                 println!("Running version checks from block #{idx}...");
-                if !version_check::run_version_checks_from_block(block) {
+                let report = version_check::check_block(block)
+                    .with_context(|| format!("Parsing version checks in block #{idx}"))?;
+                if !report.ok {
                     failures.push(idx + 1);
                 }
+                missing.extend(report.missing);
+            }
+
+            if (fix || auto_fix) && !missing.is_empty() {
+                suggest_env_fixes(&missing, auto_fix)?;
             }
 
             if !ran_any {
@@ -193,31 +522,42 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
 
             println!("All version checks passed 👍");
         }
-        WorkflowCommand::FetchManifests { output } => {
-            let wget_list = wget_list::get_wget_list().context("Fetching wget-list")?;
-            let md5sums = md5_utils::get_md5sums().context("Fetching md5sums")?;
-
-            println!("Fetched wget-list ({} bytes)", wget_list.len());
-            println!("Fetched md5sums ({} bytes)", md5sums.len());
-
+        WorkflowCommand::FetchManifests {
+            output,
+            strict,
+            books,
+        } => {
             let target_dir = output.unwrap_or(std::env::current_dir()?);
             fs::create_dir_all(&target_dir)
                 .with_context(|| format!("Creating output directory at {:?}", target_dir))?;
 
-            let wget_path = target_dir.join("wget-list");
-            let md5_path = target_dir.join("md5sums");
-
-            fs::write(&wget_path, wget_list).with_context(|| format!("Writing {wget_path:?}"))?;
-            fs::write(&md5_path, md5sums).with_context(|| format!("Writing {md5_path:?}"))?;
-
-            println!("Saved artifacts to {:?} and {:?}", wget_path, md5_path);
+            let mut failed = Vec::new();
+            for book in books.iter().map(|book| book.trim().to_lowercase()) {
+                if let Err(err) = fetch_book_manifests(&book, &target_dir, strict) {
+                    eprintln!("error: {book}: {err:#}");
+                    failed.push(book);
+                }
+            }
+            if !failed.is_empty() {
+                return Err(anyhow!(
+                    "failed to fetch manifests for: {}",
+                    failed.join(", ")
+                ));
+            }
         }
         WorkflowCommand::BuildBinutils {
             url,
             lfs_root,
             target,
+            use_ccache,
+            no_ccache,
         } => {
-            build_binutils_from_page(&url, &lfs_root, target)
+            let ccache = match (use_ccache, no_ccache) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            build_binutils_from_page(&url, &lfs_root, target, ccache)
                 .with_context(|| format!("Building Binutils using instructions from {url}"))?;
 
             println!("Binutils workflow completed successfully");
@@ -236,8 +576,14 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
             cflag,
             ldflag,
             profdata,
+            optimization_preset,
+            strict_flags,
+            target_triple,
+            cmake_toolchain,
             base,
+            from_toml,
         } => {
+            let base = resolve_base_dir(base);
             let base_dir = if base.is_relative() {
                 env::current_dir()
                     .context("Resolving scaffold base directory")?
@@ -246,25 +592,45 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                 base
             };
 
-            let request = ScaffoldRequest {
-                name: name.clone(),
-                version: version.clone(),
-                source,
-                md5,
-                configure_args: configure_arg,
-                build_commands: build_cmd,
-                install_commands: install_cmd,
-                dependencies: dependency,
-                enable_lto,
-                enable_pgo,
-                cflags: cflag,
-                ldflags: ldflag,
-                profdata,
-                stage: None,
-                variant: None,
-                notes: None,
-                module_override: None,
+            let request = match from_toml {
+                Some(path) => {
+                    let raw = fs::read_to_string(&path)
+                        .with_context(|| format!("Reading package definition {path:?}"))?;
+                    ScaffoldRequest {
+                        strict_flags,
+                        target_triple,
+                        cmake_toolchain,
+                        ..ScaffoldRequest::from(package::from_toml(&raw)?)
+                    }
+                }
+                None => ScaffoldRequest {
+                    name: name.unwrap_or_default(),
+                    version: version.unwrap_or_default(),
+                    source,
+                    md5,
+                    configure_args: configure_arg,
+                    patch_commands: Vec::new(),
+                    build_commands: build_cmd,
+                    install_commands: install_cmd,
+                    dependencies: dependency,
+                    enable_lto,
+                    enable_pgo,
+                    cflags: cflag,
+                    ldflags: ldflag,
+                    profdata,
+                    preset: optimization_preset,
+                    strict_flags,
+                    target_triple,
+                    cmake_toolchain,
+                    stage: None,
+                    variant: None,
+                    notes: None,
+                    module_override: None,
+                    source_hash: None,
+                },
             };
+            let name = request.name.clone();
+            let version = request.version.clone();
 
             let scaffold = scaffolder::scaffold_package(&base_dir, request)
                 .with_context(|| format!("Scaffolding package {name}"))?;
@@ -283,8 +649,11 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
             limit,
             base,
             overwrite,
+            parallel,
             source_url,
+            no_fallback,
         } => {
+            let base = resolve_base_dir(base);
             let base_dir = if base.is_relative() {
                 env::current_dir()
                     .context("Resolving MLFS scaffold base directory")?
@@ -293,14 +662,16 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                 base
             };
 
-            let mut records = mlfs::load_or_fetch_catalog(source_url.as_deref())
+            let mut records = mlfs::load_or_fetch_catalog(source_url.as_deref(), !no_fallback)
                 .context("Loading MLFS catalogue")?;
             records.sort_by(|a, b| a.name.cmp(&b.name).then(a.variant.cmp(&b.variant)));
 
             let mut seen = BTreeSet::new();
             let mut processed = 0usize;
             let mut created = 0usize;
+            let mut unchanged = 0usize;
             let mut skipped = Vec::new();
+            let mut queued = Vec::new();
 
             let metadata_entries = match mlfs::load_metadata_index() {
                 Ok(entries) => Some(entries),
@@ -317,10 +688,8 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
             };
 
             for record in records {
-                if let Some(limit) = limit {
-                    if processed >= limit {
-                        break;
-                    }
+                if limit.is_some_and(|limit| processed >= limit) {
+                    break;
                 }
                 processed += 1;
 
@@ -328,9 +697,11 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                     .as_ref()
                     .and_then(|entries| mlfs::match_metadata(&record, entries));
 
-                let mut request = if let Some(entry) = metadata_entry {
-                    let path = PathBuf::from("ai/metadata").join(&entry.path);
-                    match generator::request_from_metadata(&path) {
+                let metadata_path =
+                    metadata_entry.map(|entry| PathBuf::from("ai/metadata").join(&entry.path));
+
+                let mut request = if let Some(path) = &metadata_path {
+                    match generator::request_from_metadata(path) {
                         Ok(req) => req,
                         Err(err) => {
                             eprintln!(
@@ -340,9 +711,10 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                             ScaffoldRequest {
                                 name: record.name.clone(),
                                 version: record.version.clone(),
-                                source: None,
-                                md5: None,
+                                source: record.source.clone(),
+                                md5: record.md5.clone(),
                                 configure_args: Vec::new(),
+                                patch_commands: Vec::new(),
                                 build_commands: Vec::new(),
                                 install_commands: Vec::new(),
                                 dependencies: Vec::new(),
@@ -351,10 +723,15 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                                 cflags: Vec::new(),
                                 ldflags: Vec::new(),
                                 profdata: None,
+                                preset: None,
+                                strict_flags: false,
+                                target_triple: None,
+                                cmake_toolchain: false,
                                 stage: record.stage.clone(),
                                 variant: record.variant.clone(),
                                 notes: record.notes.clone(),
                                 module_override: None,
+                                source_hash: None,
                             }
                         }
                     }
@@ -362,9 +739,10 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                     ScaffoldRequest {
                         name: record.name.clone(),
                         version: record.version.clone(),
-                        source: None,
-                        md5: None,
+                        source: record.source.clone(),
+                        md5: record.md5.clone(),
                         configure_args: Vec::new(),
+                        patch_commands: Vec::new(),
                         build_commands: Vec::new(),
                         install_commands: Vec::new(),
                         dependencies: Vec::new(),
@@ -373,10 +751,15 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                         cflags: Vec::new(),
                         ldflags: Vec::new(),
                         profdata: None,
+                        preset: None,
+                        strict_flags: false,
+                        target_triple: None,
+                        cmake_toolchain: false,
                         stage: record.stage.clone(),
                         variant: record.variant.clone(),
                         notes: record.notes.clone(),
                         module_override: None,
+                        source_hash: None,
                     }
                 };
 
@@ -389,6 +772,12 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                 if request.notes.is_none() {
                     request.notes = record.notes.clone();
                 }
+                if request.md5.is_none() {
+                    request.md5 = record.md5.clone();
+                }
+                if request.source.is_none() {
+                    request.source = record.source.clone();
+                }
 
                 let module_alias = request
                     .module_override
@@ -403,6 +792,14 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                     request.module_override = Some(module_alias.clone());
                 }
 
+                if let Some(path) = &metadata_path {
+                    let module = scaffolder::module_path(&base_dir, &module_alias);
+                    if generator::module_is_up_to_date(path, &module).unwrap_or(false) {
+                        unchanged += 1;
+                        continue;
+                    }
+                }
+
                 if dry_run {
                     println!(
                         "Would scaffold {:<18} {:<12} -> {}",
@@ -411,18 +808,46 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                     continue;
                 }
 
-                match scaffolder::scaffold_package(&base_dir, request) {
-                    Ok(result) => {
-                        if let Some(pool) = &pool {
-                            db::upsert_package_via_pool(pool, &result.definition).with_context(
-                                || {
-                                    format!(
-                                        "Persisting MLFS package metadata for {} {}",
-                                        record.name, record.version
-                                    )
-                                },
-                            )?;
-                        }
+                queued.push((record, module_alias, request));
+            }
+
+            let progress = ProgressBar::new(queued.len() as u64);
+            progress.set_style(
+                ProgressStyle::with_template("[{pos}/{len}] Scaffolding {msg}")
+                    .expect("valid progress template"),
+            );
+            let workers = rayon::ThreadPoolBuilder::new()
+                .num_threads(parallel.max(1))
+                .build()
+                .context("Building scaffolding thread pool")?;
+            let outcomes: Vec<_> = workers.install(|| {
+                queued
+                    .into_par_iter()
+                    .map(|(record, module_alias, request)| {
+                        progress.set_message(module_alias.clone());
+                        let outcome =
+                            scaffolder::scaffold_package(&base_dir, request).and_then(|result| {
+                                if let Some(pool) = &pool {
+                                    db::upsert_package_via_pool(pool, &result.definition)
+                                        .with_context(|| {
+                                            format!(
+                                                "Persisting MLFS package metadata for {} {}",
+                                                record.name, record.version
+                                            )
+                                        })?;
+                                }
+                                Ok(())
+                            });
+                        progress.inc(1);
+                        (record, module_alias, outcome)
+                    })
+                    .collect()
+            });
+            progress.finish_and_clear();
+
+            for (record, module_alias, outcome) in outcomes {
+                match outcome {
+                    Ok(()) => {
                         println!(
                             "Scaffolded {:<18} {:<12} -> {}",
                             record.name, record.version, module_alias
@@ -448,8 +873,9 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                 );
             } else {
                 println!(
-                    "MLFS import complete. Created {} modules, skipped {} (already existed).",
+                    "MLFS import complete. Created {} modules, {} unchanged, skipped {} (already existed).",
                     created,
+                    unchanged,
                     skipped.len()
                 );
                 if !skipped.is_empty() {
@@ -468,6 +894,306 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
                 }
             }
         }
+        WorkflowCommand::ShowWgetGroups => {
+            let raw = wget_list::get_wget_list().context("Fetching wget-list")?;
+            let entries = wget_list::parse_wget_list(&raw);
+            let groups = wget_list::group_by_package(&entries);
+
+            for (package, entries) in &groups {
+                println!("{package}");
+                for entry in entries {
+                    println!("  {}", entry.url);
+                }
+            }
+            println!("{} packages, {} files", groups.len(), entries.len());
+        }
+        WorkflowCommand::GenerateAria2Input { output } => {
+            let raw = wget_list::get_wget_list().context("Fetching wget-list")?;
+            let md5sums = md5_utils::get_md5sums().context("Fetching md5sums")?;
+
+            let entries = wget_list::parse_wget_list(&raw);
+            let checksums = md5_utils::parse_md5sums(&md5sums);
+            let rendered = wget_list::to_aria2_input(&entries, Some(&checksums));
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, rendered).with_context(|| format!("Writing {path:?}"))?;
+                    println!(
+                        "Wrote aria2 input for {} files to {:?}",
+                        entries.len(),
+                        path
+                    );
+                }
+                None => print!("{rendered}"),
+            }
+        }
+        WorkflowCommand::AuditPackages => {
+            let pool = db::establish_pool().context("Setting up package database")?;
+            let incomplete = db::list_packages_missing_checksums_via_pool(&pool)
+                .context("Auditing package checksums")?;
+            if incomplete.is_empty() {
+                println!("All packages have a source URL and an MD5 checksum");
+                return Ok(());
+            }
+
+            println!("{:<24} {:<14} MISSING", "NAME", "VERSION");
+            for pkg in &incomplete {
+                let missing: Vec<&str> = [("source", &pkg.source), ("md5", &pkg.md5)]
+                    .into_iter()
+                    .filter(|(_, value)| value.is_none())
+                    .map(|(field, _)| field)
+                    .collect();
+                println!(
+                    "{:<24} {:<14} {}",
+                    pkg.name,
+                    pkg.version,
+                    missing.join(", ")
+                );
+            }
+            return Err(anyhow!(
+                "{} package(s) lack a source URL or MD5 checksum",
+                incomplete.len()
+            ));
+        }
+        WorkflowCommand::Download {
+            target_dir,
+            book,
+            force,
+        } => {
+            let raw = wget_list::fetch_manifest(&book, ManifestKind::WgetList)
+                .context("Fetching wget-list")?;
+            let md5sums = wget_list::fetch_manifest(&book, ManifestKind::Md5Sums)
+                .context("Fetching md5sums")?;
+            let checksums = md5_utils::parse_md5sums(&md5sums);
+            let entries = wget_list::parse_wget_list(&raw)
+                .into_iter()
+                .map(|entry| {
+                    let download = downloader::DownloadEntry::new(&entry.url);
+                    match checksums.get(&entry.filename) {
+                        Some(checksum) => download.with_checksum(checksum.clone()),
+                        None => download,
+                    }
+                })
+                .collect();
+
+            let mirrors = config::get()
+                .mirror_list()
+                .context("Loading mirror list")?
+                .map(Arc::new);
+            let options = downloader::DownloadOptions {
+                force,
+                mirrors,
+                ..Default::default()
+            };
+            let result = downloader::download_files_with_options(
+                &target_dir,
+                entries,
+                options,
+                |progress| {
+                    if progress.status == downloader::EntryStatus::Skipped {
+                        println!(
+                            "[SKIP] {} already downloaded and verified",
+                            progress.filename
+                        );
+                    }
+                },
+            );
+            // The queue is saved even when a download fails, so report from disk.
+            if let Some(queue) = downloader::DownloadQueue::load(&target_dir)? {
+                println!(
+                    "Downloaded {}, skipped {}, failed {}",
+                    queue.count(downloader::EntryStatus::Completed),
+                    queue.count(downloader::EntryStatus::Skipped),
+                    queue.count(downloader::EntryStatus::Failed)
+                );
+            }
+            result.with_context(|| format!("Downloading {book} sources into {target_dir:?}"))?;
+        }
+        WorkflowCommand::VerifyDownloads { dir, md5sums } => {
+            let md5sums = match md5sums {
+                Some(path) => {
+                    fs::read_to_string(&path).with_context(|| format!("Reading {path:?}"))?
+                }
+                None => md5_utils::get_md5sums().context("Fetching md5sums")?,
+            };
+
+            let report = md5_utils::verify_directory(&dir, &md5sums)
+                .with_context(|| format!("Verifying downloads in {dir:?}"))?;
+
+            for name in &report.failed {
+                eprintln!("FAILED: {name}");
+            }
+            for name in &report.missing {
+                eprintln!("MISSING: {name}");
+            }
+            println!(
+                "{} ok, {} failed, {} missing",
+                report.ok.len(),
+                report.failed.len(),
+                report.missing.len()
+            );
+
+            if !report.failed.is_empty() {
+                return Err(anyhow!(
+                    "{} file(s) failed checksum verification",
+                    report.failed.len()
+                ));
+            }
+        }
+        WorkflowCommand::GenerateMd5sums { dir, output } => {
+            let output = output.unwrap_or_else(|| dir.join("md5sums"));
+            let count = md5_utils::generate_md5sums_file(&dir, &output)
+                .with_context(|| format!("Generating md5sums for {dir:?}"))?;
+            println!("Wrote {count} checksums to {:?}", output);
+        }
+        WorkflowCommand::DumpPackage { name, version } => {
+            let pool = db::establish_pool().context("Setting up package database")?;
+            let definition =
+                db::find_package_definition_via_pool(&pool, &name, version.as_deref())?
+                    .ok_or_else(|| anyhow!("Package {name} not found in database"))?;
+            print!("{}", package::to_toml(&definition)?);
+        }
+        WorkflowCommand::DiffPackage {
+            name,
+            old_version,
+            new_version,
+        } => {
+            let pool = db::establish_pool().context("Setting up package database")?;
+            let old = db::find_package_definition_via_pool(&pool, &name, Some(&old_version))?
+                .ok_or_else(|| anyhow!("Package {name} {old_version} not found in database"))?;
+            let new = db::find_package_definition_via_pool(&pool, &name, Some(&new_version))?
+                .ok_or_else(|| anyhow!("Package {name} {new_version} not found in database"))?;
+
+            let diff = package::diff(&old, &new);
+            if diff.is_empty() {
+                println!("No differences between {name} {old_version} and {new_version}");
+            } else {
+                println!("--- {name} {old_version}");
+                println!("+++ {name} {new_version}");
+                print!("{diff}");
+            }
+        }
+        WorkflowCommand::ExportScript {
+            name,
+            version,
+            output,
+        } => {
+            let pool = db::establish_pool().context("Setting up package database")?;
+            let definition =
+                db::find_package_definition_via_pool(&pool, &name, version.as_deref())?
+                    .ok_or_else(|| anyhow!("Package {name} not found in database"))?;
+            let script = package::to_shell_script(&definition);
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, script).with_context(|| format!("Writing {path:?}"))?;
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                            .with_context(|| format!("Marking {path:?} executable"))?;
+                    }
+                    println!("Wrote build script to {:?}", path);
+                }
+                None => print!("{script}"),
+            }
+        }
+        WorkflowCommand::CleanQueue { target_dir } => {
+            if downloader::DownloadQueue::remove(&target_dir)? {
+                println!("Removed download queue from {:?}", target_dir);
+            } else {
+                println!("No download queue found in {:?}", target_dir);
+            }
+        }
+        WorkflowCommand::SuggestOwner { task_id } => {
+            let base_dir = env::current_dir().context("Resolving repository root")?;
+            let personas = ai::load_personas(&base_dir).context("Loading ai/personas.json")?;
+            let board = ai::load_tasks(&base_dir).context("Loading ai/tasks.json")?;
+            let task = board
+                .unfinished
+                .iter()
+                .chain(&board.solved)
+                .find(|task| task.id == task_id)
+                .ok_or_else(|| anyhow!("No task with id `{task_id}` in ai/tasks.json"))?;
+
+            println!("Suggested owners for {} ({}):", task.id, task.title);
+            for (persona, score) in ai::rank_personas_for_task(&personas, task) {
+                println!(
+                    "  {:>5.1}%  {} ({})",
+                    score * 100.0,
+                    persona.name,
+                    persona.id
+                );
+            }
+        }
+        WorkflowCommand::CloseBug { id, resolution } => {
+            let base_dir = env::current_dir().context("Resolving repository root")?;
+            let mut bugs = ai::load_bugs(&base_dir).context("Loading ai/bugs.json")?;
+            let bug = bugs
+                .iter_mut()
+                .find(|bug| bug.id == id)
+                .ok_or_else(|| anyhow!("No bug with id `{id}` in ai/bugs.json"))?;
+            bug.status = "closed".to_string();
+            if resolution.is_some() {
+                bug.resolution = resolution;
+            }
+            ai::save_bugs(&base_dir, &bugs).context("Saving ai/bugs.json")?;
+            println!("Closed bug {id}");
+        }
+        WorkflowCommand::WeeklyReport { output } => {
+            let base_dir = env::current_dir().context("Resolving repository root")?;
+            let board = ai::load_tasks(&base_dir).context("Loading ai/tasks.json")?;
+            let bugs = ai::load_bugs(&base_dir).context("Loading ai/bugs.json")?;
+
+            let mut report = Vec::new();
+            ai::write_task_summary(&board, &mut report)?;
+            ai::write_bug_summary(&bugs, &mut report)?;
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, &report).with_context(|| format!("Writing {path:?}"))?;
+                    println!("Wrote weekly report to {:?}", path);
+                }
+                None => io::stdout().write_all(&report)?,
+            }
+        }
+        WorkflowCommand::RebuildIndex { base, registry } => {
+            let base_dir = resolve_base_dir(base);
+            let registry = registry.unwrap_or_else(|| {
+                base_dir
+                    .parent()
+                    .unwrap_or(Path::new("."))
+                    .join("registry.rs")
+            });
+            let summary = scaffolder::rebuild_index(&base_dir, &registry)
+                .with_context(|| format!("Rebuilding module index under {base_dir:?}"))?;
+            println!(
+                "Indexed {} module(s) in {:?}; {} definition(s) registered in {:?}",
+                summary.modules.len(),
+                summary.by_name_module,
+                summary.registered.len(),
+                summary.registry_path
+            );
+        }
+        WorkflowCommand::PrintConfig => {
+            print!(
+                "{}",
+                toml::to_string_pretty(config::get()).context("Serializing lpkg config")?
+            );
+        }
+        WorkflowCommand::GenerateCompletion { shell, output } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            match output {
+                Some(path) => {
+                    let mut file =
+                        fs::File::create(&path).with_context(|| format!("Creating {path:?}"))?;
+                    clap_complete::generate(shell, &mut command, name, &mut file);
+                    println!("Wrote {shell} completions to {:?}", path);
+                }
+                None => clap_complete::generate(shell, &mut command, name, &mut io::stdout()),
+            }
+        }
     }
 
     Ok(())
@@ -477,7 +1203,8 @@ fn run_workflow(cmd: WorkflowCommand) -> Result<()> {
 fn run_tui(cmd: TuiCommand) -> Result<()> {
     match cmd {
         TuiCommand::DiskManager => {
-            DiskManager::run_tui().map_err(|e| anyhow!(e.to_string()))?;
+            DiskManager::run_tui(&Settings::load().keybindings, &ActionDebounce::default())
+                .map_err(|e| anyhow!(e.to_string()))?;
         }
     }
 
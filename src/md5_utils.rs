@@ -1,4 +1,163 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
 use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+/// Expected digest of a downloaded artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", content = "value", rename_all = "lowercase")]
+pub enum Checksum {
+    Md5(String),
+    Sha256(String),
+}
+
+impl Checksum {
+    /// Hex-encoded digest as stored in the checksum manifest.
+    pub fn value(&self) -> &str {
+        match self {
+            Checksum::Md5(value) | Checksum::Sha256(value) => value,
+        }
+    }
+
+    /// Hash the file at `path` with the matching algorithm and compare against the expected value.
+    pub fn matches_file(&self, path: &Path) -> Result<bool> {
+        let actual = match self {
+            Checksum::Md5(_) => compute_md5(path)?,
+            Checksum::Sha256(_) => compute_sha256(path)?,
+        };
+        Ok(actual.eq_ignore_ascii_case(self.value()))
+    }
+}
+
+/// Compute the hex-encoded MD5 digest of a file, streaming it in chunks.
+pub fn compute_md5(path: &Path) -> Result<String> {
+    let mut context = md5::Context::new();
+    stream_file(path, |chunk| context.consume(chunk))?;
+    Ok(format!("{:x}", context.finalize()))
+}
+
+/// Compute the hex-encoded SHA-256 digest of a file, streaming it in chunks.
+pub fn compute_sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    stream_file(path, |chunk| hasher.update(chunk))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn stream_file(path: &Path, mut sink: impl FnMut(&[u8])) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("reading {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        sink(&buffer[..read]);
+    }
+    Ok(())
+}
+
+/// Parse `md5sum`-style lines (`<digest>  <filename>`) into a filename -> checksum map.
+pub fn parse_md5sums(raw: &str) -> HashMap<String, Checksum> {
+    raw.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let digest = parts.next()?;
+            let filename = parts.next()?.trim_start_matches('*');
+            Some((filename.to_string(), Checksum::Md5(digest.to_lowercase())))
+        })
+        .collect()
+}
+
+/// Outcome of checking a directory of archives against an `md5sums` manifest.
+#[derive(Debug, Default, Clone)]
+pub struct VerificationReport {
+    pub ok: Vec<String>,
+    pub failed: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+impl VerificationReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Verify every file listed in `md5sums_text` against the copies found under `dir`.
+pub fn verify_directory(dir: &Path, md5sums_text: &str) -> Result<VerificationReport> {
+    let expected = parse_md5sums(md5sums_text);
+
+    let mut located: HashMap<String, PathBuf> = HashMap::new();
+    for entry in WalkDir::new(dir) {
+        let entry = entry.with_context(|| format!("walking {}", dir.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if expected.contains_key(&name) {
+            located.entry(name).or_insert_with(|| entry.into_path());
+        }
+    }
+
+    let mut filenames = expected.keys().cloned().collect::<Vec<_>>();
+    filenames.sort();
+
+    let mut report = VerificationReport::default();
+    for filename in filenames {
+        match located.get(&filename) {
+            Some(path) => {
+                if expected[&filename].matches_file(path)? {
+                    report.ok.push(filename);
+                } else {
+                    report.failed.push(filename);
+                }
+            }
+            None => report.missing.push(filename),
+        }
+    }
+
+    Ok(report)
+}
+
+const ARCHIVE_SUFFIXES: &[&str] = &[".zip", ".patch"];
+
+fn is_archive(filename: &str) -> bool {
+    filename.contains(".tar.") || ARCHIVE_SUFFIXES.iter().any(|ext| filename.ends_with(ext))
+}
+
+/// Hash every archive (`.tar.*`, `.zip`, `.patch`) directly inside `dir` and write a GNU
+/// coreutils compatible manifest to `output`. Returns the number of files written.
+pub fn generate_md5sums_file(dir: &Path, output: &Path) -> Result<usize> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry.with_context(|| format!("reading {}", dir.display()))?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if is_archive(&name) {
+            files.push((name, entry.path()));
+        }
+    }
+    files.sort();
+
+    let lines = files
+        .par_iter()
+        .map(|(name, path)| compute_md5(path).map(|hash| format!("{hash}  {name}\n")))
+        .collect::<Result<Vec<_>>>()?;
+
+    fs::write(output, lines.concat()).with_context(|| format!("writing {}", output.display()))?;
+    Ok(lines.len())
+}
 
 pub fn get_md5sums() -> Result<String> {
     let agent = ureq::AgentBuilder::new().redirects(5).build();
@@ -1,6 +1,11 @@
+use anyhow::{Context, Result};
 use console::Style;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub fn fetch_mirrors() -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let res = ureq::get("https://www.linuxfromscratch.org/lfs/mirrors.html#files")
@@ -58,3 +63,157 @@ pub fn choose_package_mirror() -> Option<String> {
         Some(chosen.to_string())
     }
 }
+
+/// A source mirror; files are fetched from `{url}/{filename}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mirror {
+    pub url: String,
+    /// Selection weight for [`MirrorList::weighted_random_url`]; higher is picked more often.
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_priority() -> u8 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct MirrorFile {
+    #[serde(default)]
+    mirrors: Vec<Mirror>,
+}
+
+/// Mirrors loaded from a TOML file of `[[mirrors]]` tables:
+///
+/// ```toml
+/// [[mirrors]]
+/// url = "https://ftp.fau.de/lfs/lfs-packages/12.4"
+/// priority = 10
+/// enabled = true
+/// ```
+#[derive(Debug, Default)]
+pub struct MirrorList {
+    mirrors: Vec<Mirror>,
+    rr_index: AtomicUsize,
+}
+
+impl MirrorList {
+    pub fn new(mirrors: Vec<Mirror>) -> Self {
+        Self {
+            mirrors,
+            rr_index: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn from_toml(path: &Path) -> Result<Self> {
+        let raw =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let file: MirrorFile =
+            toml::from_str(&raw).with_context(|| format!("parsing {}", path.display()))?;
+        Ok(Self::new(file.mirrors))
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &Mirror> {
+        self.mirrors.iter().filter(|mirror| mirror.enabled)
+    }
+
+    /// Cycle through the enabled mirrors; safe to call from several threads.
+    pub fn next_url(&self) -> Option<&str> {
+        let count = self.enabled().count();
+        if count == 0 {
+            return None;
+        }
+        let index = self.rr_index.fetch_add(1, Ordering::Relaxed) % count;
+        self.enabled().nth(index).map(|mirror| mirror.url.as_str())
+    }
+
+    /// Pick an enabled mirror with probability proportional to its priority, given a uniformly
+    /// random `roll` (e.g. `rand::random()`). When every priority is 0 the first enabled mirror
+    /// wins.
+    pub fn weighted_random_url(&self, roll: u64) -> Option<&str> {
+        let total: u64 = self
+            .enabled()
+            .map(|mirror| u64::from(mirror.priority))
+            .sum();
+        if total == 0 {
+            return self.enabled().next().map(|mirror| mirror.url.as_str());
+        }
+        let mut pick = roll % total;
+        for mirror in self.enabled() {
+            let weight = u64::from(mirror.priority);
+            if pick < weight {
+                return Some(&mirror.url);
+            }
+            pick -= weight;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirror(url: &str, priority: u8, enabled: bool) -> Mirror {
+        Mirror {
+            url: url.to_string(),
+            priority,
+            enabled,
+        }
+    }
+
+    #[test]
+    fn round_robin_skips_disabled_mirrors() {
+        let list = MirrorList::new(vec![
+            mirror("https://a.example", 1, true),
+            mirror("https://b.example", 1, false),
+            mirror("https://c.example", 1, true),
+        ]);
+        let order: Vec<_> = (0..4).map(|_| list.next_url().unwrap()).collect();
+        assert_eq!(
+            order,
+            [
+                "https://a.example",
+                "https://c.example",
+                "https://a.example",
+                "https://c.example"
+            ]
+        );
+        assert_eq!(MirrorList::default().next_url(), None);
+    }
+
+    #[test]
+    fn weighted_selection_follows_priorities() {
+        let list = MirrorList::new(vec![
+            mirror("https://a.example", 1, true),
+            mirror("https://b.example", 9, false),
+            mirror("https://c.example", 3, true),
+        ]);
+        let picks: Vec<_> = (0..4)
+            .map(|roll| list.weighted_random_url(roll).unwrap())
+            .collect();
+        assert_eq!(
+            picks,
+            [
+                "https://a.example",
+                "https://c.example",
+                "https://c.example",
+                "https://c.example"
+            ]
+        );
+        // Rolls wrap around the total weight.
+        assert_eq!(list.weighted_random_url(4), Some("https://a.example"));
+
+        let unweighted = MirrorList::new(vec![
+            mirror("https://a.example", 0, false),
+            mirror("https://b.example", 0, true),
+        ]);
+        assert_eq!(unweighted.weighted_random_url(7), Some("https://b.example"));
+    }
+}
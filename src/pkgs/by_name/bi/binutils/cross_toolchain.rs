@@ -15,20 +15,22 @@ pub struct BinutilsConfig {
     pub lfs_root: PathBuf,  // where the LFS tree will be (used for $LFS)
     pub target: String,     // LFS_TGT (e.g. x86_64-lfs-linux-gnu)
     pub info: BinutilsInfo, // parsed page info
+    /// wrap the cross compiler in ccache (cache location comes from `CCACHE_DIR`)
+    pub use_ccache: bool,
 }
 
 impl BinutilsConfig {
     /// create from env or params. If target is None, tries $LFS_TGT env var.
+    /// ccache is enabled whenever it is found on `$PATH`.
     pub fn new(lfs_root: impl AsRef<Path>, target: Option<String>, info: BinutilsInfo) -> Self {
         let lfs_root = lfs_root.as_ref().to_path_buf();
-        let target = target
-            .or_else(|| std::env::var("LFS_TGT").ok())
-            .unwrap_or_else(|| "x86_64-lfs-linux-gnu".to_string());
+        let target = resolve_target(target);
 
         Self {
             lfs_root,
             target,
             info,
+            use_ccache: ccache_available(),
         }
     }
 
@@ -59,18 +61,69 @@ impl BinutilsConfig {
     }
 }
 
+/// Explicit target, else $LFS_TGT, else the default x86_64 LFS triple.
+fn resolve_target(target: Option<String>) -> String {
+    target
+        .or_else(|| std::env::var("LFS_TGT").ok())
+        .unwrap_or_else(|| "x86_64-lfs-linux-gnu".to_string())
+}
+
+/// Whether a `ccache` binary is on `$PATH`.
+fn ccache_available() -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join("ccache").is_file()))
+        .unwrap_or(false)
+}
+
+/// Host tools Binutils Pass 1 needs before anything exists under `$LFS/tools`.
+const HOST_PREREQUISITES: &[&str] = &["gcc", "make", "tar"];
+
+/// Preflight: the host compiler and build tools must be on `$PATH`. The cross tools
+/// (`{target}-as`, `{target}-ld`) are what Pass 1 produces, so they are not required here.
+pub fn verify_host_prerequisites() -> Result<()> {
+    let path_dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    let missing: Vec<&str> = HOST_PREREQUISITES
+        .iter()
+        .copied()
+        .filter(|tool| !path_dirs.iter().any(|dir| dir.join(tool).is_file()))
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "host prerequisites missing from $PATH: {}",
+        missing.join(", ")
+    ))
+}
+
 /// High-level orchestration.
 pub fn build_binutils_from_page(
     page_url: &str,
     lfs_root: impl AsRef<Path>,
     target: Option<String>,
+    use_ccache: Option<bool>,
 ) -> Result<()> {
+    let target = resolve_target(target);
+    verify_host_prerequisites()?;
+
     println!("Fetching page: {page_url}");
     let html = fetch_page(page_url).context("fetching binutils instructions")?;
     let info = parse_binutils(&html).context("parsing binutils instructions")?;
     println!("Parsed info: {:?}", info);
+    match (info.sbu_float(), info.disk_space_mb()) {
+        (Some(sbu), Some(disk)) => println!("Build requirements: {sbu} SBU, {disk} MB disk space"),
+        (Some(sbu), None) => println!("Build requirements: {sbu} SBU"),
+        (None, Some(disk)) => println!("Build requirements: {disk} MB disk space"),
+        (None, None) => {}
+    }
 
-    let cfg = BinutilsConfig::new(lfs_root, target, info.clone());
+    let mut cfg = BinutilsConfig::new(lfs_root, Some(target), info.clone());
+    if let Some(use_ccache) = use_ccache {
+        cfg.use_ccache = use_ccache;
+    }
 
     let src_base = cfg.source_base_dir();
     if !src_base.exists() {
@@ -88,7 +141,11 @@ pub fn build_binutils_from_page(
         .ok_or_else(|| anyhow!("Could not locate or download/extract Binutils source"))?;
     println!("Using source dir: {:?}", source_dir);
 
-    let build_dir = cfg.build_dir();
+    let setup = run_setup_commands(&cfg, &source_dir).context("setup step failed")?;
+    let build_dir = match &setup.cwd {
+        Some(dir) => dir.clone(),
+        None => cfg.build_dir(),
+    };
     if !build_dir.exists() {
         println!("Creating build dir {:?}", build_dir);
         fs::create_dir_all(&build_dir)
@@ -127,12 +184,18 @@ pub fn build_binutils_from_page(
     let mut configure_cmd = Command::new(&configure_path);
     configure_cmd.current_dir(&build_dir);
     configure_cmd.args(&args);
+    configure_cmd.envs(setup.env.iter().cloned());
+    if cfg.use_ccache {
+        println!("Using ccache as compiler wrapper");
+        configure_cmd.env("CC", format!("ccache {}-gcc", cfg.target));
+        configure_cmd.env("CXX", format!("ccache {}-g++", cfg.target));
+    }
     run_command(&mut configure_cmd).context("configure step failed")?;
     println!("configure completed");
 
     if !cfg.info.build_cmds.is_empty() {
         for raw in &cfg.info.build_cmds {
-            run_shell_command(raw, &build_dir)
+            run_shell_command(raw, &build_dir, &setup.env)
                 .with_context(|| format!("build step failed: {raw}"))?;
         }
     } else {
@@ -144,7 +207,7 @@ pub fn build_binutils_from_page(
 
     if !cfg.info.install_cmds.is_empty() {
         for raw in &cfg.info.install_cmds {
-            run_shell_command(raw, &build_dir)
+            run_shell_command(raw, &build_dir, &setup.env)
                 .with_context(|| format!("install step failed: {raw}"))?;
         }
     } else {
@@ -158,6 +221,64 @@ pub fn build_binutils_from_page(
     Ok(())
 }
 
+/// Working directory and exported variables left behind by the setup commands.
+#[derive(Debug, Default)]
+struct SetupState {
+    /// final directory after any `cd`; `None` when no setup commands ran
+    cwd: Option<PathBuf>,
+    env: Vec<(String, String)>,
+}
+
+/// Replay `info.setup_cmds` starting in `source_dir`. `cd` and `export` are tracked here since
+/// every command runs in its own process; `mkdir` is skipped when its directories exist so the
+/// workflow can be re-run.
+fn run_setup_commands(cfg: &BinutilsConfig, source_dir: &Path) -> Result<SetupState> {
+    let mut state = SetupState::default();
+    if cfg.info.setup_cmds.is_empty() {
+        return Ok(state);
+    }
+
+    let mut cwd = source_dir.to_path_buf();
+    for raw in &cfg.info.setup_cmds {
+        let expanded = raw
+            .replace("$LFS_TGT", &cfg.target)
+            .replace("$LFS", &cfg.lfs_root.to_string_lossy());
+        let parts = shell_words::split(&expanded).unwrap_or_else(|_| vec![expanded.clone()]);
+        match parts.first().map(String::as_str) {
+            Some("cd") => {
+                let dir = parts
+                    .get(1)
+                    .map(|dir| cwd.join(dir))
+                    .unwrap_or_else(|| cwd.clone());
+                if !dir.is_dir() {
+                    return Err(anyhow!("cannot cd into {:?}", dir));
+                }
+                cwd = dir;
+            }
+            Some("export") => {
+                for assignment in &parts[1..] {
+                    if let Some((key, value)) = assignment.split_once('=') {
+                        state.env.push((key.to_string(), value.to_string()));
+                    }
+                }
+            }
+            Some("mkdir")
+                if parts[1..]
+                    .iter()
+                    .filter(|arg| !arg.starts_with('-'))
+                    .all(|dir| cwd.join(dir).is_dir()) =>
+            {
+                println!("Skipping {raw:?}: directory already exists");
+            }
+            Some(_) => run_shell_command(&expanded, &cwd, &state.env)
+                .with_context(|| format!("setup command failed: {raw}"))?,
+            None => {}
+        }
+    }
+    state.cwd = Some(cwd);
+    Ok(state)
+}
+
 fn locate_binutils_dir(base: &Path) -> Result<Option<PathBuf>> {
     if !base.exists() {
         return Ok(None);
@@ -194,7 +315,7 @@ fn download_and_extract(cfg: &BinutilsConfig, base: &Path) -> Result<Option<Path
         .with_context(|| format!("parsing final download URL {final_url}"))?;
     let filename = parsed
         .path_segments()
-        .and_then(|segments| segments.last())
+        .and_then(|mut segments| segments.next_back())
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
         .ok_or_else(|| anyhow!("Cannot determine filename from URL"))?;
@@ -233,7 +354,7 @@ fn run_command(cmd: &mut Command) -> Result<()> {
     Ok(())
 }
 
-fn run_shell_command(raw: &str, cwd: &Path) -> Result<()> {
+fn run_shell_command(raw: &str, cwd: &Path, env: &[(String, String)]) -> Result<()> {
     let mut parts = shell_words::split(raw).unwrap_or_else(|_| vec![raw.to_string()]);
     if parts.is_empty() {
         return Ok(());
@@ -244,5 +365,6 @@ fn run_shell_command(raw: &str, cwd: &Path) -> Result<()> {
         cmd.args(parts);
     }
     cmd.current_dir(cwd);
+    cmd.envs(env.iter().cloned());
     run_command(&mut cmd)
 }
@@ -1,13 +1,19 @@
 // Parser for Binutils Pass 1 page using lightweight HTTP fetching.
+use std::io::Read;
+
 use anyhow::{Context, Result};
 use scraper::{Html, Selector};
 
-#[derive(Debug, Clone)]
+use crate::ingest::parse_numeric;
+
+#[derive(Debug, Clone, Default)]
 pub struct BinutilsInfo {
     /// "2.45" or derived version text
     pub version: Option<String>,
     /// first archive download URL found (.tar.xz or .tar.gz)
     pub download_url: Option<String>,
+    /// preparation commands run before configure (e.g. ["mkdir -v build", "cd build"])
+    pub setup_cmds: Vec<String>,
     /// tokens for configure flags (everything after ../configure)
     pub configure_args: Vec<String>,
     /// build commands discovered (e.g. ["make"])
@@ -19,30 +25,62 @@ pub struct BinutilsInfo {
     pub disk_space: Option<String>,
 }
 
-impl Default for BinutilsInfo {
-    fn default() -> Self {
-        Self {
-            version: None,
-            download_url: None,
-            configure_args: Vec::new(),
-            build_cmds: Vec::new(),
-            install_cmds: Vec::new(),
-            sbu: None,
-            disk_space: None,
-        }
+impl BinutilsInfo {
+    /// Build time in SBU, e.g. `1.2` for "1.2 SBU".
+    pub fn sbu_float(&self) -> Option<f64> {
+        self.sbu.as_deref().and_then(parse_numeric)
+    }
+
+    /// Required disk space in MB; "1.2 GB" is converted to 1229.
+    pub fn disk_space_mb(&self) -> Option<u64> {
+        let raw = self.disk_space.as_deref()?;
+        let value = parse_numeric(raw)?;
+        let megabytes = if raw.to_uppercase().contains("GB") {
+            value * 1024.0
+        } else {
+            value
+        };
+        Some(megabytes.round() as u64)
     }
 }
 
-/// Fetch page content synchronously
+/// Largest page body `fetch_page` accepts.
+const MAX_PAGE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Fetch page content synchronously, following redirects. Fails unless the final response is an
+/// HTML document of at most [`MAX_PAGE_BYTES`].
 pub fn fetch_page(url: &str) -> Result<String> {
-    ureq::get(url)
-        .call()
-        .map_err(|err| match err {
-            ureq::Error::Status(code, _) => anyhow::anyhow!("Failed to fetch {url}: HTTP {code}"),
-            other => anyhow::anyhow!("Failed to fetch {url}: {other}"),
-        })?
-        .into_string()
-        .with_context(|| format!("reading body from {url}"))
+    let agent = ureq::AgentBuilder::new().redirects(5).build();
+    let response = agent.get(url).call().map_err(|err| match err {
+        ureq::Error::Status(code, _) => anyhow::anyhow!("Failed to fetch {url}: HTTP {code}"),
+        other => anyhow::anyhow!("Failed to fetch {url}: {other}"),
+    })?;
+
+    let content_type = response.content_type().to_ascii_lowercase();
+    if !content_type.starts_with("text/html") && content_type != "application/xhtml+xml" {
+        anyhow::bail!(
+            "expected an HTML page from {}, got Content-Type {content_type}",
+            response.get_url()
+        );
+    }
+    if let Some(length) = response
+        .header("Content-Length")
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|length| *length > MAX_PAGE_BYTES)
+    {
+        anyhow::bail!("page {url} is too large ({length} bytes, limit {MAX_PAGE_BYTES})");
+    }
+
+    let mut body = String::new();
+    response
+        .into_reader()
+        .take(MAX_PAGE_BYTES + 1)
+        .read_to_string(&mut body)
+        .with_context(|| format!("reading body from {url}"))?;
+    if body.len() as u64 > MAX_PAGE_BYTES {
+        anyhow::bail!("page {url} exceeds the {MAX_PAGE_BYTES} byte limit");
+    }
+    Ok(body)
 }
 
 /// Parse the LFS Binutils pass1 page; robust to small formatting changes.
@@ -56,30 +94,31 @@ pub fn parse_binutils(html: &str) -> Result<BinutilsInfo> {
     let mut info = BinutilsInfo::default();
 
     // 1) Version from h1.sect1 (contains "Binutils-2.45 - Pass 1")
-    if let Ok(h1_sel) = Selector::parse("h1.sect1") {
-        if let Some(h1) = document.select(&h1_sel).next() {
-            let txt = h1.text().collect::<Vec<_>>().join(" ");
-            // try to pick the token containing "Binutils-" or "binutils-"
-            if let Some(tok) = txt
-                .split_whitespace()
-                .find(|s| s.to_lowercase().contains("binutils"))
-            {
-                // extract digits from token, e.g. "Binutils-2.45"
-                if let Some(pos) = tok.find('-') {
-                    let ver = tok[pos + 1..]
-                        .trim()
-                        .trim_matches(|c: char| !c.is_ascii() && c != '.')
-                        .to_string();
-                    if !ver.is_empty() {
-                        info.version = Some(ver);
-                    }
-                } else {
-                    // fallback: try to find "2.45" somewhere in the h1 string
-                    for part in txt.split_whitespace() {
-                        if part.chars().next().map(|c| c.is_digit(10)).unwrap_or(false) {
-                            info.version = Some(part.trim().to_string());
-                            break;
-                        }
+    if let Some(h1) = Selector::parse("h1.sect1")
+        .ok()
+        .and_then(|h1_sel| document.select(&h1_sel).next())
+    {
+        let txt = h1.text().collect::<Vec<_>>().join(" ");
+        // try to pick the token containing "Binutils-" or "binutils-"
+        if let Some(tok) = txt
+            .split_whitespace()
+            .find(|s| s.to_lowercase().contains("binutils"))
+        {
+            // extract digits from token, e.g. "Binutils-2.45"
+            if let Some(pos) = tok.find('-') {
+                let ver = tok[pos + 1..]
+                    .trim()
+                    .trim_matches(|c: char| !c.is_ascii() && c != '.')
+                    .to_string();
+                if !ver.is_empty() {
+                    info.version = Some(ver);
+                }
+            } else {
+                // fallback: try to find "2.45" somewhere in the h1 string
+                for part in txt.split_whitespace() {
+                    if part.starts_with(|c: char| c.is_ascii_digit()) {
+                        info.version = Some(part.trim().to_string());
+                        break;
                     }
                 }
             }
@@ -104,23 +143,20 @@ pub fn parse_binutils(html: &str) -> Result<BinutilsInfo> {
     }
 
     // 3) Parse "segmentedlist" entries for SBU and disk space
-    if let Ok(segtitle_sel) =
-        Selector::parse("div.package .segmentedlist .seglistitem .seg strong.segtitle")
-    {
-        if let Ok(segbody_sel) =
-            Selector::parse("div.package .segmentedlist .seglistitem .seg span.segbody")
+    if let (Ok(segtitle_sel), Ok(segbody_sel)) = (
+        Selector::parse("div.package .segmentedlist .seglistitem .seg strong.segtitle"),
+        Selector::parse("div.package .segmentedlist .seglistitem .seg span.segbody"),
+    ) {
+        for (t, b) in document
+            .select(&segtitle_sel)
+            .zip(document.select(&segbody_sel))
         {
-            for (t, b) in document
-                .select(&segtitle_sel)
-                .zip(document.select(&segbody_sel))
-            {
-                let title = t.text().collect::<String>().to_lowercase();
-                let body = b.text().collect::<String>().trim().to_string();
-                if title.contains("approximate build time") {
-                    info.sbu = Some(body.clone());
-                } else if title.contains("required disk space") {
-                    info.disk_space = Some(body.clone());
-                }
+            let title = t.text().collect::<String>().to_lowercase();
+            let body = b.text().collect::<String>().trim().to_string();
+            if title.contains("approximate build time") {
+                info.sbu = Some(body.clone());
+            } else if title.contains("required disk space") {
+                info.disk_space = Some(body.clone());
             }
         }
     }
@@ -131,6 +167,17 @@ pub fn parse_binutils(html: &str) -> Result<BinutilsInfo> {
             let text = pre.text().collect::<Vec<_>>().join("\n");
             let trimmed = text.trim();
 
+            // setup block: nothing but mkdir / cd / ln / export lines
+            if is_setup_block(trimmed) {
+                info.setup_cmds.extend(
+                    trimmed
+                        .lines()
+                        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+                        .filter(|l| !l.is_empty()),
+                );
+                continue;
+            }
+
             // handle configure block (starts with ../configure or ./configure)
             if trimmed.starts_with("../configure")
                 || trimmed.starts_with("./configure")
@@ -217,3 +264,19 @@ pub fn parse_binutils(html: &str) -> Result<BinutilsInfo> {
 
     Ok(info)
 }
+
+const SETUP_COMMANDS: &[&str] = &["mkdir", "cd", "ln", "export"];
+
+fn is_setup_block(block: &str) -> bool {
+    let lines: Vec<&str> = block
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    !lines.is_empty()
+        && lines.iter().all(|line| {
+            line.split_whitespace()
+                .next()
+                .is_some_and(|program| SETUP_COMMANDS.contains(&program))
+        })
+}
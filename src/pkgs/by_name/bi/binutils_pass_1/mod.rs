@@ -1,9 +1,8 @@
-// MLFS metadata: stage: cross-toolchain, variant: Pass 1
-
-use crate::pkgs::package::{OptimizationSettings, PackageDefinition};
+use crate::pkgs::package::{OptimizationPreset, PackageDefinition};
 
 pub fn definition() -> PackageDefinition {
-    let mut pkg = PackageDefinition::new("Binutils", "2.45");
+    let mut pkg =
+        PackageDefinition::new_with_defaults("Binutils", "2.45", OptimizationPreset::Aggressive);
     pkg.source =
         Some("https://sourceware.org/pub/binutils/releases/binutils-2.45.tar.xz".to_string());
     pkg.md5 = Some("dee5b4267e0305a99a3c9d6131f45759".to_string());
@@ -23,16 +22,9 @@ pub fn definition() -> PackageDefinition {
     ];
     pkg.install_commands = vec!["make install".to_string()];
     pkg.dependencies = Vec::new();
-    let profdata = None;
-    let profdata_clone = profdata.clone();
-    pkg.optimizations = match profdata_clone {
-        Some(path) => OptimizationSettings::for_pgo_replay(path),
-        None => OptimizationSettings::default(),
-    };
-    pkg.optimizations.enable_lto = true;
+    pkg.stage = Some("cross-toolchain".to_string());
+    pkg.variant = Some("Pass 1".to_string());
+    pkg.notes = None;
     pkg.optimizations.enable_pgo = true;
-    pkg.optimizations.cflags = vec!["-O3".to_string(), "-flto".to_string()];
-    pkg.optimizations.ldflags = vec!["-flto".to_string()];
-    pkg.optimizations.profdata = profdata;
     pkg
 }
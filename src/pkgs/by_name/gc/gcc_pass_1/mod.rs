@@ -1,9 +1,8 @@
-// MLFS metadata: stage: cross-toolchain, variant: Pass 1
-
-use crate::pkgs::package::{OptimizationSettings, PackageDefinition};
+use crate::pkgs::package::{OptimizationPreset, PackageDefinition};
 
 pub fn definition() -> PackageDefinition {
-    let mut pkg = PackageDefinition::new("GCC", "15.2.0");
+    let mut pkg =
+        PackageDefinition::new_with_defaults("GCC", "15.2.0", OptimizationPreset::Aggressive);
     pkg.source = Some("https://ftp.gnu.org/gnu/gcc/gcc-15.2.0/gcc-15.2.0.tar.xz".to_string());
     pkg.md5 = Some("7c32c39b8b6e3ae85f25156228156061".to_string());
     pkg.configure_args = Vec::new();
@@ -51,16 +50,9 @@ pub fn definition() -> PackageDefinition {
     ];
     pkg.install_commands = vec!["make install".to_string()];
     pkg.dependencies = Vec::new();
-    let profdata = None;
-    let profdata_clone = profdata.clone();
-    pkg.optimizations = match profdata_clone {
-        Some(path) => OptimizationSettings::for_pgo_replay(path),
-        None => OptimizationSettings::default(),
-    };
-    pkg.optimizations.enable_lto = true;
+    pkg.stage = Some("cross-toolchain".to_string());
+    pkg.variant = Some("Pass 1".to_string());
+    pkg.notes = None;
     pkg.optimizations.enable_pgo = true;
-    pkg.optimizations.cflags = vec!["-O3".to_string(), "-flto".to_string()];
-    pkg.optimizations.ldflags = vec!["-flto".to_string()];
-    pkg.optimizations.profdata = profdata;
     pkg
 }
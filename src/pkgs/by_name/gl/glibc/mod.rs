@@ -1,9 +1,8 @@
-// MLFS metadata: stage: cross-toolchain
-
-use crate::pkgs::package::{OptimizationSettings, PackageDefinition};
+use crate::pkgs::package::{OptimizationPreset, PackageDefinition};
 
 pub fn definition() -> PackageDefinition {
-    let mut pkg = PackageDefinition::new("Glibc", "2.42");
+    let mut pkg =
+        PackageDefinition::new_with_defaults("Glibc", "2.42", OptimizationPreset::Aggressive);
     pkg.source = Some("https://ftp.gnu.org/gnu/glibc/glibc-2.42.tar.xz".to_string());
     pkg.md5 = Some("23c6f5a27932b435cae94e087cb8b1f5".to_string());
     pkg.configure_args = Vec::new();
@@ -59,16 +58,9 @@ pub fn definition() -> PackageDefinition {
     ];
     pkg.install_commands = Vec::new();
     pkg.dependencies = Vec::new();
-    let profdata = None;
-    let profdata_clone = profdata.clone();
-    pkg.optimizations = match profdata_clone {
-        Some(path) => OptimizationSettings::for_pgo_replay(path),
-        None => OptimizationSettings::default(),
-    };
-    pkg.optimizations.enable_lto = true;
+    pkg.stage = Some("cross-toolchain".to_string());
+    pkg.variant = None;
+    pkg.notes = None;
     pkg.optimizations.enable_pgo = true;
-    pkg.optimizations.cflags = vec!["-O3".to_string(), "-flto".to_string()];
-    pkg.optimizations.ldflags = vec!["-flto".to_string()];
-    pkg.optimizations.profdata = profdata;
     pkg
 }
@@ -1,9 +1,11 @@
-// MLFS metadata: stage: cross-toolchain
-
-use crate::pkgs::package::{OptimizationSettings, PackageDefinition};
+use crate::pkgs::package::{OptimizationPreset, PackageDefinition};
 
 pub fn definition() -> PackageDefinition {
-    let mut pkg = PackageDefinition::new("Linux", "6.16.9 API Headers");
+    let mut pkg = PackageDefinition::new_with_defaults(
+        "Linux",
+        "6.16.9 API Headers",
+        OptimizationPreset::Aggressive,
+    );
     pkg.source = None;
     pkg.md5 = None;
     pkg.configure_args = Vec::new();
@@ -15,16 +17,9 @@ pub fn definition() -> PackageDefinition {
     ];
     pkg.install_commands = Vec::new();
     pkg.dependencies = Vec::new();
-    let profdata = None;
-    let profdata_clone = profdata.clone();
-    pkg.optimizations = match profdata_clone {
-        Some(path) => OptimizationSettings::for_pgo_replay(path),
-        None => OptimizationSettings::default(),
-    };
-    pkg.optimizations.enable_lto = true;
+    pkg.stage = Some("cross-toolchain".to_string());
+    pkg.variant = None;
+    pkg.notes = None;
     pkg.optimizations.enable_pgo = true;
-    pkg.optimizations.cflags = vec!["-O3".to_string(), "-flto".to_string()];
-    pkg.optimizations.ldflags = vec!["-flto".to_string()];
-    pkg.optimizations.profdata = profdata;
     pkg
 }
@@ -2,3 +2,9 @@ pub mod bi;
 pub mod gc;
 pub mod gl;
 pub mod li;
+
+pub use self::bi::binutils;
+pub use self::bi::binutils_pass_1;
+pub use self::gc::gcc_pass_1;
+pub use self::gl::glibc;
+pub use self::li::linux;
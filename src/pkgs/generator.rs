@@ -4,9 +4,30 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
 use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 
 use crate::pkgs::scaffolder::{self, ScaffoldRequest};
 
+/// Fields every harvested metadata file must provide before a module can be generated.
+const REQUIRED_FIELDS: [&str; 3] = ["package.id", "package.name", "package.version"];
+
+/// Why a harvested metadata file could not be turned into a scaffold request.
+#[derive(Debug, Error)]
+pub enum MetadataParseError {
+    #[error("{}: required field `{field}` is missing", path.display())]
+    MissingField { path: PathBuf, field: &'static str },
+    #[error("{}: field `{field}` must be a non-empty string", path.display())]
+    InvalidField { path: PathBuf, field: &'static str },
+    #[error("{}: invalid metadata: {source}", path.display())]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
 #[derive(Debug, Deserialize)]
 struct HarvestedPackage {
     package: HarvestedMetadata,
@@ -91,21 +112,95 @@ struct HarvestedDependencies {
     runtime: Vec<String>,
 }
 
-/// Generate a Rust module from harvested metadata, returning the path to the generated file.
+/// Result of [`generate_module`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateOutcome {
+    /// The module was (re)written at this path.
+    Generated(PathBuf),
+    /// The module at this path already matches the metadata and was left alone.
+    Unchanged(PathBuf),
+}
+
+impl GenerateOutcome {
+    pub fn module_path(&self) -> &Path {
+        match self {
+            Self::Generated(path) | Self::Unchanged(path) => path,
+        }
+    }
+}
+
+/// Generate a Rust module from harvested metadata, skipping modules whose recorded metadata hash
+/// still matches.
 pub fn generate_module(
     metadata_path: impl AsRef<Path>,
     base_dir: impl AsRef<Path>,
-) -> Result<PathBuf> {
-    let harvested = parse_metadata(metadata_path.as_ref())?;
-    let request = build_request(&harvested)?;
+) -> Result<GenerateOutcome> {
+    let metadata_path = metadata_path.as_ref();
+    let request = request_from_metadata(metadata_path)?;
+    let module_name = request.module_override.as_deref().unwrap_or(&request.name);
+    let existing = scaffolder::module_path(base_dir.as_ref(), module_name);
+    if module_is_up_to_date(metadata_path, &existing)? {
+        return Ok(GenerateOutcome::Unchanged(existing));
+    }
+    // A module generated from older metadata is replaced; hand-written modules (no recorded
+    // hash) are left for `scaffold_package` to refuse.
+    if request.source_hash.is_some()
+        && recorded_hash(&existing)?.is_some()
+        && let Some(stale_dir) = existing.parent()
+    {
+        fs::remove_dir_all(stale_dir)
+            .with_context(|| format!("removing stale module {}", stale_dir.display()))?;
+    }
+
     let result = scaffolder::scaffold_package(base_dir.as_ref(), request)?;
-    Ok(result.module_path)
+    Ok(GenerateOutcome::Generated(result.module_path))
 }
 
 /// Build a scaffolding request directly from a metadata JSON file.
 pub fn request_from_metadata(path: impl AsRef<Path>) -> Result<ScaffoldRequest> {
-    let harvested = parse_metadata(path.as_ref())?;
-    build_request(&harvested)
+    let path = path.as_ref();
+    let harvested = parse_metadata(path)?;
+    let mut request = build_request(&harvested)?;
+    request.source_hash = Some(metadata_hash(path)?);
+    Ok(request)
+}
+
+/// Render the module source that [`generate_module`] would write for a metadata file, without
+/// writing anything.
+pub fn preview_module(metadata_path: &Path) -> Result<String> {
+    let request = request_from_metadata(metadata_path)?;
+    scaffolder::render_module(&request)
+}
+
+/// Whether the generated `mod.rs` at `module_path` was produced from the current contents of
+/// `metadata_path`, according to its `// lpkg-hash:` header. Missing modules are out of date.
+pub fn module_is_up_to_date(metadata_path: &Path, module_path: &Path) -> Result<bool> {
+    match recorded_hash(module_path)? {
+        Some(recorded) => Ok(recorded == metadata_hash(metadata_path)?),
+        None => Ok(false),
+    }
+}
+
+/// Metadata hash from the `// lpkg-hash:` header of a generated `mod.rs`, if the module exists
+/// and has one.
+fn recorded_hash(module_path: &Path) -> Result<Option<String>> {
+    if !module_path.exists() {
+        return Ok(None);
+    }
+    let module = fs::read_to_string(module_path)
+        .with_context(|| format!("reading generated module {}", module_path.display()))?;
+    Ok(module
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(scaffolder::HASH_COMMENT_PREFIX))
+        .map(|hash| hash.trim().to_string()))
+}
+
+/// Hex-encoded SHA-256 of a metadata file.
+fn metadata_hash(path: &Path) -> Result<String> {
+    let bytes =
+        fs::read(path).with_context(|| format!("reading metadata file {}", path.display()))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
 }
 
 /// Compute the directory for a module derived from the given metadata.
@@ -123,7 +218,7 @@ pub fn module_directory(
     let module = sanitize_module_name(&slug);
     let dir = base_dir
         .as_ref()
-        .join(prefix_from_module(&module))
+        .join(scaffolder::prefix_for_module(&module))
         .join(module);
     Ok(dir)
 }
@@ -165,6 +260,7 @@ fn build_request(pkg: &HarvestedPackage) -> Result<ScaffoldRequest> {
             .find(|c| c.alg.eq_ignore_ascii_case("md5"))
             .map(|c| c.value.clone()),
         configure_args: Vec::new(),
+        patch_commands: Vec::new(),
         build_commands,
         install_commands,
         dependencies,
@@ -173,10 +269,15 @@ fn build_request(pkg: &HarvestedPackage) -> Result<ScaffoldRequest> {
         cflags: pkg.optimizations.cflags.clone(),
         ldflags: pkg.optimizations.ldflags.clone(),
         profdata: pkg.optimizations.profdata.clone(),
+        preset: None,
+        strict_flags: false,
+        target_triple: None,
+        cmake_toolchain: false,
         stage: pkg.package.stage.clone(),
         variant: pkg.package.variant.clone(),
         notes: pkg.package.notes.clone(),
         module_override: Some(slug),
+        source_hash: None,
     };
 
     Ok(request)
@@ -194,20 +295,44 @@ fn module_override_from_id(id: &str) -> Option<String> {
         Some((_, slug)) => slug,
         None => id,
     };
-    Some(
-        slug.replace('.', "_")
-            .replace('/', "_")
-            .replace('-', "_")
-            .replace(' ', "_")
-            .to_lowercase(),
-    )
+    Some(slug.replace(['.', '/', '-', ' '], "_").to_lowercase())
 }
 
 fn parse_metadata(path: &Path) -> Result<HarvestedPackage> {
     let metadata = fs::read_to_string(path)
         .with_context(|| format!("reading metadata file {}", path.display()))?;
-    let harvested: HarvestedPackage = serde_json::from_str(&metadata)
-        .with_context(|| format!("parsing harvested metadata from {}", path.display()))?;
+    let value: Value =
+        serde_json::from_str(&metadata).map_err(|source| MetadataParseError::Malformed {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    for field in REQUIRED_FIELDS {
+        let pointer = format!("/{}", field.replace('.', "/"));
+        match value.pointer(&pointer) {
+            None | Some(Value::Null) => {
+                return Err(MetadataParseError::MissingField {
+                    path: path.to_path_buf(),
+                    field,
+                }
+                .into());
+            }
+            Some(Value::String(s)) if !s.trim().is_empty() => {}
+            Some(_) => {
+                return Err(MetadataParseError::InvalidField {
+                    path: path.to_path_buf(),
+                    field,
+                }
+                .into());
+            }
+        }
+    }
+
+    let harvested =
+        serde_json::from_value(value).map_err(|source| MetadataParseError::Malformed {
+            path: path.to_path_buf(),
+            source,
+        })?;
     Ok(harvested)
 }
 
@@ -216,8 +341,6 @@ fn sanitize_module_name(name: &str) -> String {
     for ch in name.chars() {
         if ch.is_ascii_alphanumeric() {
             out.push(ch.to_ascii_lowercase());
-        } else if ch == '_' || ch == '+' || ch == '-' {
-            out.push('_');
         } else {
             out.push('_');
         }
@@ -236,12 +359,50 @@ fn sanitize_module_name(name: &str) -> String {
     out
 }
 
-fn prefix_from_module(module: &str) -> String {
-    let mut chars = module.chars();
-    let first = chars.next().unwrap_or('p');
-    let second = chars.next().unwrap_or('k');
-    let mut s = String::new();
-    s.push(first);
-    s.push(second);
-    s
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lpkg-generator-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("by_name")).unwrap();
+        dir
+    }
+
+    fn write_metadata(path: &Path, version: &str) {
+        let metadata = serde_json::json!({
+            "package": { "id": "mlfs/zlib", "name": "zlib", "version": version },
+            "source": { "urls": [{ "url": format!("https://zlib.net/zlib-{version}.tar.xz") }] },
+            "build": [{ "commands": ["./configure --prefix=/usr", "make", "make install"] }],
+            "optimizations": { "enable_lto": true, "enable_pgo": false },
+        });
+        fs::write(path, metadata.to_string()).unwrap();
+    }
+
+    #[test]
+    fn changed_metadata_regenerates_the_module() {
+        let dir = scratch_dir("regenerate");
+        let base = dir.join("by_name");
+        let metadata = dir.join("zlib.json");
+
+        write_metadata(&metadata, "1.3");
+        let first = generate_module(&metadata, &base).unwrap();
+        assert!(matches!(first, GenerateOutcome::Generated(_)));
+        assert!(matches!(
+            generate_module(&metadata, &base).unwrap(),
+            GenerateOutcome::Unchanged(_)
+        ));
+
+        write_metadata(&metadata, "1.3.1");
+        let second = generate_module(&metadata, &base).unwrap();
+        assert_eq!(
+            second,
+            GenerateOutcome::Generated(first.module_path().to_path_buf())
+        );
+        let source = fs::read_to_string(second.module_path()).unwrap();
+        assert!(source.contains("1.3.1"));
+        assert!(module_is_up_to_date(&metadata, second.module_path()).unwrap());
+    }
 }
@@ -1,10 +1,15 @@
-use std::{borrow::Cow, fs, path::PathBuf};
+use std::{
+    borrow::Cow,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 
-use crate::ingest::{BookKind, BookPackage, FetchOptions, lfs};
+use crate::ingest::{BookDependency, BookKind, BookPackage, FetchOptions, lfs};
 use crate::pkgs::package::PackageDefinition;
+use crate::pkgs::scaffolder::is_rust_keyword;
 
 pub const DEFAULT_MLFS_BASE_URL: &str = "https://linuxfromscratch.org/~thomas/multilib-m32";
 
@@ -20,6 +25,12 @@ pub struct MlfsPackageRecord {
     pub variant: Option<String>,
     #[serde(default)]
     pub notes: Option<String>,
+    #[serde(default)]
+    pub md5: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<BookDependency>,
 }
 
 impl MlfsPackageRecord {
@@ -32,12 +43,19 @@ impl MlfsPackageRecord {
         id
     }
 
+    /// Module-safe alias; Perl-style `XML::Parser` becomes `xml__parser` and keywords such
+    /// as `as` get a `_pkg` suffix.
     pub fn module_alias(&self) -> String {
-        self.id()
-            .replace('.', "_")
-            .replace('/', "_")
-            .replace(' ', "_")
-            .to_lowercase()
+        let alias = self
+            .id()
+            .replace("::", "__")
+            .replace(['.', '/', ' '], "_")
+            .to_lowercase();
+        if is_rust_keyword(&alias) {
+            format!("{alias}_pkg")
+        } else {
+            alias
+        }
     }
 
     pub fn display_label(&self) -> Cow<'_, str> {
@@ -51,24 +69,32 @@ impl MlfsPackageRecord {
 
     pub fn to_package_definition(&self) -> PackageDefinition {
         let mut pkg = PackageDefinition::new(&self.name, &self.version);
-        if let Some(stage) = &self.stage {
-            pkg.optimizations
-                .cflags
-                .push(format!("-DLPKG_STAGE={}", stage.to_uppercase()));
-        }
-        if let Some(variant) = &self.variant {
-            pkg.optimizations
-                .cflags
-                .push(format!("-DLPKG_VARIANT={}", variant.to_uppercase()));
-        }
-        if let Some(notes) = &self.notes {
-            pkg.optimizations
-                .cflags
-                .push(format!("-DLPKG_NOTES={}", notes.replace(' ', "_")));
-        }
+        pkg.stage = self.stage.clone();
+        pkg.variant = self.variant.clone();
+        pkg.notes = self.notes.clone();
+        pkg.md5 = self.md5.clone();
+        pkg.source = self.source.clone();
         pkg
     }
 
+    /// Book view of the record, as returned by the other ingest backends.
+    pub fn to_book_package(&self) -> BookPackage {
+        BookPackage {
+            book: BookKind::Mlfs,
+            chapter: self.chapter,
+            section: self.section.clone(),
+            name: self.name.clone(),
+            version: Some(self.version.clone()),
+            href: None,
+            md5: self.md5.clone(),
+            primary_source_url: self.source.clone(),
+            stage: self.stage.clone(),
+            variant: self.variant.clone(),
+            notes: self.notes.clone(),
+            dependencies: self.dependencies.clone(),
+        }
+    }
+
     fn from_book_package(pkg: BookPackage) -> Option<Self> {
         let version = pkg.version?;
         Some(Self {
@@ -79,6 +105,9 @@ impl MlfsPackageRecord {
             stage: pkg.stage,
             variant: pkg.variant,
             notes: pkg.notes,
+            md5: pkg.md5,
+            source: pkg.primary_source_url,
+            dependencies: pkg.dependencies,
         })
     }
 }
@@ -107,6 +136,7 @@ pub struct MlfsMetadataEntry {
     pub path: PathBuf,
 }
 
+/// Parse the MLFS book at `base_url`; `file://` URLs read a local copy (see [`lfs::fetch_book`]).
 pub fn fetch_catalog(base_url: &str) -> Result<Vec<MlfsPackageRecord>> {
     let options = FetchOptions::new(base_url, BookKind::Mlfs);
     let packages = lfs::fetch_book(&options)?;
@@ -121,17 +151,48 @@ pub fn fetch_catalog(base_url: &str) -> Result<Vec<MlfsPackageRecord>> {
     Ok(records)
 }
 
+/// Environment variable naming a catalogue JSON file that replaces the bundled copy.
+pub const MLFS_CACHE_ENV: &str = "LPKG_MLFS_CACHE";
+
+/// The offline catalogue: the file named by `LPKG_MLFS_CACHE` when set, otherwise the copy
+/// compiled into the binary.
 pub fn load_cached_catalog() -> Result<Vec<MlfsPackageRecord>> {
-    let raw = include_str!("../../data/mlfs_ml-12.4-40-multilib.json");
+    match env::var_os(MLFS_CACHE_ENV).filter(|value| !value.is_empty()) {
+        Some(path) => load_cached_catalog_from(Some(Path::new(&path)))
+            .with_context(|| format!("loading the catalogue named by {MLFS_CACHE_ENV}")),
+        None => load_cached_catalog_from(None),
+    }
+}
+
+/// Same as [`load_cached_catalog`], reading the catalogue from `path` instead of the
+/// environment; `None` selects the bundled copy.
+pub fn load_cached_catalog_from(path: Option<&Path>) -> Result<Vec<MlfsPackageRecord>> {
+    let raw = match path {
+        Some(path) => Cow::Owned(
+            fs::read_to_string(path)
+                .with_context(|| format!("reading MLFS catalogue {}", path.display()))?,
+        ),
+        None => Cow::Borrowed(include_str!("../../data/mlfs_ml-12.4-40-multilib.json")),
+    };
     let records: Vec<MlfsPackageRecord> =
-        serde_json::from_str(raw).context("parsing cached MLFS package manifest")?;
+        serde_json::from_str(&raw).context("parsing cached MLFS package manifest")?;
     Ok(records)
 }
 
-pub fn load_or_fetch_catalog(base_url: Option<&str>) -> Result<Vec<MlfsPackageRecord>> {
+/// Fetch the live catalogue, falling back to the bundled copy on network errors (including
+/// timeouts) unless `fallback` is `false`.
+pub fn load_or_fetch_catalog(
+    base_url: Option<&str>,
+    fallback: bool,
+) -> Result<Vec<MlfsPackageRecord>> {
     let base = base_url.unwrap_or(DEFAULT_MLFS_BASE_URL);
     match fetch_catalog(base) {
         Ok(records) => Ok(records),
+        Err(err) if !fallback => Err(err),
+        Err(err) if is_timeout(&err) => {
+            tracing::warn!("timed out fetching MLFS book from {base}; using cached package list");
+            load_cached_catalog()
+        }
         Err(err) => {
             eprintln!("[mlfs] fetch error ({err}); falling back to cached MLFS package list");
             load_cached_catalog()
@@ -139,6 +200,15 @@ pub fn load_or_fetch_catalog(base_url: Option<&str>) -> Result<Vec<MlfsPackageRe
     }
 }
 
+fn is_timeout(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|io_err| io_err.kind() == io::ErrorKind::TimedOut)
+            || cause.to_string().contains("timed out")
+    })
+}
+
 pub fn load_metadata_index() -> Result<Vec<MlfsMetadataEntry>> {
     let raw = fs::read_to_string("ai/metadata/index.json").context("reading AI metadata index")?;
     let index: MetadataIndex =
@@ -173,16 +243,12 @@ pub fn match_metadata<'a>(
         .filter(|entry| normalize(&entry.name) == target_name)
         .max_by_key(|entry| {
             let mut score = 0;
-            if let (Some(tv), Some(ev)) = (&target_variant, normalize_opt(entry.variant.as_deref()))
+            if target_variant.is_some() && normalize_opt(entry.variant.as_deref()) == target_variant
             {
-                if *tv == ev {
-                    score += 4;
-                }
+                score += 4;
             }
-            if let (Some(ts), Some(es)) = (&target_stage, normalize_opt(entry.stage.as_deref())) {
-                if *ts == es {
-                    score += 2;
-                }
+            if target_stage.is_some() && normalize_opt(entry.stage.as_deref()) == target_stage {
+                score += 2;
             }
             if target_variant.is_none() && entry.variant.is_none() {
                 score += 1;
@@ -205,3 +271,64 @@ fn normalize(value: &str) -> String {
 fn normalize_opt(value: Option<&str>) -> Option<String> {
     value.map(normalize)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str) -> MlfsPackageRecord {
+        MlfsPackageRecord {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            chapter: None,
+            section: None,
+            stage: None,
+            variant: None,
+            notes: None,
+            md5: None,
+            source: None,
+            dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn module_alias_avoids_keywords() {
+        assert_eq!(record("as").module_alias(), "as_pkg");
+        assert_eq!(record("XML::Parser").module_alias(), "xml__parser");
+    }
+
+    #[test]
+    fn fetch_catalog_reads_file_uri() {
+        let base = format!("file://{}/tests/fixtures/mlfs", env!("CARGO_MANIFEST_DIR"));
+        let records = fetch_catalog(&base).unwrap();
+        let names: Vec<_> = records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Binutils", "GCC", "Glibc"]);
+        assert_eq!(records[0].version, "2.45");
+        assert_eq!(records[0].variant.as_deref(), Some("Pass 1"));
+        assert_eq!(
+            records[0].md5.as_deref(),
+            Some("dee5b4267e0305a99a3c9d6131f45759")
+        );
+        assert_eq!(records[2].stage.as_deref(), Some("system"));
+    }
+
+    #[test]
+    fn cached_catalog_reads_override_path() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mlfs/catalog.json");
+        let records = load_cached_catalog_from(Some(&fixture)).unwrap();
+        let names: Vec<_> = records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["binutils", "zlib"]);
+        assert_eq!(records[0].version, "2.46");
+        assert_eq!(records[1].stage.as_deref(), Some("system"));
+
+        // Without an override the bundled catalogue is used.
+        assert!(load_cached_catalog_from(None).unwrap().len() > records.len());
+    }
+
+    #[test]
+    fn fetch_catalog_reports_missing_file() {
+        let err = fetch_catalog("file:///nonexistent/lpkg-fixture").unwrap_err();
+        assert!(format!("{err:#}").contains("book.html"));
+    }
+}
@@ -2,4 +2,5 @@ pub mod by_name;
 pub mod generator;
 pub mod mlfs;
 pub mod package;
+pub mod registry;
 pub mod scaffolder;
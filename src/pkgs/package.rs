@@ -1,3 +1,7 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use console::Style;
 use serde::{Deserialize, Serialize};
 
 /// High-level description of a package managed by LPKG.
@@ -7,11 +11,28 @@ pub struct PackageDefinition {
     pub version: String,
     pub source: Option<String>,
     pub md5: Option<String>,
+    #[serde(default)]
     pub configure_args: Vec<String>,
+    /// `patch` invocations applied to the unpacked source before `build_commands`.
+    #[serde(default)]
+    pub patch_commands: Vec<String>,
+    #[serde(default)]
     pub build_commands: Vec<String>,
+    #[serde(default)]
     pub install_commands: Vec<String>,
+    #[serde(default)]
     pub dependencies: Vec<String>,
+    #[serde(default)]
     pub optimizations: OptimizationSettings,
+    /// Book stage the package belongs to (e.g. `cross-toolchain`).
+    #[serde(default)]
+    pub stage: Option<String>,
+    /// Variant of a package built more than once (e.g. `Pass 1`).
+    #[serde(default)]
+    pub variant: Option<String>,
+    /// Free-form notes carried over from the book (not passed to the compiler).
+    #[serde(default)]
+    pub notes: Option<String>,
 }
 
 impl PackageDefinition {
@@ -22,16 +43,249 @@ impl PackageDefinition {
             source: None,
             md5: None,
             configure_args: Vec::new(),
+            patch_commands: Vec::new(),
             build_commands: Vec::new(),
             install_commands: Vec::new(),
             dependencies: Vec::new(),
             optimizations: OptimizationSettings::default(),
+            stage: None,
+            variant: None,
+            notes: None,
+        }
+    }
+
+    /// Like [`PackageDefinition::new`], but with optimizations taken from `preset`.
+    pub fn new_with_defaults(name: &str, version: &str, preset: OptimizationPreset) -> Self {
+        let mut pkg = Self::new(name, version);
+        pkg.optimizations = OptimizationSettings::from_preset(preset);
+        pkg
+    }
+}
+
+/// Serialize a package definition to a TOML document.
+pub fn to_toml(pkg: &PackageDefinition) -> Result<String> {
+    toml::to_string_pretty(pkg).context("serializing package definition to TOML")
+}
+
+/// Parse a package definition from a TOML document.
+pub fn from_toml(s: &str) -> Result<PackageDefinition> {
+    toml::from_str(s).context("parsing package definition from TOML")
+}
+
+/// Render a package as a standalone bash build script.
+pub fn to_shell_script(pkg: &PackageDefinition) -> String {
+    let mut script = String::new();
+    script.push_str("#!/usr/bin/env bash\n");
+    script.push_str(&format!(
+        "# Build script for {} {}\n",
+        pkg.name, pkg.version
+    ));
+    script.push_str("set -euo pipefail\n\n");
+
+    script.push_str("export LFS=\"${LFS:-/mnt/lfs}\"\n");
+    script.push_str("export LFS_TGT=\"${LFS_TGT:-x86_64-lfs-linux-gnu}\"\n");
+    script.push_str("export MAKEFLAGS=\"${MAKEFLAGS:--j$(nproc)}\"\n\n");
+
+    script.push_str(&format!(
+        "export CFLAGS=\"{}\"\n",
+        pkg.optimizations.cflags.join(" ")
+    ));
+    script.push_str(&format!(
+        "export CXXFLAGS=\"{}\"\n",
+        pkg.optimizations.cflags.join(" ")
+    ));
+    script.push_str(&format!(
+        "export LDFLAGS=\"{}\"\n",
+        pkg.optimizations.ldflags.join(" ")
+    ));
+
+    if let Some(source) = &pkg.source {
+        script.push_str(&format!("\n# Source: {source}\n"));
+    }
+
+    if !pkg.patch_commands.is_empty() {
+        script.push('\n');
+        for command in &pkg.patch_commands {
+            script.push_str(command);
+            script.push('\n');
+        }
+    }
+
+    if !pkg.configure_args.is_empty() {
+        script.push_str("\n./configure");
+        for arg in &pkg.configure_args {
+            script.push_str(" \\\n    ");
+            script.push_str(arg);
+        }
+        script.push('\n');
+    }
+
+    if !pkg.build_commands.is_empty() {
+        script.push('\n');
+        for command in &pkg.build_commands {
+            script.push_str(command);
+            script.push('\n');
+        }
+    }
+
+    if !pkg.install_commands.is_empty() {
+        script.push('\n');
+        for command in &pkg.install_commands {
+            script.push_str(command);
+            script.push('\n');
         }
     }
+
+    script
+}
+
+/// A single list-field modification between two package definitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+}
+
+/// Field-by-field difference between two package definitions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageDiff {
+    pub name: Option<(String, String)>,
+    pub version: Option<(String, String)>,
+    pub source: Option<(String, String)>,
+    pub md5: Option<(String, String)>,
+    pub enable_lto: Option<(String, String)>,
+    pub enable_pgo: Option<(String, String)>,
+    pub profdata: Option<(String, String)>,
+    pub stage: Option<(String, String)>,
+    pub variant: Option<(String, String)>,
+    pub notes: Option<(String, String)>,
+    pub list_changes: Vec<(String, Change)>,
+}
+
+impl PackageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.scalar_changes().next().is_none() && self.list_changes.is_empty()
+    }
+
+    fn scalar_changes(&self) -> impl Iterator<Item = (&'static str, &(String, String))> {
+        [
+            ("name", &self.name),
+            ("version", &self.version),
+            ("source", &self.source),
+            ("md5", &self.md5),
+            ("enable_lto", &self.enable_lto),
+            ("enable_pgo", &self.enable_pgo),
+            ("profdata", &self.profdata),
+            ("stage", &self.stage),
+            ("variant", &self.variant),
+            ("notes", &self.notes),
+        ]
+        .into_iter()
+        .filter_map(|(field, change)| change.as_ref().map(|change| (field, change)))
+    }
+}
+
+impl fmt::Display for PackageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let removed = Style::new().red();
+        let added = Style::new().green();
+
+        for (field, (old, new)) in self.scalar_changes() {
+            writeln!(f, "{}", removed.apply_to(format!("-{field}: {old}")))?;
+            writeln!(f, "{}", added.apply_to(format!("+{field}: {new}")))?;
+        }
+        for (field, change) in &self.list_changes {
+            match change {
+                Change::Removed(value) => {
+                    writeln!(f, "{}", removed.apply_to(format!("-{field}: {value}")))?
+                }
+                Change::Added(value) => {
+                    writeln!(f, "{}", added.apply_to(format!("+{field}: {value}")))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compute which fields changed between `old` and `new`.
+pub fn diff(old: &PackageDefinition, new: &PackageDefinition) -> PackageDiff {
+    fn scalar<T: PartialEq + fmt::Debug>(old: &T, new: &T) -> Option<(String, String)> {
+        (old != new).then(|| (format!("{old:?}"), format!("{new:?}")))
+    }
+    fn text(old: &str, new: &str) -> Option<(String, String)> {
+        (old != new).then(|| (old.to_string(), new.to_string()))
+    }
+    fn list(field: &str, old: &[String], new: &[String], out: &mut Vec<(String, Change)>) {
+        for value in old.iter().filter(|value| !new.contains(value)) {
+            out.push((field.to_string(), Change::Removed(value.clone())));
+        }
+        for value in new.iter().filter(|value| !old.contains(value)) {
+            out.push((field.to_string(), Change::Added(value.clone())));
+        }
+    }
+
+    let mut list_changes = Vec::new();
+    list(
+        "configure_args",
+        &old.configure_args,
+        &new.configure_args,
+        &mut list_changes,
+    );
+    list(
+        "patch_commands",
+        &old.patch_commands,
+        &new.patch_commands,
+        &mut list_changes,
+    );
+    list(
+        "build_commands",
+        &old.build_commands,
+        &new.build_commands,
+        &mut list_changes,
+    );
+    list(
+        "install_commands",
+        &old.install_commands,
+        &new.install_commands,
+        &mut list_changes,
+    );
+    list(
+        "dependencies",
+        &old.dependencies,
+        &new.dependencies,
+        &mut list_changes,
+    );
+    list(
+        "cflags",
+        &old.optimizations.cflags,
+        &new.optimizations.cflags,
+        &mut list_changes,
+    );
+    list(
+        "ldflags",
+        &old.optimizations.ldflags,
+        &new.optimizations.ldflags,
+        &mut list_changes,
+    );
+
+    PackageDiff {
+        name: text(&old.name, &new.name),
+        version: text(&old.version, &new.version),
+        source: scalar(&old.source, &new.source),
+        md5: scalar(&old.md5, &new.md5),
+        enable_lto: scalar(&old.optimizations.enable_lto, &new.optimizations.enable_lto),
+        enable_pgo: scalar(&old.optimizations.enable_pgo, &new.optimizations.enable_pgo),
+        profdata: scalar(&old.optimizations.profdata, &new.optimizations.profdata),
+        stage: scalar(&old.stage, &new.stage),
+        variant: scalar(&old.variant, &new.variant),
+        notes: scalar(&old.notes, &new.notes),
+        list_changes,
+    }
 }
 
 /// Tunable compiler and linker flags applied during package builds.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OptimizationSettings {
     pub enable_lto: bool,
     pub enable_pgo: bool,
@@ -56,7 +310,211 @@ impl Default for OptimizationSettings {
     }
 }
 
+/// Two compiler flags that should not be combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagConflict {
+    pub flags: (String, String),
+    pub reason: String,
+}
+
+/// Report incompatible flag pairs in `cflags` and `ldflags`.
+pub fn detect_flag_conflicts(settings: &OptimizationSettings) -> Vec<FlagConflict> {
+    let mut conflicts = Vec::new();
+    for flags in [&settings.cflags, &settings.ldflags] {
+        let opt_levels = flags
+            .iter()
+            .filter(|flag| flag.starts_with("-O"))
+            .collect::<Vec<_>>();
+        for pair in opt_levels.windows(2) {
+            if pair[0] != pair[1] {
+                conflicts.push(FlagConflict {
+                    flags: (pair[0].clone(), pair[1].clone()),
+                    reason: "multiple optimization levels; only the last one takes effect"
+                        .to_string(),
+                });
+            }
+        }
+
+        let has = |prefix: &str| {
+            flags
+                .iter()
+                .find(|flag| *flag == prefix || flag.starts_with(&format!("{prefix}=")))
+        };
+        if let (Some(generate), Some(use_)) = (has("-fprofile-generate"), has("-fprofile-use")) {
+            conflicts.push(FlagConflict {
+                flags: (generate.clone(), use_.clone()),
+                reason: "profile instrumentation and profile replay cannot be combined".to_string(),
+            });
+        }
+        if let (Some(lto), Some(no_lto)) = (has("-flto"), has("-fno-lto")) {
+            conflicts.push(FlagConflict {
+                flags: (lto.clone(), no_lto.clone()),
+                reason: "LTO is both enabled and disabled".to_string(),
+            });
+        }
+    }
+    conflicts
+}
+
+/// A likely mistake in a package's build or install commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildCommandWarning {
+    pub command: String,
+    pub warning: String,
+}
+
+/// Report common shell mistakes in `build_commands` and `install_commands`.
+pub fn validate_build_commands(pkg: &PackageDefinition) -> Vec<BuildCommandWarning> {
+    let mut warnings = Vec::new();
+    let mut warn = |command: &str, warning: &str| {
+        warnings.push(BuildCommandWarning {
+            command: command.to_string(),
+            warning: warning.to_string(),
+        })
+    };
+
+    for command in &pkg.build_commands {
+        if command.contains("make install") {
+            warn(command, "`make install` belongs in install_commands");
+        }
+    }
+
+    for commands in [&pkg.build_commands, &pkg.install_commands] {
+        for (idx, command) in commands.iter().enumerate() {
+            let trimmed = command.trim();
+            // Continuations are fine as long as another command follows them.
+            let next = commands.get(idx + 1).map(|next| next.trim());
+            if trimmed.ends_with('\\') && next.is_none_or(|next| next.is_empty()) {
+                warn(command, "line continuation `\\` with nothing following it");
+            }
+            if trimmed.matches("$(").count() > trimmed.matches(')').count() {
+                warn(command, "unbalanced `$(` command substitution");
+            }
+            if trimmed.starts_with('#') {
+                warn(command, "command is commented out");
+            }
+        }
+    }
+    warnings
+}
+
+fn is_patch_command(command: &str) -> bool {
+    command.trim_start().starts_with("patch ")
+}
+
+/// The `patch ...` lines mixed into `build_commands`.
+pub fn extract_patch_commands(pkg: &PackageDefinition) -> Vec<String> {
+    pkg.build_commands
+        .iter()
+        .filter(|command| is_patch_command(command))
+        .cloned()
+        .collect()
+}
+
+/// Move `patch ...` lines from `build_commands` to the end of `patch_commands`.
+pub fn apply_patch_commands_to_build(mut pkg: PackageDefinition) -> PackageDefinition {
+    let (patches, build): (Vec<String>, Vec<String>) = pkg
+        .build_commands
+        .into_iter()
+        .partition(|command| is_patch_command(command));
+    pkg.patch_commands.extend(patches);
+    pkg.build_commands = build;
+    pkg
+}
+
+/// `-march`/`-mtune` flags for the CPU family named in a GNU target triple.
+pub fn architecture_flags(target: &str) -> Vec<String> {
+    let arch = target.split('-').next().unwrap_or(target);
+    let flags: &[&str] = match arch {
+        "x86_64" => &["-march=x86-64-v3", "-mtune=generic"],
+        "aarch64" => &["-march=armv8-a+crypto"],
+        "riscv64" => &["-march=rv64gc"],
+        _ => &[],
+    };
+    flags.iter().map(|flag| flag.to_string()).collect()
+}
+
+/// Render a CMake toolchain file applying `settings` when building for `target`.
+pub fn to_cmake_toolchain_file(settings: &OptimizationSettings, target: &str) -> String {
+    let arch = target.split('-').next().unwrap_or(target);
+    let cflags = settings.cflags.join(" ");
+    let ldflags = settings.ldflags.join(" ");
+    let build_type = if settings.cflags.iter().any(|flag| flag == "-g") {
+        "RelWithDebInfo"
+    } else {
+        "Release"
+    };
+    let ipo = if settings.enable_lto { "ON" } else { "OFF" };
+
+    format!(
+        "# CMake toolchain generated by lpkg for {target}\n\
+         set(CMAKE_SYSTEM_NAME Linux)\n\
+         set(CMAKE_SYSTEM_PROCESSOR {arch})\n\
+         \n\
+         set(CMAKE_C_FLAGS \"{cflags}\" CACHE STRING \"\" FORCE)\n\
+         set(CMAKE_CXX_FLAGS \"{cflags}\" CACHE STRING \"\" FORCE)\n\
+         set(CMAKE_EXE_LINKER_FLAGS \"{ldflags}\" CACHE STRING \"\" FORCE)\n\
+         set(CMAKE_BUILD_TYPE {build_type} CACHE STRING \"\" FORCE)\n\
+         set(CMAKE_INTERPROCEDURAL_OPTIMIZATION {ipo})\n"
+    )
+}
+
+/// Named optimization levels offered when scaffolding packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OptimizationPreset {
+    /// `-O2` without LTO or PGO.
+    Conservative,
+    /// `-O2` with ThinLTO.
+    Balanced,
+    /// `-O3` with full LTO.
+    Aggressive,
+    /// `-O3` with full LTO and PGO instrumentation.
+    PgoFull,
+}
+
 impl OptimizationSettings {
+    /// Default settings tuned for the given GNU target triple.
+    pub fn for_architecture(target: &str) -> Self {
+        Self::default().with_architecture(target)
+    }
+
+    /// Replace any `-march`/`-mtune` flags with ones derived from the target triple.
+    pub fn with_architecture(mut self, target: &str) -> Self {
+        self.cflags
+            .retain(|flag| !flag.starts_with("-march=") && !flag.starts_with("-mtune="));
+        let insert_at = self
+            .cflags
+            .iter()
+            .position(|flag| flag.starts_with("-O"))
+            .map_or(0, |idx| idx + 1);
+        for (offset, flag) in architecture_flags(target).into_iter().enumerate() {
+            self.cflags.insert(insert_at + offset, flag);
+        }
+        self
+    }
+
+    pub fn from_preset(preset: OptimizationPreset) -> Self {
+        let (enable_lto, enable_pgo, cflags, ldflags): (_, _, &[&str], &[&str]) = match preset {
+            OptimizationPreset::Conservative => (false, false, &["-O2"], &[]),
+            OptimizationPreset::Balanced => (true, false, &["-O2", "-flto=thin"], &["-flto=thin"]),
+            OptimizationPreset::Aggressive => (true, false, &["-O3", "-flto"], &["-flto"]),
+            OptimizationPreset::PgoFull => (
+                true,
+                true,
+                &["-O3", "-flto", "-fprofile-generate"],
+                &["-flto", "-fprofile-generate"],
+            ),
+        };
+        Self {
+            enable_lto,
+            enable_pgo,
+            cflags: cflags.iter().map(|flag| flag.to_string()).collect(),
+            ldflags: ldflags.iter().map(|flag| flag.to_string()).collect(),
+            profdata: None,
+        }
+    }
+
     /// Convenience helper for disabling instrumentation once profile data has been gathered.
     pub fn for_pgo_replay(profdata: impl Into<String>) -> Self {
         Self {
@@ -72,3 +530,84 @@ impl OptimizationSettings {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glibc() -> PackageDefinition {
+        let mut pkg = PackageDefinition::new("glibc", "2.42");
+        pkg.build_commands = vec![
+            "patch -Np1 -i ../glibc-2.42-fhs-1.patch".to_string(),
+            "mkdir -v build".to_string(),
+            "make".to_string(),
+        ];
+        pkg
+    }
+
+    #[test]
+    fn patch_commands_are_moved_out_of_build_commands() {
+        let pkg = glibc();
+        assert_eq!(
+            extract_patch_commands(&pkg),
+            vec!["patch -Np1 -i ../glibc-2.42-fhs-1.patch".to_string()]
+        );
+
+        let pkg = apply_patch_commands_to_build(pkg);
+        assert_eq!(pkg.patch_commands, extract_patch_commands(&glibc()));
+        assert_eq!(pkg.build_commands, vec!["mkdir -v build", "make"]);
+        assert!(extract_patch_commands(&pkg).is_empty());
+    }
+
+    #[test]
+    fn shell_script_applies_patches_before_building() {
+        let script = to_shell_script(&apply_patch_commands_to_build(glibc()));
+        let patch = script.find("patch -Np1").expect("patch command in script");
+        let build = script
+            .find("mkdir -v build")
+            .expect("build command in script");
+        assert!(patch < build);
+    }
+
+    #[test]
+    fn diff_reports_patch_command_changes() {
+        let old = apply_patch_commands_to_build(glibc());
+        let mut new = old.clone();
+        new.patch_commands = vec!["patch -Np1 -i ../glibc-2.42-upstream_fix-1.patch".to_string()];
+
+        let changes = diff(&old, &new).list_changes;
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "patch_commands".to_string(),
+                    Change::Removed("patch -Np1 -i ../glibc-2.42-fhs-1.patch".to_string())
+                ),
+                (
+                    "patch_commands".to_string(),
+                    Change::Added("patch -Np1 -i ../glibc-2.42-upstream_fix-1.patch".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_reports_book_metadata_changes() {
+        let old = glibc();
+        let mut new = old.clone();
+        new.stage = Some("cross-toolchain".to_string());
+        new.variant = Some("Pass 1".to_string());
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.stage,
+            Some(("None".to_string(), "Some(\"cross-toolchain\")".to_string()))
+        );
+        assert_eq!(
+            diff.variant,
+            Some(("None".to_string(), "Some(\"Pass 1\")".to_string()))
+        );
+        assert_eq!(diff.notes, None);
+        assert!(diff.list_changes.is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+// Generated by `lpkg workflow rebuild-index`; do not edit by hand.
+
+use crate::pkgs::package::PackageDefinition;
+
+/// Every package definition embedded under `crate::pkgs::by_name`.
+pub fn all_definitions() -> Vec<PackageDefinition> {
+    vec![
+        crate::pkgs::by_name::bi::binutils_pass_1::definition(),
+        crate::pkgs::by_name::gc::gcc_pass_1::definition(),
+        crate::pkgs::by_name::gl::glibc::definition(),
+        crate::pkgs::by_name::li::linux::definition(),
+    ]
+}
@@ -1,10 +1,26 @@
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
 
 use anyhow::{Context, Result, anyhow};
 
-use crate::pkgs::package::{OptimizationSettings, PackageDefinition};
+use crate::pkgs::package::{
+    OptimizationPreset, OptimizationSettings, PackageDefinition, apply_patch_commands_to_build,
+    detect_flag_conflicts, to_cmake_toolchain_file, validate_build_commands,
+};
+
+const DEFAULT_TARGET_TRIPLE: &str = "x86_64-lfs-linux-gnu";
+
+/// Preset generated modules start from; only deviations from it are written out.
+const GENERATED_PRESET: OptimizationPreset = OptimizationPreset::Aggressive;
+
+/// Per-file locks serializing `mod.rs` appends when packages are scaffolded in parallel.
+static MOD_FILE_LOCKS: LazyLock<Mutex<BTreeMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// Prefix of the header line recording [`ScaffoldRequest::source_hash`] in a generated `mod.rs`.
+pub const HASH_COMMENT_PREFIX: &str = "// lpkg-hash: ";
 
 #[derive(Debug, Clone)]
 pub struct ScaffoldRequest {
@@ -13,6 +29,8 @@ pub struct ScaffoldRequest {
     pub source: Option<String>,
     pub md5: Option<String>,
     pub configure_args: Vec<String>,
+    /// `patch` invocations; any left in `build_commands` are moved here when scaffolding.
+    pub patch_commands: Vec<String>,
     pub build_commands: Vec<String>,
     pub install_commands: Vec<String>,
     pub dependencies: Vec<String>,
@@ -21,10 +39,51 @@ pub struct ScaffoldRequest {
     pub cflags: Vec<String>,
     pub ldflags: Vec<String>,
     pub profdata: Option<String>,
+    /// Preset used for any flag list left empty (falls back to `-O3 -flto` defaults).
+    pub preset: Option<OptimizationPreset>,
+    /// Treat conflicting compiler flags as an error instead of a warning.
+    pub strict_flags: bool,
+    /// GNU target triple used to derive `-march`/`-mtune` flags.
+    pub target_triple: Option<String>,
+    /// Also write a `toolchain.cmake` next to the generated module.
+    pub cmake_toolchain: bool,
     pub stage: Option<String>,
     pub variant: Option<String>,
     pub notes: Option<String>,
     pub module_override: Option<String>,
+    /// SHA-256 of the metadata file the request was built from, written as the first line of
+    /// the generated module so unchanged metadata can be detected later.
+    pub source_hash: Option<String>,
+}
+
+impl From<PackageDefinition> for ScaffoldRequest {
+    fn from(definition: PackageDefinition) -> Self {
+        Self {
+            name: definition.name,
+            version: definition.version,
+            source: definition.source,
+            md5: definition.md5,
+            configure_args: definition.configure_args,
+            patch_commands: definition.patch_commands,
+            build_commands: definition.build_commands,
+            install_commands: definition.install_commands,
+            dependencies: definition.dependencies,
+            enable_lto: definition.optimizations.enable_lto,
+            enable_pgo: definition.optimizations.enable_pgo,
+            cflags: definition.optimizations.cflags,
+            ldflags: definition.optimizations.ldflags,
+            profdata: definition.optimizations.profdata,
+            preset: None,
+            strict_flags: false,
+            target_triple: None,
+            cmake_toolchain: false,
+            stage: definition.stage,
+            variant: definition.variant,
+            notes: definition.notes,
+            module_override: None,
+            source_hash: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -46,7 +105,7 @@ pub fn scaffold_package(
 
     let module_source_name = request.module_override.as_deref().unwrap_or(&request.name);
     let module_name = sanitize(module_source_name);
-    let prefix = prefix(&module_name);
+    let prefix = prefix_for_module(&module_name);
 
     let prefix_dir = base_dir.join(&prefix);
     fs::create_dir_all(&prefix_dir)
@@ -66,11 +125,23 @@ pub fn scaffold_package(
         .with_context(|| format!("creating package directory {:?}", package_dir))?;
 
     let module_path = package_dir.join("mod.rs");
-    let definition = build_definition(&request);
-    let source = generate_module_source(&request, &definition);
+    let (definition, source) = render_definition(&request)?;
     fs::write(&module_path, source)
         .with_context(|| format!("writing module source to {:?}", module_path))?;
 
+    if request.cmake_toolchain {
+        let target = request
+            .target_triple
+            .as_deref()
+            .unwrap_or(DEFAULT_TARGET_TRIPLE);
+        let toolchain_path = package_dir.join("toolchain.cmake");
+        fs::write(
+            &toolchain_path,
+            to_cmake_toolchain_file(&definition.optimizations, target),
+        )
+        .with_context(|| format!("writing CMake toolchain to {:?}", toolchain_path))?;
+    }
+
     Ok(ScaffoldResult {
         module_path,
         prefix_module: prefix_mod,
@@ -79,45 +150,220 @@ pub fn scaffold_package(
     })
 }
 
+/// Render the `mod.rs` source [`scaffold_package`] would write for `request`, without touching
+/// the filesystem.
+pub fn render_module(request: &ScaffoldRequest) -> Result<String> {
+    render_definition(request).map(|(_, source)| source)
+}
+
+fn render_definition(request: &ScaffoldRequest) -> Result<(PackageDefinition, String)> {
+    let definition = build_definition(request)?;
+    let mut source = generate_module_source(request, &definition);
+    if let Some(hash) = &request.source_hash {
+        source.insert_str(0, &format!("{HASH_COMMENT_PREFIX}{hash}\n"));
+    }
+    Ok((definition, source))
+}
+
+/// Package modules found by [`rebuild_index`], as `(prefix, module)` pairs.
+#[derive(Debug, Clone)]
+pub struct IndexSummary {
+    pub modules: Vec<(String, String)>,
+    /// Modules exposing `definition()` and therefore listed in the registry.
+    pub registered: Vec<(String, String)>,
+    pub by_name_module: PathBuf,
+    pub registry_path: PathBuf,
+}
+
+/// Regenerate `by_name/mod.rs`, every prefix `mod.rs` and the registry of embedded package
+/// definitions from the `{prefix}/{module}/mod.rs` files present under `base_dir`.
+pub fn rebuild_index(
+    base_dir: impl AsRef<Path>,
+    registry_path: impl AsRef<Path>,
+) -> Result<IndexSummary> {
+    let base_dir = base_dir.as_ref();
+    if !base_dir.ends_with("by_name") {
+        return Err(anyhow!("expected base directory ending with 'by_name'"));
+    }
+
+    let mut tree: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut registered = Vec::new();
+    for prefix_dir in sorted_subdirs(base_dir)? {
+        let prefix = dir_name(&prefix_dir)?;
+        for package_dir in sorted_subdirs(&prefix_dir)? {
+            let module_file = package_dir.join("mod.rs");
+            if !module_file.is_file() {
+                continue;
+            }
+            let module = dir_name(&package_dir)?;
+            let source = fs::read_to_string(&module_file)
+                .with_context(|| format!("reading module file {:?}", module_file))?;
+            if source.contains("pub fn definition()") {
+                registered.push((prefix.clone(), module.clone()));
+            }
+            tree.entry(prefix.clone()).or_default().insert(module);
+        }
+    }
+
+    let mut by_name_source = String::new();
+    for (prefix, modules) in &tree {
+        by_name_source.push_str(&format!("pub mod {prefix};\n"));
+
+        let prefix_source: String = modules
+            .iter()
+            .map(|module| format!("pub mod {module};\n"))
+            .collect();
+        let prefix_mod = base_dir.join(prefix).join("mod.rs");
+        fs::write(&prefix_mod, prefix_source)
+            .with_context(|| format!("writing module file {:?}", prefix_mod))?;
+    }
+    let modules: Vec<(String, String)> = tree
+        .iter()
+        .flat_map(|(prefix, modules)| modules.iter().map(|m| (prefix.clone(), m.clone())))
+        .collect();
+    if !modules.is_empty() {
+        by_name_source.push('\n');
+    }
+    for (prefix, module) in &modules {
+        by_name_source.push_str(&format!("pub use self::{prefix}::{module};\n"));
+    }
+    let by_name_module = base_dir.join("mod.rs");
+    fs::write(&by_name_module, by_name_source)
+        .with_context(|| format!("writing module file {:?}", by_name_module))?;
+
+    let registry_path = registry_path.as_ref().to_path_buf();
+    fs::write(&registry_path, registry_source(&registered))
+        .with_context(|| format!("writing registry to {:?}", registry_path))?;
+
+    Ok(IndexSummary {
+        modules,
+        registered,
+        by_name_module,
+        registry_path,
+    })
+}
+
+fn registry_source(registered: &[(String, String)]) -> String {
+    let body = if registered.is_empty() {
+        "    Vec::new()\n".to_string()
+    } else {
+        let calls: String = registered
+            .iter()
+            .map(|(prefix, module)| {
+                format!("        crate::pkgs::by_name::{prefix}::{module}::definition(),\n")
+            })
+            .collect();
+        format!("    vec![\n{calls}    ]\n")
+    };
+    format!(
+        "// Generated by `lpkg workflow rebuild-index`; do not edit by hand.\n\n\
+         use crate::pkgs::package::PackageDefinition;\n\n\
+         /// Every package definition embedded under `crate::pkgs::by_name`.\n\
+         pub fn all_definitions() -> Vec<PackageDefinition> {{\n{body}}}\n"
+    )
+}
+
+fn sorted_subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading directory {:?}", dir))? {
+        let path = entry
+            .with_context(|| format!("reading directory {:?}", dir))?
+            .path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn dir_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("invalid module directory {:?}", path))
+}
+
+/// Path of the generated `mod.rs` for a module named `module_name` under `base_dir`.
+pub fn module_path(base_dir: impl AsRef<Path>, module_name: &str) -> PathBuf {
+    let module_name = sanitize(module_name);
+    base_dir
+        .as_ref()
+        .join(prefix_for_module(&module_name))
+        .join(&module_name)
+        .join("mod.rs")
+}
+
 fn ensure_mod_entry(path: &Path, module: &str) -> Result<()> {
+    let lock = MOD_FILE_LOCKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone();
+    let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+
     let entry = format!("pub mod {};", module);
     if path.exists() {
         let contents =
             fs::read_to_string(path).with_context(|| format!("reading module file {:?}", path))?;
-        if contents.contains(&entry) || contents.contains(&entry.trim()) {
+        if contents.lines().any(|line| line.trim() == entry) {
             return Ok(());
         }
-        let mut file = OpenOptions::new()
-            .append(true)
-            .open(path)
-            .with_context(|| format!("opening module file {:?}", path))?;
-        writeln!(file, "pub mod {};", module)
-            .with_context(|| format!("appending to module file {:?}", path))?;
+        let updated = sort_mod_entries(&format!("{contents}{entry}\n"));
+        fs::write(path, updated).with_context(|| format!("rewriting module file {:?}", path))?;
     } else {
-        fs::write(path, format!("pub mod {};\n", module))
+        fs::write(path, format!("{entry}\n"))
             .with_context(|| format!("creating module file {:?}", path))?;
     }
     Ok(())
 }
 
-fn build_definition(request: &ScaffoldRequest) -> PackageDefinition {
+/// Sort the `pub mod X;` lines of a module file. Lines before the first declaration stay on
+/// top, other lines keep their order after the sorted block.
+fn sort_mod_entries(contents: &str) -> String {
+    let is_mod_line = |line: &&str| line.trim_start().starts_with("pub mod ");
+    let lines: Vec<&str> = contents.lines().collect();
+    let first_mod = lines.iter().position(is_mod_line);
+    let (head, rest) = lines.split_at(first_mod.unwrap_or(lines.len()));
+
+    let mut mods: Vec<&str> = rest.iter().copied().filter(is_mod_line).collect();
+    mods.sort_unstable();
+    mods.dedup();
+    let tail = rest.iter().copied().filter(|line| !is_mod_line(line));
+
+    let mut out = String::new();
+    for line in head.iter().copied().chain(mods).chain(tail) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn build_definition(request: &ScaffoldRequest) -> Result<PackageDefinition> {
     let mut pkg = PackageDefinition::new(&request.name, &request.version);
     pkg.source = request.source.clone();
     pkg.md5 = request.md5.clone();
     pkg.configure_args = request.configure_args.clone();
+    pkg.patch_commands = request.patch_commands.clone();
     pkg.build_commands = request.build_commands.clone();
     pkg.install_commands = request.install_commands.clone();
     pkg.dependencies = request.dependencies.clone();
+    pkg.stage = request.stage.clone();
+    pkg.variant = request.variant.clone();
+    pkg.notes = request.notes.clone();
+    let mut pkg = apply_patch_commands_to_build(pkg);
 
-    let mut cflags = if request.cflags.is_empty() {
-        default_cflags(request)
-    } else {
-        request.cflags.clone()
+    let preset = request.preset.map(OptimizationSettings::from_preset);
+    let mut cflags = match (&preset, request.cflags.is_empty()) {
+        (_, false) => request.cflags.clone(),
+        (Some(preset), true) => preset.cflags.clone(),
+        (None, true) => default_cflags(request),
     };
-    let mut ldflags = if request.ldflags.is_empty() {
-        default_ldflags(request)
-    } else {
-        request.ldflags.clone()
+    let mut ldflags = match (&preset, request.ldflags.is_empty()) {
+        (_, false) => request.ldflags.clone(),
+        (Some(preset), true) => preset.ldflags.clone(),
+        (None, true) => default_ldflags(request),
     };
     dedup(&mut cflags);
     dedup(&mut ldflags);
@@ -128,13 +374,42 @@ fn build_definition(request: &ScaffoldRequest) -> PackageDefinition {
         Some(path) => OptimizationSettings::for_pgo_replay(path),
         None => OptimizationSettings::default(),
     };
-    pkg.optimizations.enable_lto = request.enable_lto;
-    pkg.optimizations.enable_pgo = request.enable_pgo;
+    pkg.optimizations.enable_lto = preset
+        .as_ref()
+        .map_or(request.enable_lto, |preset| preset.enable_lto);
+    pkg.optimizations.enable_pgo = preset
+        .as_ref()
+        .map_or(request.enable_pgo, |preset| preset.enable_pgo);
     pkg.optimizations.cflags = cflags;
     pkg.optimizations.ldflags = ldflags;
     pkg.optimizations.profdata = profdata;
+    if let Some(target) = &request.target_triple {
+        pkg.optimizations = pkg.optimizations.with_architecture(target);
+    }
 
-    pkg
+    for warning in validate_build_commands(&pkg) {
+        eprintln!(
+            "warning: {} in {}: {}",
+            warning.warning, request.name, warning.command
+        );
+    }
+
+    let conflicts = detect_flag_conflicts(&pkg.optimizations);
+    for conflict in &conflicts {
+        eprintln!(
+            "warning: {} conflicts with {} in {}: {}",
+            conflict.flags.0, conflict.flags.1, request.name, conflict.reason
+        );
+    }
+    if request.strict_flags && !conflicts.is_empty() {
+        return Err(anyhow!(
+            "{} conflicting compiler flag(s) for {}",
+            conflicts.len(),
+            request.name
+        ));
+    }
+
+    Ok(pkg)
 }
 
 fn default_cflags(request: &ScaffoldRequest) -> Vec<String> {
@@ -167,68 +442,78 @@ fn dedup(values: &mut Vec<String>) {
 }
 
 fn generate_module_source(request: &ScaffoldRequest, definition: &PackageDefinition) -> String {
-    let mut metadata = Vec::new();
-    if let Some(stage) = &request.stage {
-        metadata.push(format!("stage: {}", stage));
-    }
-    if let Some(variant) = &request.variant {
-        metadata.push(format!("variant: {}", variant));
-    }
-    if let Some(notes) = &request.notes {
-        metadata.push(format!("notes: {}", notes));
-    }
-    let metadata = if metadata.is_empty() {
-        String::new()
-    } else {
-        format!("// MLFS metadata: {}\n\n", metadata.join(", "))
-    };
     let configure_args = format_vec(&definition.configure_args);
+    let patch_commands = format_vec(&definition.patch_commands);
     let build_commands = format_vec(&definition.build_commands);
     let install_commands = format_vec(&definition.install_commands);
     let dependencies = format_vec(&definition.dependencies);
-    let cflags = format_vec(&definition.optimizations.cflags);
-    let ldflags = format_vec(&definition.optimizations.ldflags);
     let source = format_option(&definition.source);
     let md5 = format_option(&definition.md5);
-    let profdata = format_option(&definition.optimizations.profdata);
+    let stage = format_option(&definition.stage);
+    let variant = format_option(&definition.variant);
+    let notes = format_option(&definition.notes);
+
+    // Only optimization fields that differ from the preset (or PGO replay) baseline are emitted.
+    let optimizations = &definition.optimizations;
+    let mut imports = vec!["OptimizationPreset", "PackageDefinition"];
+    let mut overrides = String::new();
+    let baseline = match &optimizations.profdata {
+        Some(path) => {
+            imports.insert(1, "OptimizationSettings");
+            overrides.push_str(&format!(
+                "    pkg.optimizations = OptimizationSettings::for_pgo_replay(\"{}\");\n",
+                escape(path)
+            ));
+            OptimizationSettings::for_pgo_replay(path.clone())
+        }
+        None => OptimizationSettings::from_preset(GENERATED_PRESET),
+    };
+    if optimizations.enable_lto != baseline.enable_lto {
+        overrides.push_str(&format!(
+            "    pkg.optimizations.enable_lto = {};\n",
+            optimizations.enable_lto
+        ));
+    }
+    if optimizations.enable_pgo != baseline.enable_pgo {
+        overrides.push_str(&format!(
+            "    pkg.optimizations.enable_pgo = {};\n",
+            optimizations.enable_pgo
+        ));
+    }
+    if optimizations.cflags != baseline.cflags {
+        overrides.push_str(&format!(
+            "    pkg.optimizations.cflags = {};\n",
+            format_vec(&optimizations.cflags)
+        ));
+    }
+    if optimizations.ldflags != baseline.ldflags {
+        overrides.push_str(&format!(
+            "    pkg.optimizations.ldflags = {};\n",
+            format_vec(&optimizations.ldflags)
+        ));
+    }
 
     format!(
-        "{metadata}use crate::pkgs::package::{{OptimizationSettings, PackageDefinition}};\n\n\
+        "use crate::pkgs::package::{{{imports}}};\n\n\
          pub fn definition() -> PackageDefinition {{\n\
-            let mut pkg = PackageDefinition::new(\"{name}\", \"{version}\");\n\
+            let mut pkg = PackageDefinition::new_with_defaults(\"{name}\", \"{version}\", OptimizationPreset::{preset:?});\n\
             pkg.source = {source};\n\
             pkg.md5 = {md5};\n\
              pkg.configure_args = {configure_args};\n\
+             pkg.patch_commands = {patch_commands};\n\
              pkg.build_commands = {build_commands};\n\
              pkg.install_commands = {install_commands};\n\
              pkg.dependencies = {dependencies};\n\
-             let profdata = {profdata};\n\
-             let profdata_clone = profdata.clone();\n\
-             pkg.optimizations = match profdata_clone {{\n\
-                 Some(path) => OptimizationSettings::for_pgo_replay(path),\n\
-                 None => OptimizationSettings::default(),\n\
-             }};\n\
-             pkg.optimizations.enable_lto = {enable_lto};\n\
-             pkg.optimizations.enable_pgo = {enable_pgo};\n\
-             pkg.optimizations.cflags = {cflags};\n\
-             pkg.optimizations.ldflags = {ldflags};\n\
-             pkg.optimizations.profdata = profdata;\n\
+             pkg.stage = {stage};\n\
+             pkg.variant = {variant};\n\
+             pkg.notes = {notes};\n\
+         {overrides}\
              pkg\n\
          }}\n",
-        metadata = metadata,
+        imports = imports.join(", "),
         name = request.name,
         version = request.version,
-        source = source,
-        md5 = md5,
-        configure_args = configure_args,
-        build_commands = build_commands,
-        install_commands = install_commands,
-        dependencies = dependencies,
-        profdata = profdata,
-        enable_lto = request.enable_lto,
-        enable_pgo = request.enable_pgo,
-        cflags = cflags,
-        ldflags = ldflags,
+        preset = GENERATED_PRESET,
     )
 }
 
@@ -251,15 +536,24 @@ fn format_option(value: &Option<String>) -> String {
     }
 }
 
+/// Strict and reserved Rust keywords (2024 edition), which cannot be used as module names.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+pub fn is_rust_keyword(s: &str) -> bool {
+    RUST_KEYWORDS.contains(&s)
+}
+
 fn sanitize(name: &str) -> String {
     let mut out = String::new();
     for ch in name.chars() {
         if ch.is_ascii_alphanumeric() {
             out.push(ch.to_ascii_lowercase());
-        } else if ch == '_' || ch == '+' {
-            out.push('_');
-        } else if ch == '-' {
-            out.push('_');
         } else {
             out.push('_');
         }
@@ -275,19 +569,94 @@ fn sanitize(name: &str) -> String {
     {
         out.insert(0, 'p');
     }
+    if is_rust_keyword(&out) {
+        out.push_str("_pkg");
+    }
     out
 }
 
-fn prefix(module: &str) -> String {
-    let mut chars = module.chars();
-    let first = chars.next().unwrap_or('p');
-    let second = chars.next().unwrap_or('k');
-    let mut s = String::new();
-    s.push(first);
-    s.push(second);
-    s
+/// Two-character `by_name` directory for a sanitized module name.
+///
+/// The first two characters are lowercased; anything that is not an ASCII letter or digit,
+/// as well as a missing second character, becomes `x`. A prefix that would be a Rust keyword
+/// gets a trailing `_`, since it is also declared as `pub mod {prefix};`:
+///
+/// ```text
+/// binutils  -> bi
+/// p1assword -> p1
+/// r         -> rx
+/// a_b       -> ax
+/// inetutils -> in_
+/// ```
+pub(crate) fn prefix_for_module(module: &str) -> String {
+    let mut chars = module.chars().map(|ch| {
+        if ch.is_ascii_alphanumeric() {
+            ch.to_ascii_lowercase()
+        } else {
+            'x'
+        }
+    });
+    let first = chars.next().unwrap_or('x');
+    let second = chars.next().unwrap_or('x');
+    let mut prefix: String = [first, second].iter().collect();
+    if is_rust_keyword(&prefix) {
+        prefix.push('_');
+    }
+    prefix
 }
 
 fn escape(input: &str) -> String {
     input.replace('\\', "\\\\").replace('"', "\\\"")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn scratch_by_name(name: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("lpkg-scaffolder-{}-{name}", std::process::id()))
+            .join("by_name");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn prefix_for_module_matches_documented_examples() {
+        assert_eq!(prefix_for_module("binutils"), "bi");
+        assert_eq!(prefix_for_module("p1assword"), "p1");
+        assert_eq!(prefix_for_module("r"), "rx");
+        assert_eq!(prefix_for_module("a_b"), "ax");
+    }
+
+    #[test]
+    fn prefix_for_module_never_yields_a_keyword() {
+        for (module, prefix) in [
+            ("inetutils", "in_"),
+            ("intltool", "in_"),
+            ("asciidoc", "as_"),
+            ("docbook_xml", "do_"),
+            ("fnmatch", "fn_"),
+            ("ifupdown", "if_"),
+        ] {
+            assert_eq!(prefix_for_module(module), prefix);
+        }
+        assert!(!is_rust_keyword(&prefix_for_module("inetutils")));
+    }
+
+    #[test]
+    fn scaffolding_a_keyword_prefixed_package_declares_a_valid_module() {
+        let base = scratch_by_name("inetutils");
+        let request = ScaffoldRequest::from(PackageDefinition::new("inetutils", "2.6"));
+        let result = scaffold_package(&base, request).unwrap();
+
+        assert_eq!(result.module_path, base.join("in_/inetutils/mod.rs"));
+        assert!(result.module_path.is_file());
+        let by_name = fs::read_to_string(base.join("mod.rs")).unwrap();
+        assert_eq!(by_name, "pub mod in_;\n");
+        let prefix = fs::read_to_string(base.join("in_/mod.rs")).unwrap();
+        assert_eq!(prefix, "pub mod inetutils;\n");
+    }
+}
@@ -7,6 +7,7 @@ pub struct Document {
     aria_label: Option<(String, String)>,
     title: Option<String>,
     desc: Option<String>,
+    namespaces: Vec<(String, String)>,
     defs: Vec<String>,
     elements: Vec<String>,
 }
@@ -45,6 +46,13 @@ impl Document {
         self
     }
 
+    /// Declares an extra XML namespace on the root element, e.g. `xlink` for `<use>`.
+    /// An empty prefix replaces the default SVG namespace.
+    pub fn namespace(mut self, prefix: &str, uri: &str) -> Self {
+        self.namespaces.push((prefix.to_string(), uri.to_string()));
+        self
+    }
+
     pub fn add_defs(mut self, defs: Defs) -> Self {
         self.defs.push(defs.finish());
         self
@@ -64,15 +72,24 @@ impl Document {
             aria_label,
             title,
             desc,
+            namespaces,
             defs,
             elements,
         } = self;
 
+        let default_ns = namespaces
+            .iter()
+            .rev()
+            .find(|(prefix, _)| prefix.is_empty())
+            .map(|(_, uri)| uri.as_str())
+            .unwrap_or("http://www.w3.org/2000/svg");
+
         let mut out = String::new();
-        out.push_str(&format!(
-            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\"",
-            width, height
-        ));
+        out.push_str(&format!("<svg xmlns=\"{}\"", default_ns));
+        for (prefix, uri) in namespaces.iter().filter(|(prefix, _)| !prefix.is_empty()) {
+            out.push_str(&format!(" xmlns:{}=\"{}\"", prefix, uri));
+        }
+        out.push_str(&format!(" width=\"{}\" height=\"{}\"", width, height));
         if let Some(view_box) = view_box {
             out.push_str(&format!(" viewBox=\"{}\"", view_box));
         }
@@ -86,7 +103,7 @@ impl Document {
         if aria_label.is_some() {
             out.push_str(&format!(" aria-labelledby=\"{} {}\"", title_id, desc_id));
         }
-        out.push_str(">");
+        out.push('>');
         out.push('\n');
 
         if let Some(title) = title {
@@ -114,6 +131,7 @@ impl Document {
     }
 }
 
+#[derive(Default)]
 pub struct Defs {
     content: Vec<String>,
 }
@@ -232,6 +250,7 @@ impl RadialGradient {
     }
 }
 
+#[derive(Default)]
 pub struct Filter {
     attrs: Vec<(String, String)>,
     content: Vec<String>,
@@ -291,7 +310,7 @@ impl Element {
     }
 
     pub fn text(mut self, text: &str) -> String {
-        self.content = Some(text.to_string());
+        self.content = Some(escape_text(text));
         self.render()
     }
 
@@ -304,7 +323,7 @@ impl Element {
         let attrs = self
             .attrs
             .iter()
-            .map(|(k, v)| format!(" {}=\"{}\"", k, v))
+            .map(|(k, v)| format!(" {}=\"{}\"", k, escape_attr(v)))
             .collect::<String>();
         if let Some(content) = &self.content {
             format!(
@@ -319,6 +338,7 @@ impl Element {
     }
 }
 
+#[derive(Default)]
 pub struct Group {
     attrs: Vec<(String, String)>,
     children: Vec<String>,
@@ -373,3 +393,33 @@ impl From<Element> for String {
 pub fn path(d: &str) -> String {
     Element::new("path").attr("d", d).empty()
 }
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_escapes_attributes_and_text() {
+        let rendered = Element::new("text").attr("class", "a&b").text("x<y");
+        assert_eq!(rendered, "  <text class=\"a&amp;b\">x&lt;y</text>");
+    }
+
+    #[test]
+    fn escape_attr_handles_quotes_and_brackets() {
+        assert_eq!(escape_attr("\"<tag>\""), "&quot;&lt;tag&gt;&quot;");
+        assert_eq!(escape_text("a > b & c"), "a &gt; b &amp; c");
+    }
+}
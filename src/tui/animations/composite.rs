@@ -0,0 +1,84 @@
+use super::Animation;
+use rsille::Canvas;
+use std::time::Duration;
+
+/// How a [`CompositeAnimation`] arranges its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeLayout {
+    /// Equal-width tiles from left to right.
+    Horizontal,
+    /// Equal-height tiles from top to bottom.
+    Vertical,
+    /// Every child drawn over the full area.
+    Overlay,
+}
+
+/// Several animations driven and rendered as one.
+pub struct CompositeAnimation {
+    animations: Vec<Box<dyn Animation>>,
+    layout: CompositeLayout,
+    size: (u16, u16),
+}
+
+impl CompositeAnimation {
+    pub fn new(
+        width: u16,
+        height: u16,
+        layout: CompositeLayout,
+        animations: Vec<Box<dyn Animation>>,
+    ) -> Self {
+        Self {
+            animations,
+            layout,
+            size: (width, height),
+        }
+    }
+
+    pub fn push(&mut self, animation: Box<dyn Animation>) {
+        self.animations.push(animation);
+    }
+
+    /// Origin and size of the tile assigned to child `index`.
+    fn tile(&self, index: usize) -> (u16, u16, u16, u16) {
+        let (width, height) = self.size;
+        let count = self.animations.len().max(1) as u16;
+        let index = index as u16;
+        match self.layout {
+            CompositeLayout::Horizontal => {
+                let tile_width = width / count;
+                (index * tile_width, 0, tile_width, height)
+            }
+            CompositeLayout::Vertical => {
+                let tile_height = height / count;
+                (0, index * tile_height, width, tile_height)
+            }
+            CompositeLayout::Overlay => (0, 0, width, height),
+        }
+    }
+}
+
+impl Animation for CompositeAnimation {
+    fn update(&mut self, delta: Duration) {
+        for animation in &mut self.animations {
+            animation.update(delta);
+        }
+    }
+
+    fn render_at(&self, canvas: &mut Canvas, (origin_x, origin_y): (f64, f64)) {
+        for (index, animation) in self.animations.iter().enumerate() {
+            let (x, y, width, height) = self.tile(index);
+            if width == 0 || height == 0 {
+                continue;
+            }
+            // Tiles are laid out in terminal cells; a Braille cell is 2x4 canvas dots.
+            let origin = (origin_x + f64::from(x) * 2.0, origin_y + f64::from(y) * 4.0);
+            animation.render_at(canvas, origin);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.animations
+            .iter()
+            .all(|animation| animation.is_finished())
+    }
+}
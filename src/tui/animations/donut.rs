@@ -1,5 +1,5 @@
 use std::time::Duration;
-use rsille::canvas::Canvas;
+use rsille::Canvas;
 use super::Animation;
 
 const THETA_SPACING: f64 = 0.07;
@@ -28,12 +28,10 @@ impl Animation for DonutAnimation {
         self.b += delta_secs * 0.5;
     }
 
-    fn render(&self, canvas: &mut Canvas) {
+    fn render_at(&self, canvas: &mut Canvas, (origin_x, origin_y): (f64, f64)) {
         let (width, height) = self.size;
         let (width_f, height_f) = (width as f64, height as f64);
-        let k2 = 5.0;
-        let k1 = width_f * k2 * 3.0 / (8.0 * (height_f + width_f));
-        
+
         for theta in 0..((2.0 * std::f64::consts::PI / THETA_SPACING) as i32) {
             let theta_f = theta as f64 * THETA_SPACING;
             let cos_theta = theta_f.cos();
@@ -68,7 +66,12 @@ impl Animation for DonutAnimation {
                         160..=191 => '#',
                         _ => '@',
                     };
-                    canvas.put_char(x as u16, y as u16, c);
+                    canvas.put(
+                        origin_x + f64::from(x * 2),
+                        origin_y + f64::from(y * 4),
+                        c,
+                        None,
+                    );
                 }
             }
         }
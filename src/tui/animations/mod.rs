@@ -1,9 +1,20 @@
-use rsille::canvas::Canvas;
+pub mod composite;
+pub mod donut;
+pub mod progress;
+pub mod spinner;
+pub mod wave;
+
+use rsille::Canvas;
 use std::time::Duration;
 
 pub trait Animation {
     fn update(&mut self, delta: Duration);
-    fn render(&self, canvas: &mut Canvas);
+    fn render(&self, canvas: &mut Canvas) {
+        self.render_at(canvas, (0.0, 0.0));
+    }
+    /// Draw with the top-left corner at `origin`, in canvas coordinates (Braille dots: 2 per
+    /// cell across, 4 per cell down).
+    fn render_at(&self, canvas: &mut Canvas, origin: (f64, f64));
     fn is_finished(&self) -> bool;
 }
 
@@ -1,5 +1,5 @@
 use std::time::Duration;
-use rsille::canvas::Canvas;
+use rsille::Canvas;
 use super::{Animation, ProgressAnimation};
 
 pub struct ProgressBarAnimation {
@@ -28,8 +28,24 @@ impl Animation for ProgressBarAnimation {
         }
     }
 
-    fn render(&self, canvas: &mut Canvas) {
-        // Animated progress bar rendering will be implemented here
+    fn render_at(&self, canvas: &mut Canvas, (origin_x, origin_y): (f64, f64)) {
+        // Braille cells are 2 dots wide and 4 dots tall.
+        let dots_x = self.width as usize * 2;
+        let dots_y = self.height as usize * 4;
+        let filled = (self.progress * dots_x as f64).round() as usize;
+
+        for x in 0..filled.min(dots_x) {
+            for y in 0..dots_y {
+                canvas.set(origin_x + x as f64, origin_y + y as f64);
+            }
+        }
+
+        // A single marching dot runs along the empty remainder of the bar.
+        let remaining = dots_x.saturating_sub(filled);
+        if remaining > 0 && dots_y > 0 {
+            let x = filled + (self.animation_offset * remaining as f64) as usize;
+            canvas.set(origin_x + x as f64, origin_y + (dots_y / 2) as f64);
+        }
     }
 
     fn is_finished(&self) -> bool {
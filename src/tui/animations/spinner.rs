@@ -0,0 +1,74 @@
+use super::Animation;
+use rsille::Canvas;
+use std::time::Duration;
+
+const BRAILLE_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Spinner for operations without a known progress, e.g. fetching a manifest.
+pub struct SpinnerAnimation {
+    frames: Vec<String>,
+    current_frame: usize,
+    elapsed: Duration,
+    frame_duration: Duration,
+    size: (u16, u16),
+}
+
+impl SpinnerAnimation {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self::with_frames(
+            width,
+            height,
+            BRAILLE_FRAMES
+                .iter()
+                .map(|frame| frame.to_string())
+                .collect(),
+            Duration::from_millis(80),
+        )
+    }
+
+    pub fn with_frames(
+        width: u16,
+        height: u16,
+        frames: Vec<String>,
+        frame_duration: Duration,
+    ) -> Self {
+        Self {
+            frames,
+            current_frame: 0,
+            elapsed: Duration::ZERO,
+            frame_duration,
+            size: (width, height),
+        }
+    }
+
+    pub fn current_frame(&self) -> &str {
+        self.frames
+            .get(self.current_frame)
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+}
+
+impl Animation for SpinnerAnimation {
+    fn update(&mut self, delta: Duration) {
+        if self.frames.is_empty() || self.frame_duration.is_zero() {
+            return;
+        }
+        self.elapsed += delta;
+        while self.elapsed >= self.frame_duration {
+            self.elapsed -= self.frame_duration;
+            self.current_frame = (self.current_frame + 1) % self.frames.len();
+        }
+    }
+
+    fn render_at(&self, canvas: &mut Canvas, (x, y): (f64, f64)) {
+        let (width, height) = self.size;
+        if let Some(c) = self.current_frame().chars().next() {
+            canvas.put(x + f64::from(width), y + f64::from(height) * 2.0, c, None);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        false // runs until the caller stops rendering it
+    }
+}
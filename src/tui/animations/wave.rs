@@ -0,0 +1,72 @@
+use super::Animation;
+use rsille::Canvas;
+use std::time::Duration;
+
+/// Scrolling sine wave, used as a decorative header.
+pub struct WaveAnimation {
+    width: u16,
+    height: u16,
+    phase: f64,
+    amplitude: f64,
+    frequency: f64,
+    speed: f64,
+}
+
+impl WaveAnimation {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            phase: 0.0,
+            amplitude: 1.0,
+            frequency: 2.0,
+            speed: 3.0,
+        }
+    }
+
+    /// Peak height as a fraction (0.0..=1.0) of half the canvas height.
+    pub fn with_amplitude(mut self, amplitude: f64) -> Self {
+        self.amplitude = amplitude.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Number of full periods drawn across the canvas width.
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Phase advance in radians per second.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+impl Animation for WaveAnimation {
+    fn update(&mut self, delta: Duration) {
+        self.phase += self.speed * delta.as_secs_f64();
+        self.phase %= std::f64::consts::TAU;
+    }
+
+    fn render_at(&self, canvas: &mut Canvas, (origin_x, origin_y): (f64, f64)) {
+        // Braille cells are 2 dots wide and 4 dots tall.
+        let dots_x = self.width as usize * 2;
+        let dots_y = self.height as f64 * 4.0;
+        if dots_x == 0 || dots_y == 0.0 {
+            return;
+        }
+        let middle = (dots_y - 1.0) / 2.0;
+
+        for x in 0..dots_x {
+            let t = x as f64 / dots_x as f64;
+            let angle = t * self.frequency * std::f64::consts::TAU + self.phase;
+            let y = middle - angle.sin() * self.amplitude * middle;
+            canvas.set(origin_x + x as f64, origin_y + y.round());
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        false // purely decorative
+    }
+}
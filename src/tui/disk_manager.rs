@@ -1,24 +1,303 @@
 // src/tui/disk_manager.rs
 use std::{
-    fs::{File, read_dir},
-    io::{self, Seek, SeekFrom},
-    path::PathBuf,
+    fs::{self, File, read_dir},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
 };
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, MouseEventKind};
 use crossterm::execute;
 use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
 use gptman::{GPT, GPTPartitionEntry, PartitionName};
 use tui::{
     Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::Span,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
 use uuid::Uuid;
 
+use anyhow::bail;
+
+use crate::tui::keybindings::{ActionDebounce, KeyBindings};
+
+/// Well-known GPT partition type GUIDs and their display names.
+static KNOWN_PARTITION_TYPES: &[(&str, &str)] = &[
+    ("C12A7328-F81F-11D2-BA4B-00A0C93EC93B", "EFI System"),
+    ("0FC63DAF-8483-4772-8E79-3D69D8477DE4", "Linux filesystem"),
+    ("0657FD6D-A4AB-43C4-84E5-0933C84B4F4F", "Linux swap"),
+    ("E6D6D379-F507-44C2-A23C-238F2A3DF928", "Linux LVM"),
+    ("21686148-6449-6E6F-744E-656564454649", "BIOS boot"),
+    (
+        "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7",
+        "Microsoft basic data",
+    ),
+];
+
+/// Format a sector count as MiB, or GiB with one decimal place once it reaches 1 GiB.
+fn lba_to_human(lba_count: u64, sector_size: u64) -> String {
+    bytes_to_human(lba_count.saturating_mul(sector_size))
+}
+
+fn bytes_to_human(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    const GIB: f64 = MIB * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GIB {
+        format!("{:.1} GiB", bytes / GIB)
+    } else {
+        format!("{:.0} MiB", bytes / MIB)
+    }
+}
+
+/// Size of a block device from sysfs (`/sys/block/{name}/size`, in 512-byte sectors).
+/// Partitions are looked up under `/sys/class/block`. Returns `None` if sysfs is unavailable.
+fn read_disk_size_bytes(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    ["/sys/block", "/sys/class/block"]
+        .iter()
+        .find_map(|root| fs::read_to_string(Path::new(root).join(name).join("size")).ok())
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .map(|sectors| sectors * 512)
+}
+
+/// Look up a partition type GUID as stored in the GPT entry. Both the on-disk mixed-endian
+/// layout and the big-endian layout written by `create_partition_tui` are recognised.
+fn partition_type_name(guid: &[u8; 16]) -> Option<&'static str> {
+    KNOWN_PARTITION_TYPES.iter().find_map(|(text, label)| {
+        let known = Uuid::parse_str(text).ok()?;
+        (known.as_bytes() == guid || &known.to_bytes_le() == guid).then_some(*label)
+    })
+}
+
+/// Read the GPT from `file`, trying 512-byte sectors first and then auto-detection.
+fn read_gpt(file: &mut File) -> Option<GPT> {
+    GPT::read_from(file, 512)
+        .or_else(|_| GPT::find_from(file))
+        .ok()
+}
+
+/// Partition table layout found on a disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PartitionTableKind {
+    Gpt,
+    Mbr,
+    Unknown,
+}
+
+const MBR_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+const MBR_TABLE_OFFSET: usize = 446;
+const MBR_ENTRY_SIZE: usize = 16;
+
+/// One of the four primary entries of an MBR partition table.
+struct MbrPartition {
+    index: usize,
+    bootable: bool,
+    type_code: u8,
+    start_lba: u32,
+    sectors: u32,
+}
+
+fn read_sector0(file: &mut File) -> io::Result<[u8; 512]> {
+    let mut sector = [0u8; 512];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut sector)?;
+    Ok(sector)
+}
+
+/// GPT if `gptman` can read a header, MBR if sector 0 carries the `55 AA` boot signature.
+fn detect_partition_table(file: &mut File) -> PartitionTableKind {
+    if read_gpt(file).is_some() {
+        return PartitionTableKind::Gpt;
+    }
+    match read_sector0(file) {
+        Ok(sector) if sector[510..512] == MBR_SIGNATURE => PartitionTableKind::Mbr,
+        _ => PartitionTableKind::Unknown,
+    }
+}
+
+/// The four primary partition entries from an MBR sector, including empty slots.
+fn parse_mbr_partitions(sector: &[u8; 512]) -> Vec<MbrPartition> {
+    (0..4)
+        .map(|i| {
+            let entry = &sector[MBR_TABLE_OFFSET + i * MBR_ENTRY_SIZE..][..MBR_ENTRY_SIZE];
+            let le_u32 = |at: usize| u32::from_le_bytes(entry[at..at + 4].try_into().unwrap());
+            MbrPartition {
+                index: i + 1,
+                bootable: entry[0] == 0x80,
+                type_code: entry[4],
+                start_lba: le_u32(8),
+                sectors: le_u32(12),
+            }
+        })
+        .collect()
+}
+
+/// Display line for every MBR primary entry.
+fn describe_mbr_partitions(partitions: &[MbrPartition]) -> Vec<String> {
+    partitions
+        .iter()
+        .map(|part| {
+            if part.type_code == 0 {
+                return format!("{}: empty", part.index);
+            }
+            format!(
+                "{}: type 0x{:02X}  start {}  size {} sectors ({}){}",
+                part.index,
+                part.type_code,
+                part.start_lba,
+                part.sectors,
+                lba_to_human(u64::from(part.sectors), 512),
+                if part.bootable { "  [bootable]" } else { "" }
+            )
+        })
+        .collect()
+}
+
+/// Display lines for every used partition, keyed by its 1-based GPT index.
+fn describe_partitions(disk: &Path, gpt: &GPT) -> Vec<(u32, Vec<String>)> {
+    gpt.iter()
+        .filter(|(_, entry)| entry.is_used())
+        .map(|(i, entry)| {
+            let type_guid = Uuid::from_bytes_le(entry.partition_type_guid);
+            let type_label = partition_type_name(&entry.partition_type_guid)
+                .map(|known| format!("{known} ({type_guid})"))
+                .unwrap_or_else(|| type_guid.to_string().to_uppercase());
+            let lines = vec![
+                format!(
+                    "{}: {} -> {}  {}  (type: {})",
+                    i,
+                    entry.starting_lba,
+                    entry.ending_lba,
+                    lba_to_human(entry.size().unwrap_or(0), gpt.sector_size),
+                    type_label
+                ),
+                match find_label_for_device(&partition_device_path(disk, i)) {
+                    Some(label) => format!(
+                        "    name: {}  label: {}",
+                        entry.partition_name.as_str(),
+                        label
+                    ),
+                    None => format!("    name: {}", entry.partition_name.as_str()),
+                },
+            ];
+            (i, lines)
+        })
+        .collect()
+}
+
+/// Sector range `create_partition_tui` proposes for a new partition of `size_mb` MB: the next
+/// 2048-aligned sector after the last used partition.
+fn proposed_partition_range(gpt: &GPT, size_mb: u64) -> (u64, u64) {
+    // convert MB -> sectors (512 bytes per sector)
+    let sectors = (size_mb as u128 * 1024 * 1024 / 512) as u64;
+    let last_end = gpt
+        .iter()
+        .filter(|(_, e)| e.is_used())
+        .map(|(_, e)| e.ending_lba)
+        .max()
+        .unwrap_or(2048);
+    let start = ((last_end + 2048) / 2048) * 2048 + 1;
+    let end = start + sectors.saturating_sub(1);
+    (start, end)
+}
+
+/// Check that `[start, end]` lies within the usable LBAs of the GPT header and does not
+/// overlap an existing partition.
+fn validate_partition_bounds(gpt: &GPT, start: u64, end: u64) -> anyhow::Result<()> {
+    if start < gpt.header.first_usable_lba {
+        bail!(
+            "start sector {start} is before the first usable sector {}",
+            gpt.header.first_usable_lba
+        );
+    }
+    if end > gpt.header.last_usable_lba {
+        bail!(
+            "end sector {end} is past the last usable sector {}; reduce the size",
+            gpt.header.last_usable_lba
+        );
+    }
+    if let Some((idx, entry)) = gpt
+        .iter()
+        .filter(|(_, e)| e.is_used())
+        .find(|(_, e)| e.starting_lba <= end && start <= e.ending_lba)
+    {
+        bail!(
+            "overlaps partition {idx} (sectors {}..{})",
+            entry.starting_lba,
+            entry.ending_lba
+        );
+    }
+    Ok(())
+}
+
+/// Device node of partition `index` on `disk` (`/dev/sda` -> `/dev/sda1`,
+/// `/dev/nvme0n1` -> `/dev/nvme0n1p1`).
+fn partition_device_path(disk: &Path, index: u32) -> PathBuf {
+    let disk = disk.to_string_lossy();
+    if disk.ends_with(|c: char| c.is_ascii_digit()) {
+        PathBuf::from(format!("{disk}p{index}"))
+    } else {
+        PathBuf::from(format!("{disk}{index}"))
+    }
+}
+
+/// Find the filesystem label whose `/dev/disk/by-label` symlink resolves to `device`.
+/// Missing directories and unreadable links are treated as "no label".
+fn find_label_for_device(device: &Path) -> Option<String> {
+    let device = fs::canonicalize(device).ok()?;
+    read_dir("/dev/disk/by-label")
+        .ok()?
+        .flatten()
+        .find(|link| fs::canonicalize(link.path()).is_ok_and(|target| target == device))
+        .map(|link| unescape_label(&link.file_name().to_string_lossy()))
+}
+
+/// udev escapes unsafe label characters as `\xNN`; turn them back into bytes.
+fn unescape_label(raw: &str) -> String {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut rest = raw.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let escaped = if b == b'\\' && tail.first() == Some(&b'x') {
+            tail.get(1..3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        if let Some(value) = escaped {
+            bytes.push(value);
+            rest = &tail[3..];
+            continue;
+        }
+        bytes.push(b);
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn select_step(state: &mut ListState, len: usize, offset: isize) {
+    if len == 0 {
+        state.select(None);
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    state.select(Some((current + offset).clamp(0, len as isize - 1) as usize));
+}
+
+/// A `height`-row rectangle centred in `area`, `percent_x` percent wide.
+fn centered_rect(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(area.height),
+    }
+}
+
 /// DiskManager: interactive TUI to view and create GPT partitions on Linux.
 ///
 /// Requirements (add to Cargo.toml):
@@ -30,7 +309,10 @@ pub struct DiskManager;
 
 impl DiskManager {
     /// Entrypoint: run the disk manager UI. This initializes the terminal and starts the loop.
-    pub fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
+    pub fn run_tui(
+        keys: &KeyBindings,
+        debounce: &ActionDebounce,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // init terminal
         let mut stdout = std::io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
@@ -43,14 +325,13 @@ impl DiskManager {
         if let Ok(entries) = read_dir("/dev/") {
             for e in entries.flatten() {
                 let path = e.path();
-                if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                    if name.starts_with("sd")
+                if let Some(name) = path.file_name().and_then(|s| s.to_str())
+                    && (name.starts_with("sd")
                         || name.starts_with("nvme")
                         || name.starts_with("vd")
-                        || name.starts_with("mmcblk")
-                    {
-                        devices.push(path);
-                    }
+                        || name.starts_with("mmcblk"))
+                {
+                    devices.push(path);
                 }
             }
         }
@@ -62,6 +343,15 @@ impl DiskManager {
             return Ok(());
         }
 
+        let sizes: Vec<String> = devices
+            .iter()
+            .map(|d| {
+                read_disk_size_bytes(d)
+                    .map(bytes_to_human)
+                    .unwrap_or_else(|| "unknown size".to_string())
+            })
+            .collect();
+
         let mut selected_idx = 0usize;
         let mut status_msg =
             String::from("Select disk. ↑/↓ to navigate, Enter=view, C=create, Q=quit.");
@@ -97,9 +387,10 @@ impl DiskManager {
                     .enumerate()
                     .map(|(i, d)| {
                         let label = format!(
-                            "{} {}",
+                            "{} {}  ({})",
                             if i == selected_idx { "▶" } else { " " },
-                            d.display()
+                            d.display(),
+                            sizes[i]
                         );
                         let mut li = ListItem::new(label);
                         if i == selected_idx {
@@ -121,42 +412,41 @@ impl DiskManager {
             })?;
 
             // Input handling
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Char('Q') => break,
-                        KeyCode::Up => {
-                            if selected_idx > 0 {
-                                selected_idx -= 1;
-                            }
+            if event::poll(std::time::Duration::from_millis(100))?
+                && let Event::Key(key) = event::read()?
+            {
+                match key.code {
+                    code if code == keys.quit || code == keys.back => break,
+                    code if code == keys.menu_up => {
+                        selected_idx = selected_idx.saturating_sub(1);
+                    }
+                    code if code == keys.menu_down && selected_idx + 1 < devices.len() => {
+                        selected_idx += 1;
+                    }
+                    code if code == keys.select => {
+                        if !debounce.accept() {
+                            continue;
                         }
-                        KeyCode::Down => {
-                            if selected_idx + 1 < devices.len() {
-                                selected_idx += 1;
-                            }
+                        let path = devices[selected_idx].clone();
+                        match Self::view_partitions_tui(&path, &mut term, keys) {
+                            Ok(m) => status_msg = m,
+                            Err(e) => status_msg = format!("Error reading partitions: {}", e),
                         }
-                        KeyCode::Enter => {
-                            let path = devices[selected_idx].clone();
-                            match Self::view_partitions_tui(&path, &mut term) {
-                                Ok(m) => status_msg = m,
-                                Err(e) => status_msg = format!("Error reading partitions: {}", e),
+                    }
+                    KeyCode::Char('c') | KeyCode::Char('C') => {
+                        let path = devices[selected_idx].clone();
+                        match Self::create_partition_tui(&path, &mut term, keys, debounce) {
+                            Ok(m) => {
+                                println!("[disk-manager] {}", m);
+                                status_msg = m;
                             }
-                        }
-                        KeyCode::Char('c') | KeyCode::Char('C') => {
-                            let path = devices[selected_idx].clone();
-                            match Self::create_partition_tui(&path, &mut term) {
-                                Ok(m) => {
-                                    println!("[disk-manager] {}", m);
-                                    status_msg = m;
-                                }
-                                Err(e) => {
-                                    eprintln!("[disk-manager] create partition error: {e}");
-                                    status_msg = format!("Create failed: {}", e);
-                                }
+                            Err(e) => {
+                                eprintln!("[disk-manager] create partition error: {e}");
+                                status_msg = format!("Create failed: {}", e);
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
         }
@@ -168,104 +458,264 @@ impl DiskManager {
     }
 
     /// Show GPT partitions for the chosen disk in a paged TUI view.
+    /// Pressing `d` on the selected partition asks for confirmation and deletes it.
     fn view_partitions_tui(
         disk: &PathBuf,
         term: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        keys: &KeyBindings,
     ) -> Result<String, Box<dyn std::error::Error>> {
         // try to open & read GPT (512 sector size)
         let mut file = File::open(disk)?;
-        let gpt = match GPT::read_from(&mut file, 512) {
-            Ok(g) => g,
-            Err(_) => match GPT::find_from(&mut file) {
-                Ok(g) => g,
-                Err(_) => {
-                    return Ok(format!("No GPT found on {}", disk.display()));
-                }
-            },
-        };
-
-        // Create list of lines to display using public GPT API:
-        let mut lines: Vec<String> = Vec::new();
-        lines.push(format!("Partitions on {}:", disk.display()));
-        for (i, entry) in gpt.iter() {
-            if entry.is_used() {
-                let name = entry.partition_name.as_str();
-                lines.push(format!(
-                    "{}: {} -> {}  (type: {})",
-                    i,
-                    entry.starting_lba,
-                    entry.ending_lba,
-                    // show a short GUID hex for partition type
-                    hex::encode_upper(&entry.partition_type_guid)
-                ));
-                lines.push(format!("    name: {}", name));
+        match detect_partition_table(&mut file) {
+            PartitionTableKind::Gpt => {}
+            PartitionTableKind::Mbr => {
+                let sector = read_sector0(&mut file)?;
+                return Self::view_mbr_partitions_tui(disk, &sector, term, keys);
+            }
+            PartitionTableKind::Unknown => {
+                return Ok(format!("No partition table found on {}", disk.display()));
             }
         }
-        if lines.len() == 1 {
-            lines.push("No partitions found.".into());
-        }
-        // paged view loop
-        let mut top = 0usize;
+        let Some(gpt) = read_gpt(&mut file) else {
+            return Ok(format!("No GPT found on {}", disk.display()));
+        };
+        let mut partitions = describe_partitions(disk, &gpt);
+
+        let mut state = ListState::default();
+        state.select((!partitions.is_empty()).then_some(0));
+        // Some(input) while the delete confirmation dialog is open
+        let mut confirm: Option<String> = None;
+        let mut status_msg = String::new();
+
         loop {
             term.draw(|f| {
                 let size = f.size();
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .margin(1)
-                    .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                    .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
                     .split(size);
 
-                let page_lines: Vec<ListItem> = lines
-                    .iter()
-                    .skip(top)
-                    .take((chunks[0].height as usize).saturating_sub(2))
-                    .map(|l| ListItem::new(l.clone()))
-                    .collect();
-
-                let list = List::new(page_lines).block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title(format!("Partitions: {}", disk.display())),
-                );
-                f.render_widget(list, chunks[0]);
-
-                let footer = Paragraph::new("↑/↓ scroll  •  q to go back")
+                let items: Vec<ListItem> = if partitions.is_empty() {
+                    vec![ListItem::new("No partitions found.")]
+                } else {
+                    partitions
+                        .iter()
+                        .map(|(_, lines)| ListItem::new(lines.join("\n")))
+                        .collect()
+                };
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!("Partitions: {}", disk.display())),
+                    )
+                    .highlight_style(Style::default().fg(Color::Yellow))
+                    .highlight_symbol("▶ ");
+                f.render_stateful_widget(list, chunks[0], &mut state);
+
+                let hint = "↑/↓ or wheel to select  •  d delete  •  q to go back";
+                let footer = if status_msg.is_empty() {
+                    hint.to_string()
+                } else {
+                    format!("{status_msg}  •  {hint}")
+                };
+                let footer = Paragraph::new(footer)
+                    .style(Style::default().fg(Color::Green))
                     .block(Block::default().borders(Borders::ALL));
                 f.render_widget(footer, chunks[1]);
+
+                if let (Some(input), Some(selected)) = (&confirm, state.selected()) {
+                    let mut text = format!(
+                        "Delete partition {} on {}?\n",
+                        partitions[selected].0,
+                        disk.display()
+                    );
+                    if partitions.len() == 1 {
+                        text.push_str(
+                            "This is the last partition: the disk will appear unpartitioned.\n",
+                        );
+                    }
+                    text.push_str(&format!("Are you sure? Type YES to confirm: {input}"));
+                    let area = centered_rect(size, 60, 7);
+                    let dialog = Paragraph::new(text)
+                        .style(Style::default().fg(Color::Red))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .title("Confirm delete"),
+                        );
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
             })?;
 
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(k) = event::read()? {
+            if !event::poll(std::time::Duration::from_millis(100))? {
+                continue;
+            }
+            let event = event::read()?;
+
+            if let Some(input) = confirm.as_mut() {
+                if let Event::Key(k) = event {
                     match k.code {
-                        KeyCode::Char('q') | KeyCode::Esc => break,
-                        KeyCode::Up => {
-                            if top > 0 {
-                                top = top.saturating_sub(1);
-                            }
+                        KeyCode::Esc => {
+                            confirm = None;
+                            status_msg = "Deletion cancelled".into();
                         }
-                        KeyCode::Down => {
-                            if top + 1 < lines.len() {
-                                top = top.saturating_add(1);
+                        KeyCode::Enter => {
+                            let confirmed = input == "YES";
+                            confirm = None;
+                            let selected = state.selected().unwrap_or(0);
+                            if !confirmed {
+                                status_msg = "Deletion cancelled".into();
+                                continue;
                             }
+                            let idx = partitions[selected].0;
+                            status_msg = match Self::delete_partition_tui(disk, term, idx) {
+                                Ok(m) => m,
+                                Err(e) => format!("Delete failed: {e}"),
+                            };
+                            let mut file = File::open(disk)?;
+                            partitions = read_gpt(&mut file)
+                                .map(|gpt| describe_partitions(disk, &gpt))
+                                .unwrap_or_default();
+                            state.select(
+                                (!partitions.is_empty())
+                                    .then(|| selected.min(partitions.len() - 1)),
+                            );
                         }
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(c) => input.push(c),
                         _ => {}
                     }
                 }
+                continue;
+            }
+
+            match event {
+                Event::Key(k) => match k.code {
+                    code if code == keys.quit || code == keys.back => break,
+                    code if code == keys.menu_up => select_step(&mut state, partitions.len(), -1),
+                    code if code == keys.menu_down => select_step(&mut state, partitions.len(), 1),
+                    KeyCode::Char('d') | KeyCode::Char('D') if state.selected().is_some() => {
+                        confirm = Some(String::new());
+                    }
+                    _ => {}
+                },
+                Event::Mouse(m) => match m.kind {
+                    MouseEventKind::ScrollUp => select_step(&mut state, partitions.len(), -1),
+                    MouseEventKind::ScrollDown => select_step(&mut state, partitions.len(), 1),
+                    _ => {}
+                },
+                _ => {}
             }
         }
 
         Ok(format!("Viewed partitions on {}", disk.display()))
     }
 
+    /// Clear GPT entry `partition_index` (1-based) on `disk` and write the table back.
+    fn delete_partition_tui(
+        disk: &PathBuf,
+        term: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        partition_index: u32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        term.draw(|f| {
+            let area = centered_rect(f.size(), 60, 3);
+            let notice = Paragraph::new(format!("Deleting partition {partition_index}…"))
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(Clear, area);
+            f.render_widget(notice, area);
+        })?;
+
+        let mut file = File::options().read(true).write(true).open(disk)?;
+        let mut gpt = read_gpt(&mut file).ok_or("No GPT found")?;
+        if gpt[partition_index].is_unused() {
+            return Err(format!("Partition {partition_index} is not in use").into());
+        }
+
+        gpt[partition_index] = GPTPartitionEntry::empty();
+
+        file.seek(SeekFrom::Start(0))?;
+        gpt.write_into(&mut file).map_err(io::Error::other)?;
+
+        let remaining = gpt.iter().filter(|(_, e)| e.is_used()).count();
+        let mut msg = format!(
+            "Deleted partition {} on {}",
+            partition_index,
+            disk.display()
+        );
+        if remaining == 0 {
+            msg.push_str(" — disk now appears unpartitioned");
+        }
+        Ok(msg)
+    }
+
+    /// Read-only view of the four primary entries of an MBR partition table.
+    fn view_mbr_partitions_tui(
+        disk: &Path,
+        sector: &[u8; 512],
+        term: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        keys: &KeyBindings,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let lines = describe_mbr_partitions(&parse_mbr_partitions(sector));
+
+        loop {
+            term.draw(|f| {
+                let size = f.size();
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(1)
+                    .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+                    .split(size);
+
+                let items: Vec<ListItem> =
+                    lines.iter().map(|l| ListItem::new(l.as_str())).collect();
+                let list = List::new(items).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("MBR partitions: {}", disk.display())),
+                );
+                f.render_widget(list, chunks[0]);
+
+                let footer = Paragraph::new("MBR disks are read-only here  •  q to go back")
+                    .style(Style::default().fg(Color::Green))
+                    .block(Block::default().borders(Borders::ALL));
+                f.render_widget(footer, chunks[1]);
+            })?;
+
+            if event::poll(std::time::Duration::from_millis(100))?
+                && let Event::Key(key) = event::read()?
+                && (key.code == keys.quit || key.code == keys.back)
+            {
+                break;
+            }
+        }
+
+        Ok(format!("Viewed MBR partitions on {}", disk.display()))
+    }
+
     /// Fully-TUI flow to enter partition name, size (MB), and choose partition type.
     /// Writes GPT changes to disk.
     fn create_partition_tui(
         disk: &PathBuf,
         term: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        keys: &KeyBindings,
+        debounce: &ActionDebounce,
     ) -> Result<String, Box<dyn std::error::Error>> {
         // open file read+write
         let mut file = File::options().read(true).write(true).open(disk)?;
 
+        // never write a GPT over an existing MBR table
+        if detect_partition_table(&mut file) == PartitionTableKind::Mbr {
+            return Ok(format!(
+                "{} has an MBR partition table. Use GPT for new partitions.",
+                disk.display()
+            ));
+        }
+
         // Read or create GPT
         let mut gpt = match GPT::read_from(&mut file, 512) {
             Ok(g) => g,
@@ -290,6 +740,9 @@ impl DiskManager {
         let linux_fs_guid = Uuid::parse_str("0FC63DAF-8483-4772-8E79-3D69D8477DE4")?; // Linux filesystem
 
         loop {
+            let (start, end) = proposed_partition_range(&gpt, size_mb);
+            let bounds = validate_partition_bounds(&gpt, start, end);
+
             // Render UI
             term.draw(|f| {
                 let size = f.size();
@@ -302,6 +755,7 @@ impl DiskManager {
                             Constraint::Length(3),
                             Constraint::Length(3),
                             Constraint::Min(3),
+                            Constraint::Length(3),
                         ]
                         .as_ref(),
                     )
@@ -342,57 +796,51 @@ impl DiskManager {
                         .title("Partition Type (use ←/→)"),
                 );
                 f.render_widget(type_list, chunks[3]);
+
+                let (check, color) = match &bounds {
+                    Ok(()) => (format!("Fits: sectors {start}..{end}"), Color::Green),
+                    Err(err) => (format!("Cannot create: {err}"), Color::Red),
+                };
+                let check_widget = Paragraph::new(check)
+                    .style(Style::default().fg(color))
+                    .block(Block::default().borders(Borders::ALL).title("Bounds"));
+                f.render_widget(check_widget, chunks[4]);
             })?;
 
             // Input
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(k) = event::read()? {
-                    match k.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            return Ok("Creation cancelled".to_string());
-                        }
-                        KeyCode::Left => {
-                            type_choice = type_choice.saturating_sub(1);
-                        }
-                        KeyCode::Right => {
-                            type_choice = (type_choice + 1) % 2;
-                        }
-                        KeyCode::Char(c) => {
-                            // typing to name: accept visible characters; digits typed also append
-                            if !c.is_control() {
-                                name.push(c);
-                            }
-                        }
-                        KeyCode::Backspace => {
-                            name.pop();
-                        }
-                        KeyCode::Up => {
-                            // increase size by 10MB
-                            size_mb = size_mb.saturating_add(10);
-                        }
-                        KeyCode::Down => {
-                            size_mb = size_mb.saturating_sub(10);
-                        }
-                        KeyCode::Enter => {
-                            break;
-                        }
-                        _ => {}
+            if event::poll(std::time::Duration::from_millis(100))?
+                && let Event::Key(k) = event::read()?
+            {
+                match k.code {
+                    code if code == keys.quit || code == keys.back => {
+                        return Ok("Creation cancelled".to_string());
+                    }
+                    code if code == keys.menu_up => {
+                        // increase size by 10MB
+                        size_mb = size_mb.saturating_add(10);
                     }
+                    code if code == keys.menu_down => {
+                        size_mb = size_mb.saturating_sub(10);
+                    }
+                    // out-of-bounds sizes stay on screen with the error shown
+                    code if code == keys.select && bounds.is_ok() && debounce.accept() => break,
+                    KeyCode::Left => {
+                        type_choice = type_choice.saturating_sub(1);
+                    }
+                    KeyCode::Right => {
+                        type_choice = (type_choice + 1) % 2;
+                    }
+                    // typing to name: accept visible characters; digits typed also append
+                    KeyCode::Char(c) if !c.is_control() => name.push(c),
+                    KeyCode::Backspace => {
+                        name.pop();
+                    }
+                    _ => {}
                 }
             }
         }
 
-        // convert MB -> sectors (512 bytes per sector)
-        let sectors = (size_mb as u128 * 1024 * 1024 / 512) as u64;
-        // choose starting LBA: find max ending_lba among existing partitions; align to 2048
-        let last_end = gpt
-            .iter()
-            .filter(|(_, e)| e.is_used())
-            .map(|(_, e)| e.ending_lba)
-            .max()
-            .unwrap_or(2048);
-        let start = ((last_end + 2048) / 2048) * 2048 + 1;
-        let end = start + sectors.saturating_sub(1);
+        let (start, end) = proposed_partition_range(&gpt, size_mb);
 
         // build partition entry
         let mut new_entry = GPTPartitionEntry::empty();
@@ -420,8 +868,7 @@ impl DiskManager {
 
         // Seek to start (important)
         file.seek(SeekFrom::Start(0))?;
-        gpt.write_into(&mut file)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        gpt.write_into(&mut file).map_err(io::Error::other)?;
 
         Ok(format!(
             "Created partition '{}' on {} ({} MB, sectors {}..{})",
@@ -1,104 +1,317 @@
-use crate::tui::animations::{
-    Animation, ProgressAnimation, donut::DonutAnimation, progress::ProgressBarAnimation,
+use crate::config;
+use crate::downloader::{self, DownloadEntry, DownloadOptions, DownloadProgress, EntryStatus};
+use crate::tui::animations::{Animation, spinner::SpinnerAnimation};
+use crate::tui::keybindings::KeyBindings;
+use crate::tui::settings::{Settings, active_flash, is_save_shortcut};
+use crate::wget_list::{self, WgetEntry};
+use crossterm::event::{self, Event};
+use std::{
+    env,
+    error::Error,
+    io::Stdout,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
 };
-use rsille::canvas::Canvas;
-use std::{io::Stdout, thread, time::Duration};
 use tui::{
     Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::Style,
-    text::Spans,
-    widgets::{Block, Borders, List, ListItem},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Gauge, Paragraph},
 };
 
-use crate::tui::settings::Settings;
-
 pub struct Downloader;
 
 const TARGET_FPS: u64 = 30;
 const FRAME_TIME: Duration = Duration::from_micros(1_000_000 / TARGET_FPS);
 
+/// Messages sent by the download worker thread.
+pub enum DownloadProgressEvent {
+    Progress(DownloadProgress),
+    /// The worker stopped, either because every file was handled or because it was cancelled.
+    Finished(Result<(), String>),
+}
+
+/// Display state of one file from the wget-list.
+struct FileRow {
+    filename: String,
+    downloaded: u64,
+    total: Option<u64>,
+    status: EntryStatus,
+}
+
+impl FileRow {
+    fn new(entry: &WgetEntry) -> Self {
+        Self {
+            filename: entry.filename.clone(),
+            downloaded: 0,
+            total: None,
+            status: EntryStatus::Pending,
+        }
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.status.is_done() {
+            return 1.0;
+        }
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0))
+            .unwrap_or(0.0)
+    }
+
+    fn label(&self) -> String {
+        let total = self.total.map(format_size).unwrap_or_else(|| "?".into());
+        format!(
+            "{}  {} / {}  ({})",
+            self.filename,
+            format_size(self.downloaded),
+            total,
+            self.status
+        )
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Download directory: `$LFS/sources` as in the book, or `./sources` when `LFS` is unset.
+fn target_dir() -> PathBuf {
+    env::var_os("LFS")
+        .map(|lfs| PathBuf::from(lfs).join("sources"))
+        .unwrap_or_else(|| PathBuf::from("sources"))
+}
+
 impl Downloader {
+    /// Fetch the wget-list and download every file, one gauge per file. Esc cancels the
+    /// remaining downloads (the queue stays resumable) and returns once the worker has stopped.
     pub fn show_downloader(
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
         settings: &Settings,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let files = vec!["file1.tar.gz", "file2.tar.gz", "file3.tar.gz"];
-        let progress = vec![0.3, 0.5, 0.9];
+        keys: &KeyBindings,
+    ) -> Result<(), Box<dyn Error>> {
+        let entries = Self::fetch_file_list(terminal, settings)?;
+        let target = target_dir();
+        let mut rows: Vec<FileRow> = entries.iter().map(FileRow::new).collect();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let events = Self::spawn_downloads(&entries, target.clone(), Arc::clone(&cancel));
+        let mut finished: Option<Result<(), String>> = None;
+        let mut flash_message: Option<(String, Instant)> = None;
 
-        let mut last_update = std::time::Instant::now();
         loop {
-            let frame_start = std::time::Instant::now();
-            let delta = frame_start - last_update;
-            last_update = frame_start;
+            let frame_start = Instant::now();
+            loop {
+                match events.try_recv() {
+                    Ok(DownloadProgressEvent::Progress(progress)) => {
+                        if let Some(row) = rows
+                            .iter_mut()
+                            .find(|row| row.filename == progress.filename)
+                        {
+                            row.downloaded = progress.bytes_downloaded;
+                            row.total = progress.total_bytes;
+                            row.status = progress.status;
+                        }
+                    }
+                    Ok(DownloadProgressEvent::Finished(result)) => finished = Some(result),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        finished.get_or_insert_with(|| {
+                            Err("download thread exited unexpectedly".to_string())
+                        });
+                        break;
+                    }
+                }
+            }
 
-            terminal.draw(|f| {
-                let size = f.size();
+            let cancelled = cancel.load(Ordering::Relaxed);
+            if cancelled && finished.is_some() {
+                break;
+            }
+
+            let done = rows.iter().filter(|row| row.status.is_done()).count();
+            let status = match (&finished, cancelled) {
+                (Some(Ok(())), _) => {
+                    format!("Done: {done}/{} files in {}", rows.len(), target.display())
+                }
+                (Some(Err(err)), _) => format!("Failed: {err}"),
+                (None, true) => "Cancelling…".to_string(),
+                (None, false) => format!(
+                    "Downloading into {}: {done}/{} files",
+                    target.display(),
+                    rows.len()
+                ),
+            };
+            let hint = if finished.is_some() {
+                "Esc back"
+            } else {
+                "Esc cancel"
+            };
+            let flash = active_flash(&mut flash_message);
 
+            terminal.draw(|f| {
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
-                    .margin(2)
+                    .margin(1)
                     .constraints(
                         [
-                            Constraint::Percentage(50),
-                            Constraint::Percentage(25),
-                            Constraint::Percentage(25),
+                            Constraint::Length(3),
+                            Constraint::Min(3),
+                            Constraint::Length(3),
                         ]
                         .as_ref(),
                     )
-                    .split(size);
+                    .split(f.size());
 
-                let items: Vec<ListItem> = files
-                    .iter()
-                    .map(|f| ListItem::new(Spans::from(*f)))
-                    .collect();
-                let list = List::new(items).block(
+                let header = Paragraph::new(status.as_str()).block(
                     Block::default()
-                        .title(Spans::from("Downloads"))
+                        .title("📥 Downloads")
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(settings.theme.secondary_color())),
+                        .border_style(Style::default().fg(settings.theme.primary_color())),
                 );
-                f.render_widget(list, chunks[0]);
+                f.render_widget(header, chunks[0]);
 
-                // Progress bar
-                let mut progress_canvas = Canvas::new(chunks[1].width, chunks[1].height);
-                let mut progress_bar = ProgressBarAnimation::new(chunks[1].width, chunks[1].height);
+                let files_block = Block::default()
+                    .title("Files")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(settings.theme.secondary_color()));
+                let inner = files_block.inner(chunks[1]);
+                f.render_widget(files_block, chunks[1]);
 
-                for (i, prog) in progress.iter().enumerate() {
-                    progress_bar.set_progress(*prog as f64);
-                    progress_bar.render(&mut progress_canvas);
+                // Keep the first unfinished file (and the one before it) in view.
+                let visible = inner.height as usize;
+                let active = rows
+                    .iter()
+                    .position(|row| !row.status.is_done())
+                    .unwrap_or(rows.len());
+                let start = active
+                    .saturating_sub(1)
+                    .min(rows.len().saturating_sub(visible));
+                for (offset, row) in rows.iter().skip(start).take(visible).enumerate() {
+                    let color = match row.status {
+                        EntryStatus::Failed => Color::Red,
+                        status if status.is_done() => settings.theme.success_color(),
+                        _ => settings.theme.primary_color(),
+                    };
+                    let gauge = Gauge::default()
+                        .gauge_style(Style::default().fg(color))
+                        .ratio(row.ratio())
+                        .label(row.label());
+                    let area = Rect::new(inner.x, inner.y + offset as u16, inner.width, 1);
+                    f.render_widget(gauge, area);
                 }
 
-                // Render progress bar
-                let progress_block = Block::default()
-                    .title(files[0])
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(settings.theme.primary_color()));
+                let footer = match &flash {
+                    Some(flash) => format!("{flash}  •  {hint}"),
+                    None => hint.to_string(),
+                };
+                let overall = Gauge::default()
+                    .block(Block::default().title(footer).borders(Borders::ALL))
+                    .gauge_style(Style::default().fg(settings.theme.accent_color()))
+                    .ratio(if rows.is_empty() {
+                        1.0
+                    } else {
+                        done as f64 / rows.len() as f64
+                    })
+                    .label(format!("{done}/{} files", rows.len()));
+                f.render_widget(overall, chunks[2]);
+            })?;
 
-                f.render_widget(progress_block, chunks[1]);
+            if !event::poll(FRAME_TIME.saturating_sub(frame_start.elapsed()))? {
+                continue;
+            }
 
-                // Donut animation
-                let mut donut_canvas = Canvas::new(chunks[2].width, chunks[2].height);
-                let mut donut = DonutAnimation::new(chunks[2].width, chunks[2].height);
-                donut.render(&mut donut_canvas);
+            if let Event::Key(key) = event::read()? {
+                if is_save_shortcut(&key) {
+                    flash_message = Some(settings.save_flash());
+                } else if key.code == keys.back {
+                    if finished.is_some() {
+                        break;
+                    }
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            }
+        }
 
-                // Render donut
-                let donut_block = Block::default()
-                    .title("Progress")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(settings.theme.secondary_color()));
+        Ok(())
+    }
 
-                f.render_widget(donut_block, chunks[2]);
+    /// Run the download queue on a worker thread, forwarding progress over a channel.
+    fn spawn_downloads(
+        entries: &[WgetEntry],
+        target: PathBuf,
+        cancel: Arc<AtomicBool>,
+    ) -> Receiver<DownloadProgressEvent> {
+        let (tx, rx) = mpsc::channel();
+        let entries: Vec<DownloadEntry> = entries
+            .iter()
+            .map(|entry| DownloadEntry::new(&entry.url))
+            .collect();
+        thread::spawn(move || {
+            let options = DownloadOptions {
+                mirrors: config::get().mirror_list().ok().flatten().map(Arc::new),
+                cancel: Some(cancel),
+                ..Default::default()
+            };
+            let result =
+                downloader::download_files_with_options(&target, entries, options, |progress| {
+                    let _ = tx.send(DownloadProgressEvent::Progress(progress));
+                });
+            let _ = tx.send(DownloadProgressEvent::Finished(
+                result.map(|_| ()).map_err(|err| format!("{err:#}")),
+            ));
+        });
+        rx
+    }
+
+    /// Fetch the wget-list on a worker thread, showing a spinner until it arrives.
+    fn fetch_file_list(
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        settings: &Settings,
+    ) -> Result<Vec<WgetEntry>, Box<dyn Error>> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(wget_list::get_wget_list());
+        });
+
+        let mut spinner = SpinnerAnimation::new(1, 1);
+        let mut last_update = Instant::now();
+        loop {
+            let frame_start = Instant::now();
+            spinner.update(frame_start - last_update);
+            last_update = frame_start;
+
+            match rx.try_recv() {
+                Ok(Ok(raw)) => return Ok(wget_list::parse_wget_list(&raw)),
+                Ok(Err(err)) => return Err(err.into()),
+                Err(TryRecvError::Disconnected) => {
+                    return Err("wget-list fetch thread exited unexpectedly".into());
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+
+            terminal.draw(|f| {
+                let status =
+                    Paragraph::new(format!("{} Fetching wget-list…", spinner.current_frame()))
+                        .block(
+                            Block::default()
+                                .title("Downloads")
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(settings.theme.primary_color())),
+                        );
+                f.render_widget(status, f.size());
             })?;
 
-            // Frame rate limiting
             let frame_time = frame_start.elapsed();
             if frame_time < FRAME_TIME {
                 thread::sleep(FRAME_TIME - frame_time);
             }
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,197 @@
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow};
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+
+/// Key bindings shared by every TUI screen.
+///
+/// Stored as key names under `[keybindings]` in the settings file (or in the older standalone
+/// `~/.config/lpkg/keybindings.toml`), e.g.
+///
+/// ```toml
+/// menu_up = "k"
+/// menu_down = "j"
+/// back = "Backspace"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawKeyBindings", into = "RawKeyBindings")]
+pub struct KeyBindings {
+    pub menu_up: KeyCode,
+    pub menu_down: KeyCode,
+    pub select: KeyCode,
+    pub back: KeyCode,
+    pub quit: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            menu_up: KeyCode::Up,
+            menu_down: KeyCode::Down,
+            select: KeyCode::Enter,
+            back: KeyCode::Esc,
+            quit: KeyCode::Char('q'),
+        }
+    }
+}
+
+/// Presses of the select key closer together than this count as one.
+pub const ACTION_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Drops select/action presses that follow the previous accepted one too quickly, so a
+/// bouncing Enter cannot open a screen and immediately trigger an action inside it.
+///
+/// One instance is created per TUI session and passed down to every sub-screen.
+#[derive(Debug)]
+pub struct ActionDebounce {
+    window: Duration,
+    last_action: Cell<Option<Instant>>,
+}
+
+impl ActionDebounce {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_action: Cell::new(None),
+        }
+    }
+
+    /// Record an action and return whether it should be handled.
+    pub fn accept(&self) -> bool {
+        let now = Instant::now();
+        match self.last_action.get() {
+            Some(last) if now.duration_since(last) < self.window => false,
+            _ => {
+                self.last_action.set(Some(now));
+                true
+            }
+        }
+    }
+}
+
+impl Default for ActionDebounce {
+    fn default() -> Self {
+        Self::new(ACTION_DEBOUNCE)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawKeyBindings {
+    menu_up: Option<String>,
+    menu_down: Option<String>,
+    select: Option<String>,
+    back: Option<String>,
+    quit: Option<String>,
+}
+
+impl KeyBindings {
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lpkg").join("keybindings.toml"))
+    }
+
+    /// Load bindings from the user config file, using defaults when it does not exist.
+    pub fn load() -> Result<Self> {
+        match Self::config_path() {
+            Some(path) if path.exists() => {
+                let raw = fs::read_to_string(&path)
+                    .with_context(|| format!("reading {}", path.display()))?;
+                Self::from_toml(&raw).with_context(|| format!("parsing {}", path.display()))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Parse a bindings document; keys missing from it keep their default value.
+    pub fn from_toml(raw: &str) -> Result<Self> {
+        Self::try_from(toml::from_str::<RawKeyBindings>(raw)?)
+    }
+}
+
+impl TryFrom<RawKeyBindings> for KeyBindings {
+    type Error = anyhow::Error;
+
+    fn try_from(parsed: RawKeyBindings) -> Result<Self> {
+        let mut bindings = Self::default();
+        let overrides = [
+            (parsed.menu_up, &mut bindings.menu_up),
+            (parsed.menu_down, &mut bindings.menu_down),
+            (parsed.select, &mut bindings.select),
+            (parsed.back, &mut bindings.back),
+            (parsed.quit, &mut bindings.quit),
+        ];
+        for (value, slot) in overrides {
+            if let Some(value) = value {
+                *slot = parse_key_code(&value)?;
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+impl From<KeyBindings> for RawKeyBindings {
+    fn from(bindings: KeyBindings) -> Self {
+        Self {
+            menu_up: Some(key_code_name(bindings.menu_up)),
+            menu_down: Some(key_code_name(bindings.menu_down)),
+            select: Some(key_code_name(bindings.select)),
+            back: Some(key_code_name(bindings.back)),
+            quit: Some(key_code_name(bindings.quit)),
+        }
+    }
+}
+
+/// Inverse of [`parse_key_code`]; unnamed keys fall back to their debug representation.
+fn key_code_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "Up".into(),
+        KeyCode::Down => "Down".into(),
+        KeyCode::Left => "Left".into(),
+        KeyCode::Right => "Right".into(),
+        KeyCode::Enter => "Enter".into(),
+        KeyCode::Esc => "Esc".into(),
+        KeyCode::Backspace => "Backspace".into(),
+        KeyCode::Tab => "Tab".into(),
+        KeyCode::Home => "Home".into(),
+        KeyCode::End => "End".into(),
+        KeyCode::PageUp => "PageUp".into(),
+        KeyCode::PageDown => "PageDown".into(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Parse a key name such as `Up`, `Enter`, `Esc`, `F5` or a single character.
+fn parse_key_code(value: &str) -> Result<KeyCode> {
+    let mut chars = value.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    let code = match value.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        other => match other.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) => KeyCode::F(n),
+            None => return Err(anyhow!("unknown key '{value}'")),
+        },
+    };
+    Ok(code)
+}
@@ -1,48 +1,211 @@
+use crate::db;
 use crate::tui::disk_manager::DiskManager;
-use crossterm::event::{self, Event, KeyCode};
+use crate::tui::downloader::Downloader;
+use crate::tui::keybindings::ActionDebounce;
+use crate::tui::search::search_packages_tui;
+use crate::tui::settings::{Settings, active_flash, is_save_shortcut};
+use crate::tui::system_status::system_status_tui;
+use crossterm::event::{
+    self, DisableMouseCapture, Event, KeyCode, MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
 use std::error::Error;
+use std::time::{Duration, Instant};
 use tui::{
     Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
 };
 
+#[derive(Clone, Copy)]
+enum MenuAction {
+    DiskManager,
+    Search,
+    SystemStatus,
+    Downloads,
+    Settings,
+    Exit,
+}
+
+const MENU_ITEMS: &[(&str, MenuAction)] = &[
+    ("💽 Disk Manager", MenuAction::DiskManager),
+    ("🔍 Search Packages", MenuAction::Search),
+    ("🩺 Check System Status", MenuAction::SystemStatus),
+    ("📥 Download Sources", MenuAction::Downloads),
+    ("⚙️ Settings", MenuAction::Settings),
+    ("🚪 Exit", MenuAction::Exit),
+];
+
+/// How often the DB stats line in the footer is refreshed.
+const STATS_REFRESH: Duration = Duration::from_secs(30);
+
+fn stats_line(pool: Option<&db::Pool>) -> String {
+    match pool.map(db::package_stats_via_pool) {
+        Some(Ok(stats)) => format!(
+            "DB: {} packages | LTO: {:.0}% | PGO: {:.0}%",
+            stats.total,
+            stats.lto_percent(),
+            stats.pgo_percent()
+        ),
+        _ => "DB: offline".to_string(),
+    }
+}
+
+/// Map a click position to the menu entry drawn at that row inside the bordered list `area`.
+fn item_at(area: Rect, column: u16, row: u16) -> Option<usize> {
+    let inside_x = column > area.x && column < area.x + area.width.saturating_sub(1);
+    let inside_y = row > area.y && row < area.y + area.height.saturating_sub(1);
+    if !inside_x || !inside_y {
+        return None;
+    }
+    let idx = (row - area.y - 1) as usize;
+    (idx < MENU_ITEMS.len()).then_some(idx)
+}
+
 pub fn show_main_menu() -> Result<(), Box<dyn Error>> {
     let stdout = std::io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    let mut settings = Settings::load();
+    let keys = settings.keybindings.clone();
+    let debounce = ActionDebounce::default();
+    settings.apply_mouse_capture(terminal.backend_mut())?;
+
+    let pool = db::establish_pool().ok();
+    let mut stats = stats_line(pool.as_ref());
+    let mut last_stats_refresh = Instant::now();
+
+    let mut selected = 0usize;
+    let mut menu_area = Rect::default();
+    let mut flash_message: Option<(String, Instant)> = None;
 
     loop {
+        if last_stats_refresh.elapsed() >= STATS_REFRESH {
+            stats = stats_line(pool.as_ref());
+            last_stats_refresh = Instant::now();
+        }
+
+        let flash = active_flash(&mut flash_message);
         terminal.draw(|f| {
             let size = f.size();
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(2)
-                .constraints([Constraint::Length(3), Constraint::Length(3)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Length(MENU_ITEMS.len() as u16 + 2),
+                        Constraint::Length(4),
+                    ]
+                    .as_ref(),
+                )
                 .split(size);
 
-            let menu = Paragraph::new("1) Disk Manager\n0) Exit")
-                .block(Block::default().borders(Borders::ALL));
+            let items: Vec<ListItem> = MENU_ITEMS
+                .iter()
+                .enumerate()
+                .map(|(i, (label, _))| {
+                    let marker = if i == selected { "▶" } else { " " };
+                    let mut item = ListItem::new(format!("{marker} {label}"));
+                    if i == selected {
+                        item = item.style(Style::default().fg(settings.theme.primary_color()));
+                    }
+                    item
+                })
+                .collect();
+            let menu = List::new(items).block(Block::default().borders(Borders::ALL));
+            menu_area = chunks[0];
             f.render_widget(menu, chunks[0]);
 
-            let status = Paragraph::new("Use number keys to select an option")
+            let hint = if settings.mouse_enabled {
+                "↑/↓ + Enter or click to select, q to quit"
+            } else {
+                "↑/↓ + Enter to select, q to quit"
+            };
+            let hint = flash.as_deref().unwrap_or(hint);
+            let status = Paragraph::new(format!("{hint}\n{stats}"))
                 .style(Style::default().fg(Color::Yellow))
                 .block(Block::default().borders(Borders::ALL).title("Status"));
             f.render_widget(status, chunks[1]);
         })?;
 
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('1') => DiskManager::run_tui()?,
-                    KeyCode::Char('0') => break,
-                    _ => {}
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        let action = match event::read()? {
+            Event::Key(key) if is_save_shortcut(&key) => {
+                flash_message = Some(settings.save_flash());
+                None
+            }
+            Event::Key(key) => match key.code {
+                code if code == keys.menu_up => {
+                    selected = selected.saturating_sub(1);
+                    None
+                }
+                code if code == keys.menu_down => {
+                    if selected + 1 < MENU_ITEMS.len() {
+                        selected += 1;
+                    }
+                    None
                 }
+                code if code == keys.select => debounce.accept().then_some(MENU_ITEMS[selected].1),
+                code if code == keys.quit => Some(MenuAction::Exit),
+                KeyCode::Char('0') => Some(MenuAction::Exit),
+                _ => None,
+            },
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column,
+                row,
+                ..
+            }) if settings.mouse_enabled => item_at(menu_area, column, row)
+                .filter(|_| debounce.accept())
+                .map(|idx| {
+                    selected = idx;
+                    MENU_ITEMS[idx].1
+                }),
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::ScrollUp,
+                ..
+            }) => {
+                selected = selected.saturating_sub(1);
+                None
+            }
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::ScrollDown,
+                ..
+            }) => {
+                if selected + 1 < MENU_ITEMS.len() {
+                    selected += 1;
+                }
+                None
+            }
+            _ => None,
+        };
+
+        match action {
+            Some(MenuAction::DiskManager) => {
+                DiskManager::run_tui(&keys, &debounce)?;
+                terminal.clear()?;
+                settings.apply_mouse_capture(terminal.backend_mut())?;
+            }
+            Some(MenuAction::Search) => {
+                search_packages_tui(&mut terminal, &mut settings, &keys, &debounce)?
+            }
+            Some(MenuAction::SystemStatus) => system_status_tui(&mut terminal, &settings, &keys)?,
+            Some(MenuAction::Downloads) => {
+                Downloader::show_downloader(&mut terminal, &settings, &keys)?
+            }
+            Some(MenuAction::Settings) => {
+                Settings::show_settings(&mut terminal, &mut settings, &debounce)?
             }
+            Some(MenuAction::Exit) => break,
+            None => {}
         }
     }
 
+    execute!(terminal.backend_mut(), DisableMouseCapture)?;
     Ok(())
 }
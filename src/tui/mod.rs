@@ -1,5 +1,9 @@
 pub mod animations;
 pub mod disk_manager;
 pub mod downloader;
+pub mod keybindings;
 pub mod main_menu;
+pub mod package_detail;
+pub mod search;
 pub mod settings;
+pub mod system_status;
@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::io::Stdout;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use cli_clipboard::{ClipboardContext, ClipboardProvider};
+use crossterm::event::{self, Event, KeyCode, MouseEventKind};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled};
+use tui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+
+use crate::pkgs::package::PackageDefinition;
+use crate::pkgs::scaffolder;
+use crate::tui::keybindings::KeyBindings;
+use crate::tui::settings::{Settings, active_flash, is_save_shortcut};
+
+const BY_NAME_DIR: &str = "src/pkgs/by_name";
+
+/// Labeled scalar fields shown in the left panel.
+fn scalar_lines(pkg: &PackageDefinition) -> Vec<String> {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+    vec![
+        format!("name:     {}", pkg.name),
+        format!("version:  {}", pkg.version),
+        format!("source:   {}", optional(&pkg.source)),
+        format!("md5:      {}", optional(&pkg.md5)),
+        format!("stage:    {}", optional(&pkg.stage)),
+        format!("variant:  {}", optional(&pkg.variant)),
+        format!("notes:    {}", optional(&pkg.notes)),
+    ]
+}
+
+/// Build and install commands shown in the scrollable right panel.
+fn command_lines(pkg: &PackageDefinition) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (title, values) in [
+        ("build_commands", &pkg.build_commands),
+        ("install_commands", &pkg.install_commands),
+    ] {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("{title}:"));
+        if values.is_empty() {
+            lines.push("    (none)".into());
+        }
+        lines.extend(values.iter().map(|value| format!("    {value}")));
+    }
+    lines
+}
+
+/// Locate the package's `mod.rs`, preferring the `<name>_<variant>` module when one exists.
+fn module_path(pkg: &PackageDefinition) -> PathBuf {
+    let mut candidates = Vec::new();
+    if let Some(variant) = &pkg.variant {
+        candidates.push(scaffolder::module_path(
+            BY_NAME_DIR,
+            &format!("{}_{}", pkg.name, variant),
+        ));
+    }
+    let fallback = scaffolder::module_path(BY_NAME_DIR, &pkg.name);
+    candidates
+        .into_iter()
+        .find(|path| path.exists())
+        .unwrap_or(fallback)
+}
+
+/// Suspend the TUI and open `path` in `$EDITOR` (falling back to `vi`).
+fn open_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    path: &Path,
+) -> Result<String, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(format!("No module found at {}", path.display()));
+    }
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+
+    let raw = is_raw_mode_enabled()?;
+    if raw {
+        disable_raw_mode()?;
+    }
+    let status = Command::new(&editor).arg(path).status();
+    if raw {
+        enable_raw_mode()?;
+    }
+    terminal.clear()?;
+
+    Ok(match status {
+        Ok(status) if status.success() => format!("Edited {}", path.display()),
+        Ok(status) => format!("{editor} exited with {status}"),
+        Err(err) => format!("Failed to launch {editor}: {err}"),
+    })
+}
+
+fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut ctx = ClipboardContext::new()?;
+    ctx.set_contents(text.to_owned())?;
+    Ok(())
+}
+
+/// Two-panel view of a package definition: scalar fields on the left, build and install
+/// commands on the right. `e` edits the module source, `c` copies the source URL.
+pub fn package_detail_tui(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    settings: &mut Settings,
+    keys: &KeyBindings,
+    pkg: &PackageDefinition,
+) -> Result<(), Box<dyn Error>> {
+    let scalars = scalar_lines(pkg);
+    let commands = command_lines(pkg);
+    let mut top = 0usize;
+    let mut status = String::new();
+    let mut flash_message: Option<(String, Instant)> = None;
+
+    loop {
+        let flash = active_flash(&mut flash_message);
+        terminal.draw(|f| {
+            let size = f.size();
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+                .split(size);
+            let panels = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+                .split(rows[0]);
+
+            let fields: Vec<ListItem> = scalars
+                .iter()
+                .map(|line| ListItem::new(line.clone()))
+                .collect();
+            let fields = List::new(fields).block(
+                Block::default()
+                    .title(format!("📦 {} {}", pkg.name, pkg.version))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(settings.theme.primary_color())),
+            );
+            f.render_widget(fields, panels[0]);
+
+            let command_items: Vec<ListItem> = commands
+                .iter()
+                .skip(top)
+                .map(|line| ListItem::new(line.clone()))
+                .collect();
+            let command_list = List::new(command_items)
+                .block(Block::default().title("Commands").borders(Borders::ALL));
+            f.render_widget(command_list, panels[1]);
+
+            let hint = "↑/↓ scroll  •  e edit  •  c copy source  •  Esc/q back";
+            let footer = match (&flash, status.is_empty()) {
+                (Some(flash), _) => format!("{flash}  •  {hint}"),
+                (None, true) => hint.to_string(),
+                (None, false) => format!("{status}  •  {hint}"),
+            };
+            let footer = Paragraph::new(footer)
+                .style(Style::default().fg(Color::Yellow))
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(footer, rows[1]);
+        })?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if is_save_shortcut(&key) => {
+                flash_message = Some(settings.save_flash());
+            }
+            Event::Key(key) => match key.code {
+                code if code == keys.back || code == keys.quit => break,
+                code if code == keys.menu_up => top = top.saturating_sub(1),
+                code if code == keys.menu_down && top + 1 < commands.len() => top += 1,
+                KeyCode::Char('e') => status = open_in_editor(terminal, &module_path(pkg))?,
+                KeyCode::Char('c') => {
+                    status = match &pkg.source {
+                        Some(source) => match copy_to_clipboard(source) {
+                            Ok(()) => "Copied source URL to clipboard".to_string(),
+                            Err(err) => format!("Clipboard unavailable: {err}"),
+                        },
+                        None => "Package has no source URL".to_string(),
+                    };
+                }
+                _ => {}
+            },
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollUp => top = top.saturating_sub(1),
+                MouseEventKind::ScrollDown if top + 1 < commands.len() => top += 1,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
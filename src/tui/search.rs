@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::io::Stdout;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, MouseEventKind};
+use tui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::db::{self, models::Package};
+use crate::tui::keybindings::{ActionDebounce, KeyBindings};
+use crate::tui::package_detail::package_detail_tui;
+use crate::tui::settings::{Settings, active_flash, is_save_shortcut};
+
+/// Delay between the last keystroke and the database query.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(200);
+const SEARCH_LIMIT: i64 = 100;
+
+/// Interactive package search backed by the package database. Esc returns to the caller.
+pub fn search_packages_tui(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    settings: &mut Settings,
+    keys: &KeyBindings,
+    debounce: &ActionDebounce,
+) -> Result<(), Box<dyn Error>> {
+    let pool = db::establish_pool()?;
+
+    let mut input = String::new();
+    let mut results: Vec<Package> = Vec::new();
+    let mut state = ListState::default();
+    let mut status = String::from("Type to search packages");
+    let mut last_keystroke: Option<Instant> = None;
+    let mut flash_message: Option<(String, Instant)> = None;
+
+    loop {
+        let flash = active_flash(&mut flash_message);
+        terminal.draw(|f| {
+            let size = f.size();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints(
+                    [
+                        Constraint::Length(3),
+                        Constraint::Min(3),
+                        Constraint::Length(3),
+                    ]
+                    .as_ref(),
+                )
+                .split(size);
+
+            let field = Paragraph::new(format!("{input}▏")).block(
+                Block::default()
+                    .title("🔍 Search Packages")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(settings.theme.primary_color())),
+            );
+            f.render_widget(field, chunks[0]);
+
+            let items: Vec<ListItem> = results
+                .iter()
+                .map(|pkg| {
+                    let mut label = format!("{} {}", pkg.name, pkg.version);
+                    if let Some(variant) = &pkg.variant {
+                        label.push_str(&format!(" ({variant})"));
+                    }
+                    ListItem::new(label)
+                })
+                .collect();
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .title(format!("Results ({})", results.len()))
+                        .borders(Borders::ALL),
+                )
+                .highlight_style(Style::default().fg(settings.theme.primary_color()))
+                .highlight_symbol("▶ ");
+            f.render_stateful_widget(list, chunks[1], &mut state);
+
+            let footer = Paragraph::new(format!(
+                "{}  •  ↑/↓ select  •  Enter details  •  Esc back",
+                flash.as_deref().unwrap_or(&status)
+            ))
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL));
+            f.render_widget(footer, chunks[2]);
+        })?;
+
+        if let Some(at) = last_keystroke
+            && at.elapsed() >= SEARCH_DEBOUNCE
+        {
+            last_keystroke = None;
+            match db::search_packages_via_pool(&pool, &input, Some(SEARCH_LIMIT)) {
+                Ok(found) => {
+                    status = format!("{} match(es)", found.len());
+                    results = found;
+                }
+                Err(err) => {
+                    status = format!("Search failed: {err}");
+                    results.clear();
+                }
+            }
+            state.select((!results.is_empty()).then_some(0));
+        }
+
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if is_save_shortcut(&key) => {
+                flash_message = Some(settings.save_flash());
+            }
+            Event::Key(key) => match key.code {
+                code if code == keys.back => break,
+                code if code == keys.menu_up => select_offset(&mut state, results.len(), -1),
+                code if code == keys.menu_down => select_offset(&mut state, results.len(), 1),
+                code if code == keys.select => {
+                    if !debounce.accept() {
+                        continue;
+                    }
+                    if let Some(pkg) = state.selected().and_then(|idx| results.get(idx)) {
+                        match pkg.clone().into_definition() {
+                            Ok(definition) => {
+                                package_detail_tui(terminal, settings, keys, &definition)?
+                            }
+                            Err(err) => status = format!("Failed to load package: {err}"),
+                        }
+                    }
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    last_keystroke = Some(Instant::now());
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    last_keystroke = Some(Instant::now());
+                }
+                _ => {}
+            },
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollUp => select_offset(&mut state, results.len(), -1),
+                MouseEventKind::ScrollDown => select_offset(&mut state, results.len(), 1),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn select_offset(state: &mut ListState, len: usize, offset: isize) {
+    if len == 0 {
+        state.select(None);
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    let next = (current + offset).clamp(0, len as isize - 1);
+    state.select(Some(next as usize));
+}
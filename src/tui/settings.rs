@@ -1,26 +1,405 @@
-use std::io::Stdout;
-use tui::{Terminal, backend::CrosstermBackend};
+use crate::tui::keybindings::{ActionDebounce, KeyBindings};
+use anyhow::{Context, Result};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEventKind,
+};
+use crossterm::execute;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Stdout, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
 
+/// How long a status-bar flash such as "Settings saved ✅" stays visible.
+pub const FLASH_DURATION: Duration = Duration::from_secs(1);
+
+/// `Ctrl-S`, handled by every screen to save the settings.
+pub fn is_save_shortcut(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Text of a flash still inside `FLASH_DURATION`; expired flashes are cleared.
+pub fn active_flash(flash_message: &mut Option<(String, Instant)>) -> Option<String> {
+    if flash_message
+        .as_ref()
+        .is_some_and(|(_, at)| at.elapsed() >= FLASH_DURATION)
+    {
+        *flash_message = None;
+    }
+    flash_message.as_ref().map(|(text, _)| text.clone())
+}
+
+/// User preferences persisted to `~/.config/lpkg/settings.toml`.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub theme: Theme,
+    /// Capture mouse events so menu items can be clicked.
+    pub mouse_enabled: bool,
+    pub keybindings: KeyBindings,
 }
 
-pub struct Theme;
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            mouse_enabled: true,
+            keybindings: KeyBindings::default(),
+        }
+    }
+}
+
+/// An sRGB colour as stored in `settings.toml` (`primary = [r, g, b]`).
+pub type Rgb = (u8, u8, u8);
+
+/// UI colours. `Custom` is persisted as a `[theme.custom]` table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Default,
+    Custom {
+        primary: Rgb,
+        secondary: Rgb,
+        accent: Rgb,
+        success: Rgb,
+    },
+}
 
 impl Theme {
-    pub fn primary_color(&self) -> tui::style::Color {
-        tui::style::Color::Cyan
+    pub fn primary_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::Cyan,
+            Theme::Custom { primary, .. } => rgb(*primary),
+        }
+    }
+    pub fn secondary_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::White,
+            Theme::Custom { secondary, .. } => rgb(*secondary),
+        }
+    }
+    pub fn accent_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::Yellow,
+            Theme::Custom { accent, .. } => rgb(*accent),
+        }
     }
-    pub fn secondary_color(&self) -> tui::style::Color {
-        tui::style::Color::White
+    pub fn success_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::Green,
+            Theme::Custom { success, .. } => rgb(*success),
+        }
     }
+
+    /// The four colours in edit order: primary, secondary, accent, success.
+    fn channels(&self) -> [Rgb; 4] {
+        match *self {
+            Theme::Default => [(0, 255, 255), (255, 255, 255), (255, 255, 0), (0, 255, 0)],
+            Theme::Custom {
+                primary,
+                secondary,
+                accent,
+                success,
+            } => [primary, secondary, accent, success],
+        }
+    }
+}
+
+fn rgb((r, g, b): Rgb) -> Color {
+    Color::Rgb(r, g, b)
 }
 
+const THEME_COLOR_NAMES: [&str; 4] = ["Primary", "Secondary", "Accent", "Success"];
+const CHANNEL_NAMES: [&str; 3] = ["red", "green", "blue"];
+const SETTINGS_ITEMS: usize = 3;
+
 impl Settings {
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lpkg").join("settings.toml"))
+    }
+
+    /// Load settings from the config file, falling back to defaults when it is missing or
+    /// invalid. Without a `[keybindings]` section the standalone keybindings file is used.
+    pub fn load() -> Self {
+        let raw = Self::config_path().and_then(|path| fs::read_to_string(path).ok());
+        let (mut settings, has_keybindings) = match raw.as_deref().map(Self::from_toml) {
+            Some(Ok(loaded)) => loaded,
+            Some(Err(err)) => {
+                tracing::warn!("ignoring invalid settings file: {err:#}");
+                (Self::default(), false)
+            }
+            None => (Self::default(), false),
+        };
+
+        if !has_keybindings {
+            match KeyBindings::load() {
+                Ok(keybindings) => settings.keybindings = keybindings,
+                Err(err) => tracing::warn!("ignoring invalid keybindings file: {err:#}"),
+            }
+        }
+        settings
+    }
+
+    fn from_toml(raw: &str) -> Result<(Self, bool)> {
+        let table: toml::Table = toml::from_str(raw).context("parsing settings")?;
+        let has_keybindings = table.contains_key("keybindings");
+        let settings = table.try_into().context("parsing settings")?;
+        Ok((settings, has_keybindings))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::config_path().context("no config directory available")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let data = toml::to_string_pretty(self).context("serializing settings")?;
+        fs::write(&path, data).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Enable or disable crossterm mouse capture to match `mouse_enabled`.
+    pub fn apply_mouse_capture(&self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.mouse_enabled {
+            execute!(out, EnableMouseCapture)
+        } else {
+            execute!(out, DisableMouseCapture)
+        }
+    }
+
     pub fn show_settings(
-        _terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        settings: &mut Settings,
+        debounce: &ActionDebounce,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Render settings UI here
+        let keys = settings.keybindings.clone();
+        let mut status = String::new();
+        let mut selected = 0usize;
+        let mut flash_message: Option<(String, Instant)> = None;
+        loop {
+            let flash = active_flash(&mut flash_message);
+            terminal.draw(|f| {
+                let size = f.size();
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(2)
+                    .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+                    .split(size);
+
+                let labels = [
+                    format!(
+                        "Mouse support: {}",
+                        if settings.mouse_enabled { "on" } else { "off" }
+                    ),
+                    "Edit Custom Theme".to_string(),
+                    format!(
+                        "Reset theme to default{}",
+                        if settings.theme == Theme::Default {
+                            " (active)"
+                        } else {
+                            ""
+                        }
+                    ),
+                ];
+                let items: Vec<ListItem> = labels
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, label)| {
+                        let marker = if i == selected { "▶" } else { " " };
+                        ListItem::new(format!("{marker} {label}"))
+                    })
+                    .collect();
+                let list = List::new(items).block(
+                    Block::default()
+                        .title("Settings")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(settings.theme.primary_color())),
+                );
+                f.render_widget(list, chunks[0]);
+
+                let hint = "↑/↓ select  •  Enter/Space apply  •  Esc/q to go back";
+                let help = match (&flash, status.is_empty()) {
+                    (Some(flash), _) => format!("{flash}  •  {hint}"),
+                    (None, true) => hint.to_string(),
+                    (None, false) => format!("{status}  •  {hint}"),
+                };
+                let help = Paragraph::new(help).block(Block::default().borders(Borders::ALL));
+                f.render_widget(help, chunks[1]);
+            })?;
+
+            if event::poll(std::time::Duration::from_millis(100))? {
+                let activate = match event::read()? {
+                    Event::Key(key) if is_save_shortcut(&key) => {
+                        flash_message = Some(settings.save_flash());
+                        false
+                    }
+                    Event::Key(key) => match key.code {
+                        code if code == keys.select || code == KeyCode::Char(' ') => true,
+                        code if code == keys.menu_up => {
+                            selected = selected.saturating_sub(1);
+                            false
+                        }
+                        code if code == keys.menu_down => {
+                            selected = (selected + 1).min(SETTINGS_ITEMS - 1);
+                            false
+                        }
+                        code if code == keys.back || code == keys.quit => break,
+                        _ => false,
+                    },
+                    Event::Mouse(mouse) => mouse.kind == MouseEventKind::Up(MouseButton::Left),
+                    _ => false,
+                };
+                if !activate || !debounce.accept() {
+                    continue;
+                }
+                match selected {
+                    0 => {
+                        settings.mouse_enabled = !settings.mouse_enabled;
+                        settings.apply_mouse_capture(terminal.backend_mut())?;
+                        status = settings.save_status();
+                    }
+                    1 => match edit_custom_theme(terminal, settings)? {
+                        Some(theme) => {
+                            settings.theme = theme;
+                            status = settings.save_status();
+                        }
+                        None => status = "Theme edit cancelled".to_string(),
+                    },
+                    _ => {
+                        settings.theme = Theme::Default;
+                        status = settings.save_status();
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
+
+    /// Save for the `Ctrl-S` shortcut and return the flash to show in the status bar.
+    pub fn save_flash(&self) -> (String, Instant) {
+        let text = match self.save() {
+            Ok(()) => "Settings saved ✅".to_string(),
+            Err(err) => format!("Failed to save settings: {err:#}"),
+        };
+        (text, Instant::now())
+    }
+
+    fn save_status(&self) -> String {
+        match self.save() {
+            Ok(()) => "Saved".to_string(),
+            Err(err) => format!("Failed to save settings: {err:#}"),
+        }
+    }
+}
+
+/// Prompt for each of the twelve colour channels in turn, starting from the current theme.
+/// Returns `None` when the user presses Esc.
+fn edit_custom_theme(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    settings: &Settings,
+) -> Result<Option<Theme>, Box<dyn std::error::Error>> {
+    let mut colors = settings.theme.channels();
+    let mut field = 0usize;
+    let mut input = String::new();
+    let mut error = String::new();
+
+    while field < THEME_COLOR_NAMES.len() * CHANNEL_NAMES.len() {
+        let (color_idx, channel_idx) = (field / 3, field % 3);
+        let current = channel(&colors[color_idx], channel_idx);
+        terminal.draw(|f| {
+            let area = f.size();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(2)
+                .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+                .split(area);
+
+            let prompt = Paragraph::new(format!(
+                "{} {} (0-255, current {current}): {input}▏",
+                THEME_COLOR_NAMES[color_idx], CHANNEL_NAMES[channel_idx]
+            ))
+            .block(
+                Block::default()
+                    .title("Edit Custom Theme")
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(prompt, chunks[0]);
+
+            let mut lines: Vec<ListItem> = THEME_COLOR_NAMES
+                .iter()
+                .zip(colors)
+                .map(|(name, color)| {
+                    ListItem::new(format!("{name}: {color:?}"))
+                        .style(Style::default().fg(rgb(color)))
+                })
+                .collect();
+            lines.push(ListItem::new(if error.is_empty() {
+                "Enter keeps the current value  •  Esc cancels".to_string()
+            } else {
+                error.clone()
+            }));
+            f.render_widget(
+                List::new(lines).block(Block::default().borders(Borders::ALL)),
+                chunks[1],
+            );
+        })?;
+
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 3 => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let value = if input.is_empty() {
+                        Ok(current)
+                    } else {
+                        input.parse::<u8>()
+                    };
+                    match value {
+                        Ok(value) => {
+                            set_channel(&mut colors[color_idx], channel_idx, value);
+                            input.clear();
+                            error.clear();
+                            field += 1;
+                        }
+                        Err(_) => error = format!("{input} is not in 0-255"),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let [primary, secondary, accent, success] = colors;
+    Ok(Some(Theme::Custom {
+        primary,
+        secondary,
+        accent,
+        success,
+    }))
+}
+
+fn channel(color: &Rgb, idx: usize) -> u8 {
+    [color.0, color.1, color.2][idx]
+}
+
+fn set_channel(color: &mut Rgb, idx: usize, value: u8) {
+    match idx {
+        0 => color.0 = value,
+        1 => color.1 = value,
+        _ => color.2 = value,
+    }
 }
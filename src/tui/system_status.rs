@@ -0,0 +1,175 @@
+use std::env;
+use std::error::Error;
+use std::io::Stdout;
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode};
+use tui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+
+use crate::db;
+use crate::tui::keybindings::KeyBindings;
+use crate::tui::settings::{Settings, active_flash, is_save_shortcut};
+use crate::version_check::run_command;
+
+const NETWORK_PROBE: &str = "8.8.8.8:53";
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A labelled host check, run on its own thread by [`run_checks`].
+type HostCheck = (&'static str, fn() -> Result<String, String>);
+
+/// Outcome of one host check: a short detail on success, the reason on failure.
+pub struct StatusCheck {
+    pub label: &'static str,
+    pub outcome: Result<String, String>,
+}
+
+impl StatusCheck {
+    fn line(&self) -> String {
+        match &self.outcome {
+            Ok(detail) => format!("✅ {}: {detail}", self.label),
+            Err(reason) => format!("❌ {}: {reason}", self.label),
+        }
+    }
+}
+
+/// Run all host checks in parallel; results keep the display order.
+pub fn run_checks() -> Vec<StatusCheck> {
+    let checks: [HostCheck; 5] = [
+        ("💽 Disk space in $LFS", check_disk_space),
+        ("📥 wget", check_wget),
+        ("🛠️ gcc", check_gcc),
+        ("🌐 Network", check_network),
+        ("🗄️ Database", check_database),
+    ];
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = checks
+            .iter()
+            .map(|&(label, check)| (label, scope.spawn(check)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|(label, handle)| StatusCheck {
+                label,
+                outcome: handle
+                    .join()
+                    .unwrap_or_else(|_| Err("check panicked".to_string())),
+            })
+            .collect()
+    })
+}
+
+/// Available space on the filesystem holding `$LFS`, as reported by `df`.
+fn check_disk_space() -> Result<String, String> {
+    let lfs = env::var("LFS").map_err(|_| "LFS is not set".to_string())?;
+    let output = run_command("df", &["-Pk", &lfs]).ok_or_else(|| format!("df failed for {lfs}"))?;
+    let available_kib: u64 = output
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(|| "unexpected df output".to_string())?;
+    Ok(format!(
+        "{:.1} GiB available in {lfs}",
+        available_kib as f64 / (1024.0 * 1024.0)
+    ))
+}
+
+fn check_wget() -> Result<String, String> {
+    run_command("which", &["wget"]).ok_or_else(|| "wget not found in PATH".to_string())
+}
+
+fn check_gcc() -> Result<String, String> {
+    run_command("gcc", &["--version"])
+        .and_then(|output| output.lines().next().map(str::to_string))
+        .ok_or_else(|| "gcc --version failed".to_string())
+}
+
+fn check_network() -> Result<String, String> {
+    let addr: SocketAddr = NETWORK_PROBE.parse().map_err(|err| format!("{err}"))?;
+    TcpStream::connect_timeout(&addr, NETWORK_TIMEOUT)
+        .map(|_| format!("reached {NETWORK_PROBE}"))
+        .map_err(|err| format!("cannot reach {NETWORK_PROBE}: {err}"))
+}
+
+fn check_database() -> Result<String, String> {
+    let pool = db::establish_pool().map_err(|err| format!("{err:#}"))?;
+    pool.get()
+        .map(|_| "connection OK".to_string())
+        .map_err(|err| err.to_string())
+}
+
+/// Host readiness screen. `r` re-runs the checks, Esc returns to the caller.
+pub fn system_status_tui(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    settings: &Settings,
+    keys: &KeyBindings,
+) -> Result<(), Box<dyn Error>> {
+    let mut results: Option<Vec<StatusCheck>> = None;
+    let mut flash_message: Option<(String, Instant)> = None;
+
+    loop {
+        let flash = active_flash(&mut flash_message);
+        terminal.draw(|f| {
+            let size = f.size();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+                .split(size);
+
+            let items: Vec<ListItem> = match &results {
+                Some(checks) => checks
+                    .iter()
+                    .map(|check| ListItem::new(check.line()))
+                    .collect(),
+                None => vec![ListItem::new("⏳ Running checks…")],
+            };
+            let list = List::new(items).block(
+                Block::default()
+                    .title("🩺 System Status")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(settings.theme.primary_color())),
+            );
+            f.render_widget(list, chunks[0]);
+
+            let hint = "r re-run checks  •  Esc back";
+            let footer = match &flash {
+                Some(flash) => format!("{flash}  •  {hint}"),
+                None => hint.to_string(),
+            };
+            let footer = Paragraph::new(footer)
+                .style(Style::default().fg(Color::Yellow))
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(footer, chunks[1]);
+        })?;
+
+        if results.is_none() {
+            results = Some(run_checks());
+            continue;
+        }
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                _ if is_save_shortcut(&key) => flash_message = Some(settings.save_flash()),
+                code if code == keys.back => break,
+                KeyCode::Char('r') => results = None,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+use std::env;
 use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
 
 /// Führt ein Kommando aus und gibt stdout zurück
-fn run_command(cmd: &str, args: &[&str]) -> Option<String> {
+pub fn run_command(cmd: &str, args: &[&str]) -> Option<String> {
     let output = Command::new(cmd).args(args).output().ok()?;
     if output.status.success() {
         Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
@@ -21,53 +27,242 @@ fn check_version(installed: &str, required: &str) -> bool {
     }
 }
 
-/// Prüft einen <pre>-Block auf Versionen
-pub fn run_version_checks_from_block(block: &str) -> bool {
-    let mut ok = true;
-
-    for line in block.lines() {
-        let line = line.trim();
-        if line.starts_with("ver_check") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 4 {
-                let prog = parts[1];
-                let cmd = parts[2];
-                let ver = parts[3];
-                let installed = run_command(cmd, &["--version"]).unwrap_or_default();
-                let ver_inst = installed
-                    .lines()
+/// Ergebnis der Prüfungen eines <pre>-Blocks
+#[derive(Debug, Clone)]
+pub struct BlockReport {
+    pub ok: bool,
+    /// Programme, die nicht gefunden wurden („Cannot find“)
+    pub missing: Vec<String>,
+}
+
+/// Eine geparste Zeile aus dem version-check.sh-Block des LFS-Buchs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionCheckLine {
+    /// `ver_check <Programm> <Kommando> <Mindestversion> ["<Regex>"]`
+    VkCheck {
+        program: String,
+        command: String,
+        min_version: String,
+        /// Regex, dessen erste Gruppe (sonst der ganze Treffer) die Version aus `--version` liest
+        regex: Option<String>,
+    },
+    /// `ver_kernel <Mindestversion>`
+    VkKernel { min_version: String },
+}
+
+static VER_CHECK_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ver_check\s+(\S+)\s+(\S+)\s+(\S+)(.*)$").unwrap());
+
+/// Parst die `ver_check`- und `ver_kernel`-Zeilen eines <pre>-Blocks, ohne sie auszuführen.
+/// Unvollständige und alle anderen Zeilen werden übersprungen. Ein gequotetes Token nach der
+/// Mindestversion gilt als Regex und muss gültig sein.
+pub fn parse_version_check_lines(block: &str) -> Result<Vec<VersionCheckLine>> {
+    let mut lines = Vec::new();
+    for line in block.lines().map(str::trim) {
+        if let Some(caps) = VER_CHECK_LINE.captures(line) {
+            let rest = caps[4].trim();
+            let regex = if rest.starts_with(['"', '\'']) {
+                let pattern = shell_words::split(rest)
+                    .with_context(|| format!("Splitting version regex in `{line}`"))?
+                    .into_iter()
                     .next()
-                    .unwrap_or("")
-                    .split_whitespace()
-                    .last()
-                    .unwrap_or("");
-                if check_version(ver_inst, ver) {
-                    println!("OK: {} {} >= {}", prog, ver_inst, ver);
+                    .unwrap_or_default();
+                Regex::new(&pattern)
+                    .with_context(|| format!("Invalid version regex {pattern:?} in `{line}`"))?;
+                Some(pattern)
+            } else {
+                None
+            };
+            lines.push(VersionCheckLine::VkCheck {
+                program: caps[1].to_string(),
+                command: caps[2].to_string(),
+                min_version: caps[3].to_string(),
+                regex,
+            });
+        } else if let ["ver_kernel", min_version, ..] =
+            line.split_whitespace().collect::<Vec<_>>().as_slice()
+        {
+            lines.push(VersionCheckLine::VkKernel {
+                min_version: min_version.to_string(),
+            });
+        }
+    }
+    Ok(lines)
+}
+
+/// Liest die Version aus der `--version`-Ausgabe: per Regex, falls angegeben, sonst das letzte
+/// Wort der ersten Zeile
+fn extract_version<'a>(output: &'a str, regex: Option<&str>) -> &'a str {
+    if let Some(pattern) = regex {
+        return Regex::new(pattern)
+            .ok()
+            .and_then(|re| {
+                let caps = re.captures(output)?;
+                caps.get(1).or_else(|| caps.get(0))
+            })
+            .map(|m| m.as_str())
+            .unwrap_or("");
+    }
+    output
+        .lines()
+        .next()
+        .unwrap_or("")
+        .split_whitespace()
+        .last()
+        .unwrap_or("")
+}
+
+/// Prüft einen <pre>-Block auf Versionen.
+///
+/// Erwartet wird der Inhalt von version-check.sh aus Kapitel 2 des LFS-Buchs. Ausgewertet werden
+/// nur zwei Zeilenarten, alles andere wird ignoriert:
+///
+/// * `ver_check <Programm> <Kommando> <Mindestversion>` führt `<Kommando> --version` aus und
+///   vergleicht das letzte Wort der ersten Ausgabezeile per semver mit der Mindestversion. Ein
+///   optionales fünftes, gequotetes Token ist ein Regex, der die Version stattdessen ausliest.
+/// * `ver_kernel <Mindestversion>` vergleicht `uname -r` numerisch mit der Mindestversion.
+///
+/// Gibt `true` zurück, wenn jedes Programm gefunden wurde und alle Versionen ausreichen; ein
+/// ungültiger Regex zählt als Fehlschlag. Details liefert [`check_block`].
+pub fn run_version_checks_from_block(block: &str) -> bool {
+    match check_block(block) {
+        Ok(report) => report.ok,
+        Err(err) => {
+            eprintln!("ERROR: {err:#}");
+            false
+        }
+    }
+}
+
+/// Prüft einen <pre>-Block und sammelt fehlende Programme. Schlägt fehl, wenn ein
+/// `ver_check`-Regex ungültig ist.
+pub fn check_block(block: &str) -> Result<BlockReport> {
+    let mut report = BlockReport {
+        ok: true,
+        missing: Vec::new(),
+    };
+
+    for line in parse_version_check_lines(block)? {
+        match line {
+            VersionCheckLine::VkCheck {
+                program,
+                command,
+                min_version,
+                regex,
+            } => {
+                let Some(installed) = run_command(&command, &["--version"]) else {
+                    eprintln!("ERROR: Cannot find {} ({})", program, command);
+                    report.ok = false;
+                    report.missing.push(command);
+                    continue;
+                };
+                let ver_inst = extract_version(&installed, regex.as_deref());
+                if check_version(ver_inst, &min_version) {
+                    println!("OK: {} {} >= {}", program, ver_inst, min_version);
                 } else {
-                    eprintln!("ERROR: {} {} < {}", prog, ver_inst, ver);
-                    ok = false;
+                    eprintln!("ERROR: {} {} < {}", program, ver_inst, min_version);
+                    report.ok = false;
                 }
             }
-        } else if line.starts_with("ver_kernel") {
-            if let Some(ver) = line.split_whitespace().nth(1) {
+            VersionCheckLine::VkKernel { min_version } => {
                 let kernel = run_command("uname", &["-r"]).unwrap_or_default();
                 let installed = kernel
                     .split(['-', '.'])
                     .filter_map(|s| s.parse::<u32>().ok())
                     .collect::<Vec<_>>();
-                let required = ver
+                let required = min_version
                     .split(['-', '.'])
                     .filter_map(|s| s.parse::<u32>().ok())
                     .collect::<Vec<_>>();
                 if installed >= required {
-                    println!("OK: Linux Kernel {} >= {}", kernel, ver);
+                    println!("OK: Linux Kernel {} >= {}", kernel, min_version);
                 } else {
-                    eprintln!("ERROR: Linux Kernel {} < {}", kernel, ver);
-                    ok = false;
+                    eprintln!("ERROR: Linux Kernel {} < {}", kernel, min_version);
+                    report.ok = false;
                 }
             }
         }
     }
 
-    ok
+    Ok(report)
+}
+
+/// Paketmanager, für die Installationsvorschläge erzeugt werden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Apt,
+    Pacman,
+    Dnf,
+}
+
+/// Paketnamen (Debian-Schreibweise) für die in ver_check geprüften Programme
+static INSTALL_PACKAGES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    HashMap::from([
+        ("bash", "bash"),
+        ("ld", "binutils"),
+        ("bison", "bison"),
+        ("sort", "coreutils"),
+        ("diff", "diffutils"),
+        ("find", "findutils"),
+        ("gawk", "gawk"),
+        ("gcc", "gcc"),
+        ("g++", "g++"),
+        ("grep", "grep"),
+        ("gzip", "gzip"),
+        ("m4", "m4"),
+        ("make", "make"),
+        ("patch", "patch"),
+        ("perl", "perl"),
+        ("python3", "python3"),
+        ("sed", "sed"),
+        ("tar", "tar"),
+        ("texi2any", "texinfo"),
+        ("makeinfo", "texinfo"),
+        ("xz", "xz-utils"),
+    ])
+});
+
+impl PackageManager {
+    /// Erkennt den Paketmanager anhand von apt-get, pacman oder dnf im PATH
+    pub fn detect() -> Option<Self> {
+        [
+            ("apt-get", Self::Apt),
+            ("pacman", Self::Pacman),
+            ("dnf", Self::Dnf),
+        ]
+        .into_iter()
+        .find(|(binary, _)| in_path(binary))
+        .map(|(_, manager)| manager)
+    }
+
+    /// Installationskommando für ein fehlendes Programm, falls bekannt
+    pub fn install_command(self, program: &str) -> Option<Vec<String>> {
+        let package = *INSTALL_PACKAGES.get(program)?;
+        let package = match (self, package) {
+            (Self::Pacman, "g++") => "gcc",
+            (Self::Pacman, "python3") => "python",
+            (Self::Pacman, "xz-utils") => "xz",
+            (Self::Dnf, "g++") => "gcc-c++",
+            (Self::Dnf, "xz-utils") => "xz",
+            (_, package) => package,
+        };
+        let base: &[&str] = match self {
+            Self::Apt => &["sudo", "apt-get", "install", "-y"],
+            Self::Pacman => &["sudo", "pacman", "-S", "--noconfirm"],
+            Self::Dnf => &["sudo", "dnf", "install", "-y"],
+        };
+        Some(
+            base.iter()
+                .copied()
+                .chain([package])
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+fn in_path(binary: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
 }
@@ -1,5 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::{Context, Result};
 
+use crate::ingest::lfs::version_separator;
+use crate::md5_utils::Checksum;
+
+const KNOWN_EXTENSIONS: &[&str] = &[
+    "tar.xz", "tar.gz", "tar.bz2", "tar.zst", "tar.lz", "tgz", "zip", "patch", "sig", "asc",
+];
+
+/// A single download listed in a wget-list manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WgetEntry {
+    pub url: String,
+    pub filename: String,
+    pub package_name: Option<String>,
+    pub extension: Option<String>,
+}
+
+impl WgetEntry {
+    pub fn from_url(url: &str) -> Self {
+        let filename = url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(url)
+            .to_string();
+        let extension = KNOWN_EXTENSIONS
+            .iter()
+            .find(|ext| filename.ends_with(&format!(".{ext}")))
+            .map(|ext| ext.to_string());
+        let stem = match &extension {
+            Some(ext) => &filename[..filename.len() - ext.len() - 1],
+            None => filename.as_str(),
+        };
+        let package_name = package_name_from_stem(stem, extension.as_deref() == Some("patch"));
+
+        Self {
+            url: url.to_string(),
+            filename,
+            package_name,
+            extension,
+        }
+    }
+
+    pub fn is_patch(&self) -> bool {
+        self.extension.as_deref() == Some("patch")
+    }
+
+    pub fn is_signature(&self) -> bool {
+        matches!(self.extension.as_deref(), Some("sig" | "asc"))
+    }
+}
+
+/// Strip the version suffix from a filename stem: everything from the last `-<digit>`, the
+/// same split `ingest::lfs::split_name_version` applies to book titles. LFS patch stems
+/// (`name-version-description-revision`) drop their description and revision first.
+fn package_name_from_stem(stem: &str, is_patch: bool) -> Option<String> {
+    let stem = if is_patch {
+        stem.rsplitn(3, '-').nth(2).unwrap_or(stem)
+    } else {
+        stem
+    };
+    let cut = version_separator(stem).or_else(|| stem.find(|c: char| c.is_ascii_digit()));
+
+    let name = match cut {
+        Some(idx) => &stem[..idx],
+        None => stem,
+    };
+    let name = name.trim_end_matches(['-', '_', '.']);
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Parse the raw newline-delimited wget-list, ignoring blank lines and comments.
+pub fn parse_wget_list(raw: &str) -> Vec<WgetEntry> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(WgetEntry::from_url)
+        .collect()
+}
+
+/// Group entries by inferred package name. Entries without a package name are keyed by filename.
+pub fn group_by_package(entries: &[WgetEntry]) -> BTreeMap<String, Vec<&WgetEntry>> {
+    let mut groups: BTreeMap<String, Vec<&WgetEntry>> = BTreeMap::new();
+    for entry in entries {
+        let key = entry
+            .package_name
+            .clone()
+            .unwrap_or_else(|| entry.filename.clone());
+        groups.entry(key).or_default().push(entry);
+    }
+    groups
+}
+
+/// Return `(filename, urls)` pairs for every filename listed more than once.
+pub fn find_duplicates(entries: &[WgetEntry]) -> Vec<(String, Vec<String>)> {
+    let mut by_filename: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for entry in entries {
+        by_filename
+            .entry(entry.filename.as_str())
+            .or_default()
+            .push(entry.url.clone());
+    }
+    by_filename
+        .into_iter()
+        .filter(|(_, urls)| urls.len() > 1)
+        .map(|(filename, urls)| (filename.to_string(), urls))
+        .collect()
+}
+
+/// Render entries as an aria2c input file, attaching checksums keyed by filename when known.
+pub fn to_aria2_input(
+    entries: &[WgetEntry],
+    checksum_map: Option<&HashMap<String, Checksum>>,
+) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&entry.url);
+        out.push('\n');
+        if let Some(checksum) = checksum_map.and_then(|map| map.get(&entry.filename)) {
+            let algorithm = match checksum {
+                Checksum::Md5(_) => "md5",
+                Checksum::Sha256(_) => "sha-256",
+            };
+            out.push_str(&format!("  checksum={algorithm}={}\n", checksum.value()));
+        }
+        out.push_str(&format!("  out={}\n", entry.filename));
+    }
+    out
+}
+
 pub fn get_wget_list() -> Result<String> {
     let url = "https://www.linuxfromscratch.org/~thomas/multilib-m32/wget-list-sysv";
     let agent = ureq::AgentBuilder::new().redirects(5).build();
@@ -15,3 +150,155 @@ pub fn get_wget_list() -> Result<String> {
         .into_string()
         .with_context(|| format!("reading body from {url}"))
 }
+
+/// The two manifests every book publishes alongside its sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    WgetList,
+    Md5Sums,
+}
+
+impl ManifestKind {
+    /// File name used for cached copies under `ai/metadata/cache`.
+    pub fn filename(&self) -> &'static str {
+        match self {
+            ManifestKind::WgetList => "wget-list.txt",
+            ManifestKind::Md5Sums => "md5sums.txt",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ManifestKind::WgetList => "wget-list",
+            ManifestKind::Md5Sums => "md5sums",
+        }
+    }
+}
+
+/// Upstream URL of `kind` for `book` (`lfs`, `mlfs`, `blfs` or `glfs`).
+pub fn manifest_url(book: &str, kind: ManifestKind) -> Option<&'static str> {
+    match (book, kind) {
+        ("mlfs", ManifestKind::WgetList) => {
+            Some("https://www.linuxfromscratch.org/~thomas/multilib-m32/wget-list-sysv")
+        }
+        ("mlfs", ManifestKind::Md5Sums) => {
+            Some("https://www.linuxfromscratch.org/~thomas/multilib-m32/md5sums")
+        }
+        ("lfs", ManifestKind::WgetList) => {
+            Some("https://www.linuxfromscratch.org/lfs/view/12.1/wget-list")
+        }
+        ("lfs", ManifestKind::Md5Sums) => {
+            Some("https://www.linuxfromscratch.org/lfs/view/12.1/md5sums")
+        }
+        ("blfs", ManifestKind::WgetList) => {
+            Some("https://anduin.linuxfromscratch.org/BLFS/view/systemd/wget-list")
+        }
+        ("blfs", ManifestKind::Md5Sums) => {
+            Some("https://anduin.linuxfromscratch.org/BLFS/view/systemd/md5sums")
+        }
+        ("glfs", ManifestKind::WgetList) => {
+            Some("https://www.linuxfromscratch.org/glfs/view/glfs/wget-list")
+        }
+        ("glfs", ManifestKind::Md5Sums) => {
+            Some("https://www.linuxfromscratch.org/glfs/view/glfs/md5sums")
+        }
+        _ => None,
+    }
+}
+
+/// Download `kind` for `book` using the URL from [`manifest_url`].
+pub fn fetch_manifest(book: &str, kind: ManifestKind) -> Result<String> {
+    let url = manifest_url(book, kind)
+        .with_context(|| format!("no manifest URL configured for book '{book}'"))?;
+    let agent = ureq::AgentBuilder::new().redirects(5).build();
+    agent
+        .get(url)
+        .call()
+        .map_err(|err| match err {
+            ureq::Error::Status(code, _) => {
+                anyhow::anyhow!("Failed to fetch {}: HTTP {code}", kind.description())
+            }
+            other => anyhow::anyhow!("Failed to fetch {}: {other}", kind.description()),
+        })?
+        .into_string()
+        .with_context(|| format!("reading body from {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_group_entries() {
+        let raw = "\
+https://sourceware.org/pub/binutils/releases/binutils-2.45.tar.xz
+https://www.linuxfromscratch.org/patches/lfs/development/bzip2-1.0.8-install_docs-1.patch
+
+https://ftp.gnu.org/gnu/gcc/gcc-15.2.0/gcc-15.2.0.tar.xz
+https://ftp.gnu.org/gnu/gcc/gcc-15.2.0/gcc-15.2.0.tar.xz.sig
+https://downloads.sourceforge.net/tcl/tcl8.6.16-src.tar.gz
+";
+        let entries = parse_wget_list(raw);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].filename, "binutils-2.45.tar.xz");
+        assert_eq!(entries[0].package_name.as_deref(), Some("binutils"));
+        assert_eq!(entries[0].extension.as_deref(), Some("tar.xz"));
+        assert_eq!(entries[1].package_name.as_deref(), Some("bzip2"));
+        assert!(entries[1].is_patch());
+        assert!(entries[3].is_signature());
+        assert_eq!(entries[4].package_name.as_deref(), Some("tcl"));
+
+        let groups = group_by_package(&entries);
+        assert_eq!(groups["gcc"].len(), 2);
+        assert_eq!(groups.len(), 4);
+    }
+
+    #[test]
+    fn package_names_keep_trailing_digits() {
+        let raw = "\
+https://code.videolan.org/videolan/x264/-/archive/master/x264-20250815.tar.bz2
+https://download.gnome.org/sources/libxml2/2.14/libxml2-2.14.5.tar.xz
+https://download.gnome.org/sources/libxml2/2.14/libxml2-2.14.5-security_fixes-1.patch
+https://ftp.gnu.org/gnu/gcc/gcc-15.2.0/gcc-15.2.0.tar.xz
+";
+        let names: Vec<_> = parse_wget_list(raw)
+            .into_iter()
+            .map(|entry| entry.package_name)
+            .collect();
+        assert_eq!(
+            names,
+            ["x264", "libxml2", "libxml2", "gcc"].map(|name| Some(name.to_string()))
+        );
+    }
+
+    #[test]
+    fn detects_duplicate_filenames() {
+        let raw = "\
+https://ftp.gnu.org/gnu/m4/m4-1.4.20.tar.xz
+https://mirror.example.org/gnu/m4/m4-1.4.20.tar.xz
+https://ftp.gnu.org/gnu/make/make-4.4.1.tar.gz
+";
+        let duplicates = find_duplicates(&parse_wget_list(raw));
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0, "m4-1.4.20.tar.xz");
+        assert_eq!(duplicates[0].1.len(), 2);
+    }
+
+    #[test]
+    fn renders_aria2_input() {
+        let entries = parse_wget_list(
+            "https://ftp.gnu.org/gnu/m4/m4-1.4.20.tar.xz\nhttps://ftp.gnu.org/gnu/make/make-4.4.1.tar.gz\n",
+        );
+        let mut checksums = HashMap::new();
+        checksums.insert(
+            "m4-1.4.20.tar.xz".to_string(),
+            Checksum::Md5("6eb2ebed5b24e74b6e890919331d2132".to_string()),
+        );
+
+        let rendered = to_aria2_input(&entries, Some(&checksums));
+        assert_eq!(
+            rendered,
+            "https://ftp.gnu.org/gnu/m4/m4-1.4.20.tar.xz\n  checksum=md5=6eb2ebed5b24e74b6e890919331d2132\n  out=m4-1.4.20.tar.xz\nhttps://ftp.gnu.org/gnu/make/make-4.4.1.tar.gz\n  out=make-4.4.1.tar.gz\n"
+        );
+    }
+}